@@ -10,4 +10,5 @@ extern crate rustc_middle;
 extern crate rustc_span;
 
 pub mod plugin;
+mod text;
 pub use plugin::ArgusPlugin;
@@ -1,24 +1,28 @@
 use std::{
   borrow::Cow,
-  env, io,
+  env, fs,
+  io::{self, Write},
   path::PathBuf,
-  process::{exit, Command},
+  process::{exit, Command, Stdio},
   time::Instant,
 };
 
 use argus_ext::ty::TyCtxtExt;
 use argus_lib::{
-  analysis,
-  find_bodies::{find_bodies, find_enclosing_bodies},
-  types::{ObligationHash, ToTarget},
+  analysis::{self, PathStyle, SerializeOptions},
+  evaluation::Evaluation,
+  find_bodies::{find_bodies, find_enclosing_bodies, find_items},
+  summary::{Summary, TraitErrorSite},
+  types::{BodyBundle, ObligationHash, SharedBundle, ToTarget},
 };
-use clap::{Parser, Subcommand};
+use argus_schema::{print::PrintOptions, Versioned};
+use clap::{Parser, Subcommand, ValueEnum};
 use fluid_let::fluid_set;
-use rustc_hir::BodyId;
+use rustc_hir::{def_id::LocalDefId, BodyId};
 use rustc_interface::interface::Result as RustcResult;
 use rustc_middle::ty::TyCtxt;
 use rustc_plugin::{CrateFilter, RustcPlugin, RustcPluginArgs, Utf8Path};
-use rustc_span::{FileName, RealFileName};
+use rustc_span::{FileName, RealFileName, Span};
 use rustc_utils::{
   source_map::{
     filename::Filename,
@@ -26,7 +30,9 @@ use rustc_utils::{
   },
   timer::elapsed,
 };
-use serde::{self, Deserialize, Serialize};
+use serde::{self, de::IgnoredAny, Deserialize, Serialize};
+
+use crate::text;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -38,16 +44,206 @@ pub struct ArgusPluginArgs {
 
   #[clap(long)]
   show_stderr: bool,
+
+  /// Show and build trees for obligations that are otherwise only
+  /// reported when they fail, e.g., predicates on lang-item traits.
+  #[clap(long)]
+  force_on_error: bool,
+
+  /// Encoding of the output, the schema is the same for every format.
+  #[clap(long, value_enum, default_value = "json")]
+  format: OutputFormat,
+
+  /// Write results to `DIR/<crate><extra-filename>.<format>`, named like
+  /// Cargo's artifacts, instead of stdout. Required for binary formats,
+  /// Cargo forwards the driver's stdout as UTF-8 text.
+  #[clap(
+    long,
+    value_name = "DIR",
+    required_if_eq_any = &[("format", "cbor"), ("format", "msgpack")],
+  )]
+  out_dir: Option<PathBuf>,
+
+  #[clap(flatten)]
+  serialize: SerializeArgs,
+
+  #[clap(flatten)]
+  print: PrintArgs,
+}
+
+/// How definition paths are written to the output, for every format.
+#[derive(Parser, Serialize, Deserialize)]
+struct SerializeArgs {
+  /// Name items where they're defined, e.g., `alloc::vec::Vec`, through
+  /// public re-exports, e.g., `std::vec::Vec`, or by name alone.
+  #[clap(long, value_enum, default_value = "qualified")]
+  paths: PathsArg,
+
+  /// Prefix paths of the local crate with `crate::`.
+  #[clap(long)]
+  crate_prefix: bool,
+
+  /// Name local impls by their location rather than their types.
+  #[clap(long)]
+  impl_filename_line: bool,
+
+  /// Abbreviate types nested deeper than `N`, their subterms are replaced
+  /// by placeholders that index the type table.
+  #[clap(long, value_name = "N")]
+  max_ty_depth: Option<usize>,
+
+  /// Abbreviate types with more than `N` compound arguments.
+  #[clap(long, value_name = "N")]
+  max_ty_width: Option<usize>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Serialize, Deserialize)]
+enum PathsArg {
+  Qualified,
+  Visible,
+  Trimmed,
+}
+
+impl SerializeArgs {
+  fn options(&self) -> SerializeOptions {
+    SerializeOptions {
+      paths: match self.paths {
+        PathsArg::Qualified => PathStyle::Qualified,
+        PathsArg::Visible => PathStyle::Visible,
+        PathsArg::Trimmed => PathStyle::Trimmed,
+      },
+      crate_prefix: self.crate_prefix,
+      impl_filename_line: self.impl_filename_line,
+      max_ty_depth: self.max_ty_depth,
+      max_ty_width: self.max_ty_width,
+    }
+  }
+
+  /// Flags to pass these arguments on to another Argus command.
+  fn to_flags(&self) -> Vec<String> {
+    let paths = match self.paths {
+      PathsArg::Qualified => "qualified",
+      PathsArg::Visible => "visible",
+      PathsArg::Trimmed => "trimmed",
+    };
+    let mut flags = vec!["--paths".to_owned(), paths.to_owned()];
+    if self.crate_prefix {
+      flags.push("--crate-prefix".to_owned());
+    }
+    if self.impl_filename_line {
+      flags.push("--impl-filename-line".to_owned());
+    }
+    if let Some(depth) = self.max_ty_depth {
+      flags.extend(["--max-ty-depth".to_owned(), depth.to_string()]);
+    }
+    if let Some(width) = self.max_ty_width {
+      flags.extend(["--max-ty-width".to_owned(), width.to_string()]);
+    }
+    flags
+  }
+}
+
+/// How types are printed by `--format text`.
+#[derive(Parser, Serialize, Deserialize)]
+struct PrintArgs {
+  /// Print fully qualified paths, e.g., `alloc::vec::Vec`.
+  #[clap(long)]
+  full_paths: bool,
+
+  /// Print `Fn*` traits as `Fn<(A,)>` rather than `Fn(A)`.
+  #[clap(long)]
+  no_fn_sugar: bool,
+
+  /// Cut generic argument lists after `N` arguments.
+  #[clap(long, value_name = "N")]
+  max_generic_args: Option<usize>,
+}
+
+impl PrintArgs {
+  fn options(&self) -> PrintOptions {
+    PrintOptions {
+      trim_paths: !self.full_paths,
+      sugar_fn_traits: !self.no_fn_sugar,
+      max_generic_args: self.max_generic_args,
+    }
+  }
+}
+
+/// Binary formats are much more compact than JSON for large outputs,
+/// e.g., those of `bundle`. Text is a report for reading, not parsing.
+#[derive(ValueEnum, Clone, Copy, Serialize, Deserialize)]
+enum OutputFormat {
+  Json,
+  Cbor,
+  #[clap(name = "msgpack")]
+  MessagePack,
+  Text,
+}
+
+impl OutputFormat {
+  fn extension(self) -> &'static str {
+    match self {
+      OutputFormat::Json => "json",
+      OutputFormat::Cbor => "cbor",
+      OutputFormat::MessagePack => "msgpack",
+      OutputFormat::Text => "txt",
+    }
+  }
+
+  /// Stream `value` to `out`, text is rendered for the command in `args`.
+  fn write<T: Serialize>(
+    self,
+    args: &ArgusPluginArgs,
+    value: &Versioned<T>,
+    out: impl Write,
+  ) -> anyhow::Result<()> {
+    let mut out = io::BufWriter::new(out);
+    match self {
+      OutputFormat::Text => {
+        text::write(&args.command, value, args.print.options(), &mut out)?;
+      }
+      OutputFormat::Json => serde_json::to_writer(&mut out, value)?,
+      OutputFormat::Cbor => ciborium::into_writer(value, &mut out)
+        .map_err(|e| anyhow::anyhow!("{e:?}"))?,
+      OutputFormat::MessagePack => {
+        // Struct fields are written by name, as in the JSON output.
+        let mut serializer =
+          rmp_serde::Serializer::new(&mut out).with_struct_map();
+        value.serialize(&mut serializer)?;
+      }
+    }
+    out.flush()?;
+    Ok(())
+  }
 }
 
 #[derive(Subcommand, Serialize, Deserialize)]
-enum ArgusCommand {
+pub(crate) enum ArgusCommand {
   Preload,
   RustcVersion,
-  Bundle,
+  Bundle {
+    /// Write the types, paths and impl headers of the crate's bodies once,
+    /// in tables shared by all of them. Tables are per crate, in a workspace
+    /// every crate is written with its own tables.
+    #[clap(long)]
+    shared_tables: bool,
+  },
+  /// Rank the `//~ ROOT_CAUSE:` annotations in the workspace.
+  Evaluate,
+  /// Group the trait errors of the workspace by failing trait, self type
+  /// and root cause.
+  Summary,
+  /// Trait errors of each crate, merged by `summary`.
+  #[clap(hide = true)]
+  CrateSummary,
   Obligations {
     file: Option<String>,
   },
+  /// Obligations of impl blocks, trait and type definitions, checked
+  /// outside of any body by well-formedness and coherence checking.
+  Items {
+    file: Option<String>,
+  },
   Tree {
     file: String,
     id: ObligationHash,
@@ -59,36 +255,111 @@ enum ArgusCommand {
   },
 }
 
+/// What an analysis runs on, a body or an item.
+trait AnalysisUnit: Copy + std::fmt::Debug {
+  fn find_all(tcx: TyCtxt) -> Vec<(Span, Self)>;
+
+  fn find_enclosing(tcx: TyCtxt, span: Span) -> Vec<Self>;
+
+  fn filename(self, tcx: TyCtxt) -> FileName;
+}
+
+impl AnalysisUnit for BodyId {
+  fn find_all(tcx: TyCtxt) -> Vec<(Span, Self)> {
+    find_bodies(tcx)
+  }
+
+  fn find_enclosing(tcx: TyCtxt, span: Span) -> Vec<Self> {
+    find_enclosing_bodies(tcx, span).collect()
+  }
+
+  fn filename(self, tcx: TyCtxt) -> FileName {
+    tcx.body_filename(self)
+  }
+}
+
+impl AnalysisUnit for LocalDefId {
+  fn find_all(tcx: TyCtxt) -> Vec<(Span, Self)> {
+    find_items(tcx)
+  }
+
+  fn find_enclosing(tcx: TyCtxt, span: Span) -> Vec<Self> {
+    find_items(tcx)
+      .into_iter()
+      .filter_map(|(item_span, id)| item_span.contains(span).then_some(id))
+      .collect()
+  }
+
+  fn filename(self, tcx: TyCtxt) -> FileName {
+    tcx.sess.source_map().span_to_filename(tcx.def_span(self))
+  }
+}
+
 trait ArgusAnalysis: Sized + Send + Sync {
+  type Unit: AnalysisUnit;
   type Output: Serialize + Send + Sync;
+  /// Output of the crate, built from that of each unit.
+  type CrateOutput: Serialize + Send + Sync;
   fn analyze(
     &mut self,
     tcx: TyCtxt,
-    id: BodyId,
+    id: Self::Unit,
   ) -> anyhow::Result<Self::Output>;
+
+  /// Called on the compiler's thread once all units are analyzed.
+  fn finish(outputs: Vec<Self::Output>) -> Self::CrateOutput;
 }
 
-impl<O, F> ArgusAnalysis for F
+/// Wraps an analysis function over a unit `U`, see `AnalysisUnit`.
+struct AnalysisFn<U, F>(F, std::marker::PhantomData<fn(U)>);
+
+fn on<U, F>(f: F) -> AnalysisFn<U, F> {
+  AnalysisFn(f, std::marker::PhantomData)
+}
+
+impl<U, O, F> ArgusAnalysis for AnalysisFn<U, F>
 where
-  for<'tcx> F: Fn(TyCtxt<'tcx>, BodyId) -> anyhow::Result<O> + Send + Sync,
+  U: AnalysisUnit,
+  for<'tcx> F: Fn(TyCtxt<'tcx>, U) -> anyhow::Result<O> + Send + Sync,
   O: Serialize + Send + Sync,
 {
+  type Unit = U;
   type Output = O;
-  fn analyze(
-    &mut self,
-    tcx: TyCtxt,
-    id: BodyId,
-  ) -> anyhow::Result<Self::Output> {
-    (self)(tcx, id)
+  type CrateOutput = Vec<O>;
+  fn analyze(&mut self, tcx: TyCtxt, id: U) -> anyhow::Result<Self::Output> {
+    (self.0)(tcx, id)
+  }
+
+  fn finish(outputs: Vec<O>) -> Vec<O> {
+    outputs
+  }
+}
+
+/// Bundles the bodies of a crate with shared tables, these are kept in the
+/// compiler thread's storage until the last body is bundled.
+struct SharedBundleAnalysis;
+
+impl ArgusAnalysis for SharedBundleAnalysis {
+  type Unit = BodyId;
+  type Output = BodyBundle;
+  type CrateOutput = SharedBundle;
+  fn analyze(&mut self, tcx: TyCtxt, id: BodyId) -> anyhow::Result<BodyBundle> {
+    analysis::shared_bundle(tcx, id)
+  }
+
+  fn finish(bodies: Vec<BodyBundle>) -> SharedBundle {
+    SharedBundle::new(bodies)
   }
 }
 
 struct ArgusCallbacks<A: ArgusAnalysis, T: ToTarget, F: FnOnce() -> Option<T>> {
   show_stderr: bool,
+  force_on_error: bool,
+  options: SerializeOptions,
   file: Option<PathBuf>,
   analysis: Option<A>,
   compute_target: Option<F>,
-  result: Vec<A::Output>,
+  result: Option<A::CrateOutput>,
   rustc_start: Instant,
 }
 
@@ -115,7 +386,17 @@ impl RustcPlugin for ArgusPlugin {
 
   fn args(&self, target_dir: &Utf8Path) -> RustcPluginArgs<ArgusPluginArgs> {
     use ArgusCommand as AC;
-    let args = ArgusPluginArgs::parse_from(env::args().skip(1));
+    let mut args = ArgusPluginArgs::parse_from(env::args().skip(1));
+    // Drivers don't run in the current directory.
+    if let Some(dir) = args.out_dir.take() {
+      let dir = fs::create_dir_all(&dir)
+        .and_then(|()| dir.canonicalize())
+        .unwrap_or_else(|e| {
+          eprintln!("could not create {}: {e}", dir.display());
+          exit(1);
+        });
+      args.out_dir = Some(dir);
+    }
     let cargo_path =
       env::var("CARGO_PATH").unwrap_or_else(|_| "cargo".to_string());
 
@@ -130,20 +411,26 @@ impl RustcPlugin for ArgusPlugin {
         let exit_status = cmd.status().expect("could not run cargo");
         exit(exit_status.code().unwrap_or(-1));
       }
+      AC::Summary => summarize_workspace(&args),
       AC::RustcVersion => {
         let commit_hash =
           rustc_interface::util::rustc_version_str().unwrap_or("unknown");
         println!("{commit_hash}");
         exit(0);
       }
-      AC::Obligations { .. } | AC::Tree { .. } | AC::Bundle => {}
+      AC::Obligations { .. }
+      | AC::Items { .. }
+      | AC::Tree { .. }
+      | AC::Bundle { .. }
+      | AC::Evaluate
+      | AC::CrateSummary => {}
     };
 
     let file = match &args.command {
       AC::Tree { file, .. } => Some(file),
-      AC::Obligations { file } => file.as_ref(),
-      AC::Bundle => None,
-      AC::Preload | AC::RustcVersion => unreachable!(),
+      AC::Obligations { file } | AC::Items { file } => file.as_ref(),
+      AC::Bundle { .. } | AC::Evaluate | AC::CrateSummary => None,
+      AC::Preload | AC::RustcVersion | AC::Summary => unreachable!(),
     };
 
     let filter = file.map_or(CrateFilter::OnlyWorkspace, |file| {
@@ -184,40 +471,141 @@ impl RustcPlugin for ArgusPlugin {
         };
 
         let v = run(
-          analysis::tree,
+          on(analysis::tree),
           Some(PathBuf::from(&file)),
           compute_target,
           &plugin_args,
           &compiler_args,
         );
-        postprocess(v)
+        postprocess(&plugin_args, &compiler_args, v)
       }
       AC::Obligations { file, .. } => {
         let v = run(
-          analysis::obligations,
+          on(analysis::obligations),
+          file.as_ref().map(PathBuf::from),
+          no_target,
+          &plugin_args,
+          &compiler_args,
+        );
+        postprocess(&plugin_args, &compiler_args, v)
+      }
+      AC::Items { file, .. } => {
+        let v = run(
+          on(analysis::item_bundle),
           file.as_ref().map(PathBuf::from),
           no_target,
           &plugin_args,
           &compiler_args,
         );
-        postprocess(v)
+        postprocess(&plugin_args, &compiler_args, v)
       }
-      AC::Bundle => {
-        log::warn!("Bundling takes an enormous amount of time.");
+      AC::Bundle { shared_tables } => {
+        bundle(&plugin_args, &compiler_args, *shared_tables)
+      }
+      AC::Evaluate => {
         let v = run(
-          analysis::bundle,
+          on(analysis::evaluate),
           None,
           no_target,
           &plugin_args,
           &compiler_args,
         );
-        postprocess(v)
+        postprocess(
+          &plugin_args,
+          &compiler_args,
+          v.map(|ranks| {
+            Evaluation::from(ranks.into_iter().flatten().collect::<Vec<_>>())
+          }),
+        )
       }
-      AC::Preload | AC::RustcVersion => unreachable!(),
+      AC::CrateSummary => {
+        let v = run(
+          on(analysis::trait_error_sites),
+          None,
+          no_target,
+          &plugin_args,
+          &compiler_args,
+        );
+        postprocess(
+          &plugin_args,
+          &compiler_args,
+          v.map(|sites| sites.into_iter().flatten().collect::<Vec<_>>()),
+        )
+      }
+      AC::Preload | AC::RustcVersion | AC::Summary => unreachable!(),
     }
   }
 }
 
+/// Bundle every body of the crate, if `shared_tables` their types, paths
+/// and impl headers are written once for all of them.
+fn bundle(
+  plugin_args: &ArgusPluginArgs,
+  compiler_args: &[String],
+  shared_tables: bool,
+) -> RustcResult<()> {
+  log::warn!("Bundling takes an enormous amount of time.");
+  let no_target = || None::<(ObligationHash, CharRange)>;
+  if shared_tables {
+    let v = run(
+      SharedBundleAnalysis,
+      None,
+      no_target,
+      plugin_args,
+      compiler_args,
+    );
+    return postprocess(plugin_args, compiler_args, v);
+  }
+  let v = run(
+    on(analysis::bundle),
+    None,
+    no_target,
+    plugin_args,
+    compiler_args,
+  );
+  postprocess(plugin_args, compiler_args, v)
+}
+
+/// Each crate is analyzed by a separate driver, run `crate-summary` on the
+/// workspace and merge the sites of every crate into a single summary.
+fn summarize_workspace(args: &ArgusPluginArgs) -> ! {
+  let mut cmd =
+    Command::new(env::current_exe().expect("current executable path invalid"));
+  // Flags go before the subcommand.
+  cmd.arg("argus");
+  if args.show_stderr {
+    cmd.arg("--show-stderr");
+  }
+  if args.force_on_error {
+    cmd.arg("--force-on-error");
+  }
+  cmd.args(args.serialize.to_flags()).arg("crate-summary");
+
+  let output = cmd
+    .stderr(Stdio::inherit())
+    .output()
+    .expect("could not run cargo-argus");
+
+  // Drivers print their results back to back, one per crate.
+  let sites = serde_json::Deserializer::from_slice(&output.stdout)
+    .into_iter::<Versioned<Result<Vec<TraitErrorSite>, IgnoredAny>>>()
+    .filter_map(|crate_sites| match crate_sites {
+      Ok(Versioned {
+        output: Ok(sites), ..
+      }) => Some(sites),
+      Ok(_) => None,
+      Err(e) => {
+        log::error!("could not parse crate summary {e:?}");
+        None
+      }
+    })
+    .flatten()
+    .collect::<Vec<_>>();
+
+  write_output(args, "summary", &Summary::from(sites)).unwrap();
+  exit(output.status.code().unwrap_or(-1));
+}
+
 #[allow(clippy::unnecessary_wraps)]
 fn run<A: ArgusAnalysis, T: ToTarget>(
   analysis: A,
@@ -225,13 +613,15 @@ fn run<A: ArgusAnalysis, T: ToTarget>(
   compute_target: impl FnOnce() -> Option<T> + Send,
   plugin_args: &ArgusPluginArgs,
   args: &[String],
-) -> ArgusResult<Vec<A::Output>> {
+) -> ArgusResult<A::CrateOutput> {
   let mut callbacks = ArgusCallbacks {
     file,
     show_stderr: plugin_args.show_stderr,
+    force_on_error: plugin_args.force_on_error,
+    options: plugin_args.serialize.options(),
     analysis: Some(analysis),
     compute_target: Some(compute_target),
-    result: Vec::default(),
+    result: None,
     rustc_start: Instant::now(),
   };
 
@@ -240,7 +630,7 @@ fn run<A: ArgusAnalysis, T: ToTarget>(
   #[allow(unused_must_use)]
   let _ = run_with_callbacks(args, &mut callbacks);
 
-  Ok(callbacks.result)
+  Ok(callbacks.result.unwrap_or_else(|| A::finish(Vec::new())))
 }
 
 #[allow(clippy::unnecessary_wraps)]
@@ -271,11 +661,58 @@ pub fn run_with_callbacks(
 }
 
 #[allow(clippy::unnecessary_wraps)]
-fn postprocess<T: Serialize>(result: T) -> RustcResult<()> {
-  serde_json::to_writer(io::stdout(), &result).unwrap();
+fn postprocess<T: Serialize>(
+  plugin_args: &ArgusPluginArgs,
+  compiler_args: &[String],
+  result: T,
+) -> RustcResult<()> {
+  write_output(plugin_args, &artifact_name(compiler_args), &result).unwrap();
   Ok(())
 }
 
+/// Write `value` to stdout, or to the file `name` in the output directory,
+/// tagged with the schema version.
+fn write_output<T: Serialize>(
+  args: &ArgusPluginArgs,
+  name: &str,
+  value: &T,
+) -> anyhow::Result<()> {
+  let value = &Versioned::new(value);
+  match &args.out_dir {
+    Some(dir) => {
+      let path = dir.join(name).with_extension(args.format.extension());
+      args.format.write(args, value, fs::File::create(path)?)
+    }
+    None => args.format.write(args, value, io::stdout().lock()),
+  }
+}
+
+/// The crate name and Cargo's disambiguating suffix, e.g., `foo-1a2b3c`.
+fn artifact_name(compiler_args: &[String]) -> String {
+  let mut name = "crate";
+  let mut extra = "";
+  let mut args = compiler_args.iter().map(String::as_str);
+  while let Some(arg) = args.next() {
+    match arg {
+      "--crate-name" => name = args.next().unwrap_or(name),
+      "-C" => {
+        if let Some(suffix) = args
+          .next()
+          .and_then(|cg| cg.strip_prefix("extra-filename="))
+        {
+          extra = suffix;
+        }
+      }
+      _ => {
+        if let Some(suffix) = arg.strip_prefix("-Cextra-filename=") {
+          extra = suffix;
+        }
+      }
+    }
+  }
+  format!("{name}{extra}")
+}
+
 impl<A: ArgusAnalysis, T: ToTarget, F: FnOnce() -> Option<T>>
   rustc_driver::Callbacks for ArgusCallbacks<A, T, F>
 {
@@ -306,17 +743,17 @@ impl<A: ArgusAnalysis, T: ToTarget, F: FnOnce() -> Option<T>>
       elapsed("global_ctxt", start);
       let mut analysis = self.analysis.take().unwrap();
       let target_file = self.file.as_ref();
+      let options = self.options;
+      fluid_set!(analysis::FORCE_ON_ERROR, self.force_on_error);
 
-      let mut inner = |(_, body)| {
-        if let FileName::Real(RealFileName::LocalPath(p)) =
-          tcx.body_filename(body)
-        {
+      let mut inner = |(_, unit): (Span, A::Unit)| {
+        if let FileName::Real(RealFileName::LocalPath(p)) = unit.filename(tcx) {
           if target_file.map_or(true, |f| f.ends_with(&p)) {
-            log::info!("analyzing {:?}", body);
-            match analysis.analyze(tcx, body) {
+            log::info!("analyzing {:?}", unit);
+            match options.apply(|| analysis.analyze(tcx, unit)) {
               Ok(v) => Some(v),
               Err(e) => {
-                log::error!("Error analyzing body {:?} {:?}", body, e);
+                log::error!("Error analyzing {:?} {:?}", unit, e);
                 None
               }
             }
@@ -329,21 +766,23 @@ impl<A: ArgusAnalysis, T: ToTarget, F: FnOnce() -> Option<T>>
         }
       };
 
-      self.result = match (self.compute_target.take().unwrap())() {
+      let outputs = match (self.compute_target.take().unwrap())() {
         Some(target) => {
           let target = target.to_target(tcx).expect("Couldn't compute target");
           let body_span = target.span;
           fluid_set!(analysis::OBLIGATION_TARGET, target);
 
-          find_enclosing_bodies(tcx, body_span)
+          A::Unit::find_enclosing(tcx, body_span)
+            .into_iter()
             .filter_map(|b| inner((body_span, b)))
             .collect::<Vec<_>>()
         }
-        None => find_bodies(tcx)
+        None => A::Unit::find_all(tcx)
           .into_iter()
           .filter_map(inner)
           .collect::<Vec<_>>(),
       };
+      self.result = Some(A::finish(outputs));
     });
 
     rustc_driver::Compilation::Stop
@@ -0,0 +1,337 @@
+//! Human-readable reports of each command's output, for `--format text`.
+//!
+//! Outputs are decoded from their JSON form into the `argus-schema`
+//! mirrors, so the reports only depend on the schema.
+use std::{collections::HashMap, fmt::Write as _, io::Write};
+
+use argus_schema::{
+  evaluation::Evaluation,
+  print::{PrintOptions, Printer},
+  proof_tree::{Node, SerializedTree},
+  summary::{ErrorGroup, ErrorLocation, Summary, TraitErrorSite},
+  ty::TyVal,
+  types::{
+    BodyBundle, CoherenceErrorKind, EvaluationResult, ItemBundle, Obligation,
+    ObligationHash, ObligationsInBody, SharedBundle, TraitError,
+  },
+  ArgusError, ArgusResult, CharRange, Versioned,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::plugin::ArgusCommand;
+
+/// Render the output of `command` and write it to `out`.
+pub(crate) fn write<T: Serialize>(
+  command: &ArgusCommand,
+  value: &Versioned<T>,
+  options: PrintOptions,
+  mut out: impl Write,
+) -> anyhow::Result<()> {
+  let json = serde_json::to_vec(value)?;
+  let report = match command {
+    ArgusCommand::Bundle {
+      shared_tables: false,
+    } => render_result(&json, |bodies: Vec<BodyBundle>| {
+      bodies.iter().map(|b| bundle(b, None, options)).collect()
+    })?,
+    ArgusCommand::Bundle {
+      shared_tables: true,
+    } => render_result(&json, |shared: SharedBundle| {
+      let bundles = shared.bodies.iter();
+      bundles.map(|b| bundle(b, Some(&shared), options)).collect()
+    })?,
+    ArgusCommand::Items { .. } => {
+      render_result(&json, |items: Vec<ItemBundle>| {
+        items.iter().map(|i| item(i, options)).collect()
+      })?
+    }
+    ArgusCommand::Obligations { .. } => {
+      render_result(&json, |bodies: Vec<ObligationsInBody>| {
+        bodies.iter().map(|b| obligations(b, options)).collect()
+      })?
+    }
+    ArgusCommand::Tree { .. } => {
+      render_result(&json, |trees: Vec<SerializedTree>| {
+        trees.iter().map(|t| tree(t, options)).collect()
+      })?
+    }
+    ArgusCommand::Evaluate => render_result(&json, |e| evaluation(&e))?,
+    ArgusCommand::CrateSummary => {
+      render_result(&json, |sites: Vec<TraitErrorSite>| {
+        sites.iter().map(error_site).collect()
+      })?
+    }
+    ArgusCommand::Summary => {
+      let summary: Versioned<Summary> = serde_json::from_slice(&json)?;
+      summary_report(&summary.output)
+    }
+    ArgusCommand::Preload | ArgusCommand::RustcVersion => {
+      anyhow::bail!("command has no output to render")
+    }
+  };
+  out.write_all(report.as_bytes())?;
+  out.flush()?;
+  Ok(())
+}
+
+fn render_result<T: DeserializeOwned>(
+  json: &[u8],
+  render: impl FnOnce(T) -> String,
+) -> anyhow::Result<String> {
+  let result: Versioned<ArgusResult<T>> = serde_json::from_slice(json)?;
+  Ok(match result.output {
+    Ok(output) => render(output),
+    Err(ArgusError::BuildError { .. }) => "error: build failed\n".into(),
+    Err(ArgusError::AnalysisError { error }) => format!("error: {error}\n"),
+  })
+}
+
+/// One-indexed `line:column`, as printed by rustc.
+fn position(range: &CharRange) -> String {
+  format!("{}:{}", range.start.line + 1, range.start.column + 1)
+}
+
+fn location(location: &ErrorLocation) -> String {
+  format!(
+    "{}:{}:{}",
+    location.filename,
+    location.line + 1,
+    location.column + 1
+  )
+}
+
+fn result_label(result: EvaluationResult) -> &'static str {
+  match result {
+    EvaluationResult::Yes => "yes",
+    EvaluationResult::MaybeOverflow => "overflow",
+    EvaluationResult::MaybeAmbiguity => "ambiguous",
+    EvaluationResult::No => "no",
+  }
+}
+
+/// Printer of values with types in `tys`, or in the tables of `shared`.
+fn printer<'a>(
+  shared: Option<&'a SharedBundle>,
+  tys: &'a [TyVal],
+  options: PrintOptions,
+) -> Printer<'a> {
+  match shared {
+    Some(shared) => Printer::shared(shared, options),
+    None => Printer::new(tys, options),
+  }
+}
+
+/// Each trait error with its failing predicates and their root causes, in
+/// the order Argus shows them.
+#[allow(clippy::too_many_arguments)]
+fn trait_errors(
+  out: &mut String,
+  filename: &str,
+  errors: &[TraitError],
+  obligations: &[Obligation],
+  tys: &[TyVal],
+  trees: &HashMap<ObligationHash, SerializedTree>,
+  shared: Option<&SharedBundle>,
+  options: PrintOptions,
+) {
+  let printer = printer(shared, tys, options);
+  for error in errors {
+    let _ = writeln!(out, "{filename}:{}: error", position(&error.range));
+    for hash in &error.hashes {
+      let Some(obligation) = obligations.iter().find(|o| o.hash == *hash)
+      else {
+        continue;
+      };
+      let predicate = printer.predicate(&obligation.obligation.predicate);
+      let _ = writeln!(out, "  `{predicate}` is not satisfied");
+      if let Some(tree) = trees.get(hash) {
+        root_causes(out, tree, shared, options);
+      }
+    }
+  }
+}
+
+fn root_causes(
+  out: &mut String,
+  tree: &SerializedTree,
+  shared: Option<&SharedBundle>,
+  options: PrintOptions,
+) {
+  let printer = printer(shared, &tree.tys, options);
+  for (rank, idx) in tree.analysis.ranked_goals().into_iter().enumerate() {
+    let Some(Node::Goal(goal)) = tree.nodes.get(idx) else {
+      continue;
+    };
+    let predicate = printer.predicate(&tree.goals[*goal].value.predicate);
+    let _ = writeln!(out, "    {}. `{predicate}`", rank + 1);
+  }
+}
+
+fn bundle(
+  bundle: &BodyBundle,
+  shared: Option<&SharedBundle>,
+  options: PrintOptions,
+) -> String {
+  let mut out = String::new();
+  let body = &bundle.body;
+  for ambiguity in &body.ambiguity_errors {
+    let _ = writeln!(
+      out,
+      "{}:{}: error: type annotations needed",
+      bundle.filename,
+      position(&ambiguity.range)
+    );
+  }
+  trait_errors(
+    &mut out,
+    &bundle.filename,
+    &body.trait_errors,
+    &body.obligations,
+    &body.tys,
+    &bundle.trees,
+    shared,
+    options,
+  );
+  out
+}
+
+fn item(bundle: &ItemBundle, options: PrintOptions) -> String {
+  let mut out = String::new();
+  let item = &bundle.item;
+  let printer = Printer::new(&item.tys, options);
+  for error in &item.coherence_errors {
+    let kind = match error.kind {
+      CoherenceErrorKind::Orphan => "orphan impl",
+      CoherenceErrorKind::Overlap => "overlapping impls",
+    };
+    let _ = writeln!(
+      out,
+      "{}:{}: error: {kind}",
+      bundle.filename,
+      position(&error.range)
+    );
+    for header in &error.conflicting_impls {
+      let _ =
+        writeln!(out, "  conflicts with `{}`", printer.impl_header(header));
+    }
+  }
+  trait_errors(
+    &mut out,
+    &bundle.filename,
+    &item.trait_errors,
+    &item.obligations,
+    &item.tys,
+    &bundle.trees,
+    None,
+    options,
+  );
+  out
+}
+
+/// Necessary obligations of the body, those shown in the IDE.
+fn obligations(body: &ObligationsInBody, options: PrintOptions) -> String {
+  let mut out = String::new();
+  let printer = Printer::new(&body.tys, options);
+  let name = body.name.as_ref().map(|name| printer.path(name));
+  let _ = writeln!(
+    out,
+    "{} at {}",
+    name.as_deref().unwrap_or("body"),
+    position(&body.range)
+  );
+  for obligation in &body.obligations {
+    if !obligation.is_necessary {
+      continue;
+    }
+    let _ = writeln!(
+      out,
+      "  {}: {}: `{}`",
+      position(&obligation.range),
+      result_label(obligation.result),
+      printer.predicate(&obligation.obligation.predicate)
+    );
+  }
+  out
+}
+
+fn tree(tree: &SerializedTree, options: PrintOptions) -> String {
+  let mut out = String::new();
+  let printer = Printer::new(&tree.tys, options);
+  if let Some(Node::Goal(goal)) = tree.nodes.get(tree.root) {
+    let goal = &tree.goals[*goal];
+    let _ = writeln!(
+      out,
+      "`{}`: {}",
+      printer.predicate(&goal.value.predicate),
+      result_label(tree.results[goal.result])
+    );
+  }
+  root_causes(&mut out, tree, None, options);
+  out
+}
+
+fn evaluation(evaluation: &Evaluation) -> String {
+  let mut out = String::new();
+  for rank in &evaluation.ranks {
+    let position = rank
+      .rank
+      .map_or_else(|| "not found".to_string(), |rank| format!("rank {rank}"));
+    let _ = writeln!(
+      out,
+      "{}:{}: {position}: {}",
+      rank.filename,
+      rank.expected.line + 1,
+      rank.expected.message
+    );
+  }
+  let summary = &evaluation.summary;
+  let _ = writeln!(
+    out,
+    "{} errors, {} found, top-1 {}, top-3 {}, top-5 {}, MRR {:.3}",
+    summary.errors,
+    summary.found,
+    summary.top1,
+    summary.top3,
+    summary.top5,
+    summary.mean_reciprocal_rank
+  );
+  out
+}
+
+fn error_site(site: &TraitErrorSite) -> String {
+  let predicate = match (&site.self_ty, &site.failing_trait) {
+    (Some(self_ty), Some(failing_trait)) => {
+      format!(": `{self_ty}: {failing_trait}`")
+    }
+    _ => String::new(),
+  };
+  format!("{}: error{predicate}\n", location(&site.location))
+}
+
+fn summary_report(summary: &Summary) -> String {
+  let mut out = String::new();
+  let _ = writeln!(out, "{} trait errors", summary.errors);
+  let sections = [
+    ("by trait", &summary.by_trait),
+    ("by self type", &summary.by_self_ty),
+    ("by root cause", &summary.by_root_cause),
+  ];
+  for (title, groups) in sections {
+    if groups.is_empty() {
+      continue;
+    }
+    let _ = writeln!(out, "\n{title}:");
+    for ErrorGroup {
+      key,
+      count,
+      locations,
+    } in groups
+    {
+      let _ = writeln!(out, "  {count} `{key}`");
+      for loc in locations {
+        let _ = writeln!(out, "    {}", location(loc));
+      }
+    }
+  }
+  out
+}
@@ -1,9 +1,23 @@
-use rustc_data_structures::stable_hasher::Hash64;
-use rustc_infer::{infer::InferCtxt, traits::PredicateObligation};
-use rustc_middle::ty::{self, Predicate, TypeFoldable};
+use rustc_data_structures::stable_hasher::{Hash64, HashStable, StableHasher};
+use rustc_hir::def_id::DefId;
+use rustc_infer::{
+  infer::{BoundRegionConversionTime, InferCtxt, TyCtxtInferExt},
+  traits::{Obligation, ObligationCause, PredicateObligation},
+};
+use rustc_middle::ty::{
+  self, fold::BottomUpFolder, Predicate, TyCtxt, TypeFoldable, TypeVisitableExt,
+};
+use rustc_span::DUMMY_SP;
 use rustc_trait_selection::{
   solve::{GenerateProofTree, InferCtxtEvalExt},
-  traits::query::NoSolution,
+  traits::{
+    error_reporting::on_unimplemented::{
+      OnUnimplementedNote, TypeErrCtxtExt as _,
+    },
+    orphan_check_trait_ref,
+    query::NoSolution,
+    InCrate, ObligationCtxt, OrphanCheckMode,
+  },
 };
 
 use crate::{ty::TyCtxtExt, EvaluationResult};
@@ -18,10 +32,49 @@ pub trait InferCtxtExt<'tcx> {
 
   fn predicate_hash(&self, p: &Predicate<'tcx>) -> Hash64;
 
+  /// Hash identifying an obligation. Unlike `predicate_hash` this includes
+  /// the cause span and param env, so equal predicates required at
+  /// different locations, or under different where clauses, are distinct.
+  fn obligation_hash(&self, obligation: &PredicateObligation<'tcx>) -> Hash64;
+
   fn evaluate_obligation(
     &self,
     obligation: &PredicateObligation<'tcx>,
   ) -> EvaluationResult;
+
+  /// Would the local crate be allowed to write `impl Trait for Ty`?
+  ///
+  /// Returns `None` when the answer depends on inference variables or
+  /// when the self type is a bare parameter, in which case a where
+  /// clause (not an impl) is the usual fix.
+  fn orphan_check_local(
+    &self,
+    trait_ref: ty::PolyTraitRef<'tcx>,
+  ) -> Option<bool>;
+
+  /// Impls of the trait whose headers unify with `trait_ref`, regardless
+  /// of their where clauses.
+  fn unifying_impls(
+    &self,
+    param_env: ty::ParamEnv<'tcx>,
+    trait_ref: ty::PolyTraitRef<'tcx>,
+  ) -> Vec<DefId>;
+
+  /// Impls of the trait that coherence would consider overlapping with a
+  /// user impl for `trait_ref`.
+  ///
+  /// Like coherence, an impl is only excluded when its header doesn't unify
+  /// with `trait_ref` or one of its where clauses can never hold, including
+  /// by impls that upstream or downstream crates may add.
+  fn overlapping_impls(&self, trait_ref: ty::PolyTraitRef<'tcx>) -> Vec<DefId>;
+
+  /// Evaluate the `#[diagnostic::on_unimplemented]` attribute of the
+  /// predicate's trait, formatted with the predicate's generic args.
+  fn on_unimplemented_note(
+    &self,
+    param_env: ty::ParamEnv<'tcx>,
+    trait_pred: ty::PolyTraitPredicate<'tcx>,
+  ) -> OnUnimplementedNote;
 }
 
 impl<'tcx> InferCtxtExt<'tcx> for InferCtxt<'tcx> {
@@ -52,6 +105,19 @@ impl<'tcx> InferCtxtExt<'tcx> for InferCtxt<'tcx> {
     self.tcx.predicate_hash(&p)
   }
 
+  fn obligation_hash(&self, obligation: &PredicateObligation<'tcx>) -> Hash64 {
+    let predicate_hash = self.predicate_hash(&obligation.predicate);
+    let caller_bounds =
+      self.tcx.erase_regions(obligation.param_env.caller_bounds());
+    self.tcx.with_stable_hashing_context(|mut hcx| {
+      let mut h = StableHasher::new();
+      predicate_hash.hash_stable(&mut hcx, &mut h);
+      obligation.cause.span.hash_stable(&mut hcx, &mut h);
+      caller_bounds.hash_stable(&mut hcx, &mut h);
+      h.finish()
+    })
+  }
+
   fn evaluate_obligation(
     &self,
     obligation: &PredicateObligation<'tcx>,
@@ -64,4 +130,175 @@ impl<'tcx> InferCtxtExt<'tcx> for InferCtxt<'tcx> {
       _ => Err(NoSolution),
     }
   }
+
+  fn orphan_check_local(
+    &self,
+    trait_ref: ty::PolyTraitRef<'tcx>,
+  ) -> Option<bool> {
+    let trait_ref = self.resolve_vars_if_possible(trait_ref);
+    let self_ty = trait_ref.skip_binder().self_ty();
+    if trait_ref.has_infer()
+      || self_ty.is_ty_var()
+      || matches!(self_ty.kind(), ty::Param(..))
+    {
+      return None;
+    }
+
+    // Orphan rules only constrain impls of foreign traits.
+    if trait_ref.def_id().is_local() {
+      return Some(true);
+    }
+
+    self.probe(|_| {
+      let tcx = self.tcx;
+      let trait_ref = self.instantiate_binder_with_fresh_vars(
+        DUMMY_SP,
+        BoundRegionConversionTime::HigherRankedType,
+        trait_ref,
+      );
+      // The orphan checker expects a fully instantiated impl header,
+      // replace the remaining parameters with fresh variables, these
+      // are treated as uncovered types.
+      let trait_ref = fresh_params(self, tcx.erase_regions(trait_ref));
+
+      orphan_check_trait_ref(
+        self,
+        trait_ref,
+        InCrate::Local {
+          mode: OrphanCheckMode::Proper,
+        },
+        Ok::<_, std::convert::Infallible>,
+      )
+      .ok()
+      .map(|res| res.is_ok())
+    })
+  }
+
+  fn unifying_impls(
+    &self,
+    param_env: ty::ParamEnv<'tcx>,
+    trait_ref: ty::PolyTraitRef<'tcx>,
+  ) -> Vec<DefId> {
+    let tcx = self.tcx;
+    tcx
+      .all_impls(trait_ref.def_id())
+      .filter(|&impl_def_id| {
+        tcx.impl_polarity(impl_def_id) == ty::ImplPolarity::Positive
+      })
+      .filter(|&impl_def_id| {
+        self.probe(|_| {
+          let goal_ref = self.instantiate_binder_with_fresh_vars(
+            DUMMY_SP,
+            BoundRegionConversionTime::HigherRankedType,
+            trait_ref,
+          );
+          let args = self.fresh_args_for_item(DUMMY_SP, impl_def_id);
+          let impl_ref = tcx
+            .impl_trait_ref(impl_def_id)
+            .expect("impl of a trait")
+            .instantiate(tcx, args);
+          self.can_eq(param_env, goal_ref, impl_ref)
+        })
+      })
+      .collect()
+  }
+
+  fn overlapping_impls(&self, trait_ref: ty::PolyTraitRef<'tcx>) -> Vec<DefId> {
+    let tcx = self.tcx;
+    let trait_ref = tcx.erase_regions(self.resolve_vars_if_possible(trait_ref));
+    if trait_ref.has_infer() {
+      return vec![];
+    }
+
+    // Mirrors `coherence::overlap`, the user impl and the existing one are
+    // unified in an intercrate context with an empty environment, the
+    // generic parameters of the user impl are replaced by fresh variables.
+    let infcx = tcx
+      .infer_ctxt()
+      .intercrate(true)
+      .with_next_trait_solver(true)
+      .build();
+    let param_env = ty::ParamEnv::empty();
+    let cause = ObligationCause::dummy();
+    let overlaps =
+      |impl_def_id| {
+        let ocx = ObligationCtxt::new(&infcx);
+        let goal_ref = infcx.instantiate_binder_with_fresh_vars(
+          DUMMY_SP,
+          BoundRegionConversionTime::HigherRankedType,
+          trait_ref,
+        );
+        let goal_ref = fresh_params(&infcx, goal_ref);
+        let args = infcx.fresh_args_for_item(DUMMY_SP, impl_def_id);
+        let impl_ref = tcx
+          .impl_trait_ref(impl_def_id)
+          .expect("impl of a trait")
+          .instantiate(tcx, args);
+        if ocx.eq(&cause, param_env, goal_ref, impl_ref).is_err() {
+          return false;
+        }
+
+        let predicates = tcx.predicates_of(impl_def_id).instantiate(tcx, args);
+        ocx.register_obligations(predicates.predicates.into_iter().map(
+          |clause| Obligation::new(tcx, cause.clone(), param_env, clause),
+        ));
+        // Ambiguities don't rule out the overlap, only true errors do.
+        ocx
+          .select_all_or_error()
+          .iter()
+          .all(|error| !error.is_true_error())
+      };
+
+    tcx
+      .all_impls(trait_ref.def_id())
+      .filter(|&impl_def_id| {
+        tcx.impl_polarity(impl_def_id) == ty::ImplPolarity::Positive
+      })
+      .filter(|&impl_def_id| infcx.probe(|_| overlaps(impl_def_id)))
+      .collect()
+  }
+
+  fn on_unimplemented_note(
+    &self,
+    param_env: ty::ParamEnv<'tcx>,
+    trait_pred: ty::PolyTraitPredicate<'tcx>,
+  ) -> OnUnimplementedNote {
+    let trait_pred = self.resolve_vars_if_possible(trait_pred);
+    let obligation = Obligation::new(
+      self.tcx,
+      ObligationCause::dummy(),
+      param_env,
+      trait_pred,
+    );
+    self.err_ctxt().on_unimplemented_note(
+      trait_pred.map_bound(|p| p.trait_ref),
+      &obligation,
+      &mut None,
+    )
+  }
+}
+
+/// Replace the generic parameters in `value` with fresh inference variables.
+fn fresh_params<'tcx, T: TypeFoldable<TyCtxt<'tcx>>>(
+  infcx: &InferCtxt<'tcx>,
+  value: T,
+) -> T {
+  value.fold_with(&mut BottomUpFolder {
+    tcx: infcx.tcx,
+    ty_op: |ty| {
+      if let ty::Param(..) = ty.kind() {
+        infcx.next_ty_var(DUMMY_SP)
+      } else {
+        ty
+      }
+    },
+    lt_op: |lt| lt,
+    ct_op: |ct| {
+      if let ty::ConstKind::Param(_) = ct.kind() {
+        infcx.next_const_var(ct.ty(), DUMMY_SP)
+      } else {
+        ct
+      }
+    },
+  })
 }
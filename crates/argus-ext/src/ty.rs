@@ -49,6 +49,10 @@ pub trait TyCtxtExt<'tcx> {
     inspector: ObligationInspector<'tcx>,
   ) -> &TypeckResults;
 
+  /// Whether type checking the body failed, including the auto trait goals
+  /// on its coroutines that are only proven after typeck.
+  fn body_has_errors(self, typeck_results: &TypeckResults<'tcx>) -> bool;
+
   /// Test whether `a` is a parent node of `b`.
   fn is_parent_of(&self, a: HirId, b: HirId) -> bool;
 
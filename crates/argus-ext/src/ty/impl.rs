@@ -1,9 +1,15 @@
 use rustc_data_structures::stable_hasher::Hash64;
-use rustc_hir::{def_id::DefId, BodyId, HirId};
+use rustc_hir::{
+  def_id::{DefId, LocalDefId},
+  BodyId, HirId,
+};
 use rustc_hir_typeck::inspect_typeck;
 use rustc_infer::{
-  infer::InferCtxt,
-  traits::{solve::CandidateSource, ObligationInspector, PredicateObligation},
+  infer::{InferCtxt, TyCtxtInferExt},
+  traits::{
+    solve::CandidateSource, Obligation, ObligationInspector,
+    PredicateObligation,
+  },
 };
 use rustc_middle::ty::{
   self, Predicate, Ty, TyCtxt, TypeSuperVisitable, TypeVisitable, TypeVisitor,
@@ -15,6 +21,7 @@ use rustc_trait_selection::{
   traits::{
     query::NoSolution,
     solve::{Certainty, MaybeCause},
+    ObligationCtxt,
   },
 };
 use rustc_utils::source_map::range::CharRange;
@@ -23,6 +30,46 @@ use rustc_utils::source_map::range::CharRange;
 use super::*;
 use crate::{hash::StableHash, EvaluationResult};
 
+/// Auto trait goals on coroutines defined in the body are stalled during
+/// typeck, rustc proves them after borrowck in `check_coroutine_obligations`.
+/// This proves them the same way, with the `inspector` attached. Typeck isn't
+/// running as a query here, so the coroutine layouts these goals need don't
+/// cause a cycle.
+fn inspect_coroutine_obligations<'tcx>(
+  tcx: TyCtxt<'tcx>,
+  def_id: LocalDefId,
+  typeck_results: &TypeckResults<'tcx>,
+  inspector: ObligationInspector<'tcx>,
+) {
+  if typeck_results.coroutine_stalled_predicates.is_empty() {
+    return;
+  }
+
+  let param_env = tcx.param_env(def_id);
+  let infcx = tcx
+    .infer_ctxt()
+    .ignoring_regions()
+    .with_opaque_type_inference(def_id)
+    .with_next_trait_solver(true)
+    .build();
+  infcx.attach_obligation_inspector(inspector);
+
+  let ocx = ObligationCtxt::new(&infcx);
+  for (predicate, cause) in &typeck_results.coroutine_stalled_predicates {
+    ocx.register_obligation(Obligation::new(
+      tcx,
+      cause.clone(),
+      param_env,
+      *predicate,
+    ));
+  }
+  // Errors are reported through the inspector.
+  let _ = ocx.select_all_or_error();
+  // Hidden types aren't checked here, but opaque types left in the context
+  // are a delayed bug when it's dropped.
+  let _ = infcx.take_opaque_types();
+}
+
 impl EvaluationResultExt for EvaluationResult {
   fn is_yes(&self) -> bool {
     matches!(self, EvaluationResult::Ok(Certainty::Yes))
@@ -135,7 +182,22 @@ impl<'tcx> TyCtxtExt<'tcx> for TyCtxt<'tcx> {
   ) -> &TypeckResults {
     let local_def_id = self.hir().body_owner_def_id(body_id);
     // Typeck current body, accumulating inspected information in TLS.
-    inspect_typeck(self, local_def_id, inspector)
+    let typeck_results = inspect_typeck(self, local_def_id, inspector);
+    inspect_coroutine_obligations(
+      self,
+      local_def_id,
+      typeck_results,
+      inspector,
+    );
+    typeck_results
+  }
+
+  fn body_has_errors(self, typeck_results: &TypeckResults<'tcx>) -> bool {
+    typeck_results.tainted_by_errors.is_some()
+      || (!typeck_results.coroutine_stalled_predicates.is_empty()
+        && self
+          .check_coroutine_obligations(typeck_results.hir_owner.def_id)
+          .is_err())
   }
 
   fn is_parent_of(&self, a: HirId, b: HirId) -> bool {
@@ -414,6 +476,9 @@ impl<'tcx> PredicateExt<'tcx> for Predicate<'tcx> {
       // Testing is an error implies another means that we can leak inference variables while
       // probing the ENA Tables. Bad. It's safe to catch this panic because we fork the
       // inference contexts and only a single thread has access at a time.
+      // Silence the panic, but put back whichever hook was installed (e.g.,
+      // rustc's ICE hook) rather than the default one.
+      let hook = panic::take_hook();
       panic::set_hook(Box::new(|_| {}));
       let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
         let refinee = infcx.freshen(*self);
@@ -421,7 +486,7 @@ impl<'tcx> PredicateExt<'tcx> for Predicate<'tcx> {
         infcx.error_implies(refiner, refinee)
       }))
       .unwrap_or(false);
-      let _ = panic::take_hook();
+      panic::set_hook(hook);
       res
     })
   }
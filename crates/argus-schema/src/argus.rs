@@ -0,0 +1,87 @@
+//! Argus-specific groupings of rustc data, see `argus_ser::argus`.
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  hir,
+  path::DefLocation,
+  ty::{
+    BoundVariableKind, Clause, GenericArg, Polarity,
+    TraitRefPrintOnlyTraitPath, Ty,
+  },
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ImplHeader {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub l: Option<DefLocation>,
+  pub args: Vec<GenericArg>,
+  pub name: TraitRefPrintOnlyTraitPath,
+  pub self_ty: Ty,
+  pub predicates: GroupedClauses,
+  /// Type parameters printed with a `?Sized` bound.
+  pub tys_without_default_bounds: Vec<Ty>,
+  /// The header as written, only for local impls.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub hir: Option<hir::Impl>,
+}
+
+/// Index of an impl header in the shared tables of a bundle.
+pub type ImplHeaderIdx = usize;
+
+/// An impl header, or its index in the shared tables of a bundle, see
+/// [`SharedBundle`](crate::types::SharedBundle).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(untagged)]
+pub enum ImplHeaderRef {
+  Header(Box<ImplHeader>),
+  Shared(ImplHeaderIdx),
+}
+
+/// A subterm of a goal and the subterm of an impl's trait reference at the
+/// same position, where they fail to unify.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct ArgDiff {
+  pub goal: Ty,
+  #[serde(rename = "impl")]
+  pub impl_ty: Ty,
+}
+
+/// Clauses grouped by their self type, the rest are left in `other`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct GroupedClauses {
+  pub grouped: Vec<PolyClauseWithBounds>,
+  pub other: Vec<Clause>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct PolyClauseWithBounds {
+  pub value: ClauseWithBounds,
+  pub bound_vars: Vec<BoundVariableKind>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct ClauseWithBounds {
+  pub ty: Ty,
+  pub bounds: Vec<ClauseBound>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum ClauseBound {
+  Trait(Polarity, TraitRefPrintOnlyTraitPath),
+  /// Sugared `Fn*` bound with its return type.
+  FnTrait(Polarity, TraitRefPrintOnlyTraitPath, Ty),
+  Region(crate::ty::Region),
+  /// A trait bound with its associated types, e.g., `Iterator<Item = u32>`.
+  AssocTrait(crate::ty::Trait),
+}
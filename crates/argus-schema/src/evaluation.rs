@@ -0,0 +1,48 @@
+//! Root cause rankings, see `argus_lib::evaluation`.
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Expected root cause parsed from a `//~ ROOT_CAUSE:` annotation.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ExpectedCause {
+  /// Zero-indexed line of the annotated error.
+  pub line: usize,
+  pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorRank {
+  pub filename: String,
+  pub expected: ExpectedCause,
+  /// Position of the expected cause, `None` if Argus didn't list it.
+  pub rank: Option<usize>,
+  pub num_goals: usize,
+  pub num_tree_nodes: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluationSummary {
+  pub errors: usize,
+  pub found: usize,
+  pub top1: usize,
+  pub top3: usize,
+  pub top5: usize,
+  pub mean_rank: Option<f64>,
+  pub mean_reciprocal_rank: f64,
+}
+
+/// The output of `cargo argus evaluate`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Evaluation {
+  pub ranks: Vec<ErrorRank>,
+  pub summary: EvaluationSummary,
+}
@@ -0,0 +1,321 @@
+//! Types and bounds as written by the user, see `argus_ser::hir`.
+//!
+//! Unlike the [`ty`](crate::ty) mirrors these aren't interned, and field
+//! names are those of `rustc_hir`. Spans, ids and resolutions are dropped.
+#[cfg(feature = "json-schema")]
+use schemars::{
+  schema::{ArrayValidation, InstanceType, Schema, SchemaObject},
+  JsonSchema,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::ty::Symbol;
+
+/// Payload of a variant whose fields were all dropped, serialized as `[]`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dropped();
+
+// The derived schema lists no `items`, which draft 7 doesn't allow.
+#[cfg(feature = "json-schema")]
+impl JsonSchema for Dropped {
+  fn schema_name() -> String {
+    "Dropped".into()
+  }
+
+  fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> Schema {
+    SchemaObject {
+      instance_type: Some(InstanceType::Array.into()),
+      array: Some(Box::new(ArrayValidation {
+        max_items: Some(0),
+        ..Default::default()
+      })),
+      ..Default::default()
+    }
+    .into()
+  }
+}
+
+/// A node obligations can point to, see `Obligation::hir`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum Node {
+  Ty(Ty),
+  TraitRef(TraitRef),
+  TypeBinding(TypeBinding),
+  WhereBoundPredicate(WhereBoundPredicate),
+  GenericParam(GenericParam),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct Impl {
+  pub polarity: ImplPolarity,
+  pub generics: Generics,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub of_trait: Option<TraitRef>,
+  pub self_ty: Ty,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum ImplPolarity {
+  Positive,
+  Negative,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct Generics {
+  pub params: Vec<GenericParam>,
+  pub predicates: Vec<WherePredicate>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct TraitRef {
+  pub path: Path,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct PolyTraitRef {
+  pub bound_generic_params: Vec<GenericParam>,
+  pub trait_ref: TraitRef,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct Ty {
+  pub kind: TyKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum TyKind {
+  InferDelegation(Dropped),
+  Slice(Box<Ty>),
+  /// The length is dropped, leaving a one element tuple.
+  Array((Box<Ty>,)),
+  Ptr(Box<MutTy>),
+  Ref(Lifetime, Box<MutTy>),
+  BareFn,
+  Never,
+  Tup(Vec<Ty>),
+  AnonAdt,
+  Path(QPath),
+  OpaqueDef(Dropped),
+  TraitObject(Vec<PolyTraitRef>, Lifetime),
+  Typeof,
+  Infer,
+  Err,
+  /// The pattern is dropped, leaving a one element tuple.
+  Pat((Box<Ty>,)),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct MutTy {
+  pub ty: Ty,
+  pub mutbl: Mutability,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum Mutability {
+  Mut,
+  Not,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum QPath {
+  /// `Ty` or `<Ty as Trait>::Name`, the qualified self type is optional.
+  Resolved(Option<Box<Ty>>, Path),
+  /// `<Ty>::Name`
+  TypeRelative(Box<Ty>, Box<PathSegment>),
+  LangItem(Dropped),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct GenericParam {
+  pub name: ParamName,
+  pub kind: GenericParamKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum GenericParamKind {
+  Lifetime {},
+  Type {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default: Option<Ty>,
+  },
+  Const {
+    ty: Ty,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default: Option<AnonConst>,
+  },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum ParamName {
+  Plain(Ident),
+  Fresh,
+  Error,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum WherePredicate {
+  BoundPredicate(WhereBoundPredicate),
+  RegionPredicate(WhereRegionPredicate),
+  EqPredicate(WhereEqPredicate),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct WhereBoundPredicate {
+  pub bound_generic_params: Vec<GenericParam>,
+  pub bounded_ty: Ty,
+  pub bounds: Vec<GenericBound>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct WhereRegionPredicate {
+  pub lifetime: Lifetime,
+  pub bounds: Vec<GenericBound>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct WhereEqPredicate {
+  pub lhs_ty: Ty,
+  pub rhs_ty: Ty,
+}
+
+/// An elided lifetime has an empty name.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct Lifetime {
+  pub ident: Ident,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct Ident {
+  pub name: Symbol,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct Path {
+  pub segments: Vec<PathSegment>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct PathSegment {
+  pub ident: Ident,
+  pub res: Res,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub args: Option<GenericArgs>,
+}
+
+/// The kind of resolution, the resolved definition itself is dropped.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum Res {
+  Def(Dropped),
+  PrimTy,
+  SelfTyParam {},
+  SelfTyAlias {},
+  SelfCtor,
+  Local,
+  ToolMod,
+  NonMacroAttr,
+  Err,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct GenericArgs {
+  pub args: Vec<GenericArg>,
+  pub bindings: Vec<TypeBinding>,
+  pub parenthesized: GenericArgsParentheses,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum GenericArg {
+  Lifetime(Lifetime),
+  Type(Ty),
+  Const(ConstArg),
+  Infer(InferArg),
+}
+
+/// The expression is dropped.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct AnonConst {}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct ConstArg {
+  pub value: AnonConst,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct InferArg {}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum GenericArgsParentheses {
+  No,
+  ReturnTypeNotation,
+  /// `Fn(A, B) -> C`
+  ParenSugar,
+}
+
+/// An associated item constraint, e.g., `Item = u32` or `Item: Debug`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct TypeBinding {
+  pub ident: Ident,
+  pub gen_args: GenericArgs,
+  pub kind: TypeBindingKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum TypeBindingKind {
+  Constraint { bounds: Vec<GenericBound> },
+  Equality { term: Term },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum GenericBound {
+  Trait(PolyTraitRef, TraitBoundModifier),
+  Outlives(Lifetime),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum TraitBoundModifier {
+  None,
+  Negative,
+  Maybe,
+  MaybeConst,
+  Const,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum Term {
+  Ty(Ty),
+  Const(AnonConst),
+}
@@ -0,0 +1,276 @@
+//! Typed mirrors of the data emitted by Argus.
+//!
+//! The serializers in `argus-ser` and `argus-lib` need `rustc_private`,
+//! this crate only needs serde. Each module mirrors its counterpart and
+//! deserializes what the CLI writes, e.g., the output of `cargo argus
+//! bundle` is a [`BundleOutput`]. Types inside a body, item or tree are
+//! interned, a [`Ty`](ty::Ty) is an index into the `tys` of its owner.
+pub mod argus;
+pub mod evaluation;
+pub mod hir;
+pub mod path;
+pub mod print;
+pub mod proof_tree;
+#[cfg(feature = "json-schema")]
+pub mod schema;
+pub mod summary;
+pub mod ty;
+pub mod types;
+
+use std::marker::PhantomData;
+
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Zero-indexed line and column, counted in characters.
+#[derive(
+  Serialize,
+  Deserialize,
+  Debug,
+  Clone,
+  Copy,
+  PartialEq,
+  Eq,
+  Hash,
+  PartialOrd,
+  Ord,
+)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct CharPos {
+  pub line: usize,
+  pub column: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct CharRange {
+  pub start: CharPos,
+  pub end: CharPos,
+  pub filename: FilenameIndex,
+}
+
+/// Index of a file in the session's source map.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct FilenameIndex {
+  pub private_use_as_methods_instead: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(tag = "type")]
+pub enum ArgusError {
+  BuildError { range: Option<CharRange> },
+  AnalysisError { error: String },
+}
+
+pub type ArgusResult<T> = Result<T, ArgusError>;
+
+/// Version of the output schema, bumped on every breaking change.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A top-level output, tagged with the [`SCHEMA_VERSION`] it was written
+/// with. The output's own fields are inlined, e.g., a result is written as
+/// `{"schemaVersion": 1, "Ok": ...}`.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Versioned<T> {
+  pub schema_version: u32,
+  #[serde(flatten)]
+  pub output: T,
+}
+
+impl<T> Versioned<T> {
+  pub fn new(output: T) -> Self {
+    Versioned {
+      schema_version: SCHEMA_VERSION,
+      output,
+    }
+  }
+}
+
+// Deserializing a flattened field buffers it, which loses the format's
+// handling of integer map keys, e.g., those of a tree's topology. Instead
+// the output reads every entry of the map but the version.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Versioned<T> {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: serde::Deserializer<'de>,
+  {
+    deserializer.deserialize_map(versioned::VersionedVisitor(PhantomData))
+  }
+}
+
+mod versioned {
+  use std::{fmt, marker::PhantomData};
+
+  use serde::de::{
+    self, value::MapAccessDeserializer, Deserialize, DeserializeSeed,
+    IgnoredAny, IntoDeserializer, MapAccess, Visitor,
+  };
+
+  use super::Versioned;
+
+  const VERSION_KEY: &str = "schemaVersion";
+
+  pub struct VersionedVisitor<T>(pub PhantomData<T>);
+
+  impl<'de, T: Deserialize<'de>> Visitor<'de> for VersionedVisitor<T> {
+    type Value = Versioned<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+      f.write_str("an output with a schema version")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+      self,
+      mut map: A,
+    ) -> Result<Self::Value, A::Error> {
+      let mut version = None;
+      let output =
+        T::deserialize(MapAccessDeserializer::new(WithoutVersion {
+          map: &mut map,
+          version: &mut version,
+        }))?;
+      // Enums stop at their variant, the version may come after it.
+      while let Some(key) = map.next_key::<String>()? {
+        if key == VERSION_KEY {
+          version = Some(map.next_value()?);
+        } else {
+          map.next_value::<IgnoredAny>()?;
+        }
+      }
+      let schema_version =
+        version.ok_or_else(|| de::Error::missing_field(VERSION_KEY))?;
+      Ok(Versioned {
+        schema_version,
+        output,
+      })
+    }
+  }
+
+  /// The entries of `map` without the version, which is stored on the side.
+  struct WithoutVersion<'a, A> {
+    map: A,
+    version: &'a mut Option<u32>,
+  }
+
+  impl<'de, A: MapAccess<'de>> MapAccess<'de> for WithoutVersion<'_, A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+      &mut self,
+      seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+      while let Some(key) = self.map.next_key::<String>()? {
+        if key == VERSION_KEY {
+          *self.version = Some(self.map.next_value()?);
+        } else {
+          return seed.deserialize(key.into_deserializer()).map(Some);
+        }
+      }
+      Ok(None)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+      &mut self,
+      seed: V,
+    ) -> Result<V::Value, A::Error> {
+      self.map.next_value_seed(seed)
+    }
+  }
+}
+
+/// Output of `cargo argus tree`.
+pub type TreeOutput = Versioned<ArgusResult<Vec<proof_tree::SerializedTree>>>;
+
+/// Output of `cargo argus obligations`.
+pub type ObligationsOutput =
+  Versioned<ArgusResult<Vec<types::ObligationsInBody>>>;
+
+/// Output of `cargo argus items`.
+pub type ItemsOutput = Versioned<ArgusResult<Vec<types::ItemBundle>>>;
+
+/// Output of `cargo argus bundle`.
+pub type BundleOutput = Versioned<ArgusResult<Vec<types::BodyBundle>>>;
+
+/// Output of `cargo argus bundle --shared-tables`.
+pub type SharedBundleOutput = Versioned<ArgusResult<types::SharedBundle>>;
+
+/// Output of `cargo argus evaluate`.
+pub type EvaluateOutput = Versioned<ArgusResult<evaluation::Evaluation>>;
+
+/// Output of `cargo argus crate-summary`, one per crate.
+pub type CrateSummaryOutput =
+  Versioned<ArgusResult<Vec<summary::TraitErrorSite>>>;
+
+/// Output of `cargo argus summary`.
+pub type SummaryOutput = Versioned<summary::Summary>;
+
+mod string {
+  use std::{fmt::Display, str::FromStr};
+
+  use serde::{de, Deserialize, Deserializer, Serializer};
+
+  pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    T: Display,
+    S: Serializer,
+  {
+    serializer.collect_str(value)
+  }
+
+  pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+  where
+    T: FromStr,
+    T::Err: Display,
+    D: Deserializer<'de>,
+  {
+    String::deserialize(deserializer)?
+      .parse()
+      .map_err(de::Error::custom)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use super::{ArgusError, ArgusResult, Versioned};
+
+  #[test]
+  fn versioned_inlines_the_output() {
+    let output = Versioned::new(ArgusResult::Ok(vec![1, 2]));
+    let json = serde_json::to_string(&output).unwrap();
+    assert_eq!(json, r#"{"schemaVersion":1,"Ok":[1,2]}"#);
+    assert_eq!(serde_json::from_str::<Versioned<_>>(&json).unwrap(), output);
+
+    let error =
+      r#"{"schemaVersion":1,"Err":{"type":"AnalysisError","error":"oops"}}"#;
+    let error = serde_json::from_str::<Versioned<ArgusResult<()>>>(error);
+    assert!(matches!(
+      error.unwrap().output,
+      Err(ArgusError::AnalysisError { .. })
+    ));
+  }
+
+  #[test]
+  fn versioned_keeps_integer_keys() {
+    let json = r#"{"Ok":{"0":[1],"2":[]},"schemaVersion":1}"#;
+    let output = serde_json::from_str::<
+      Versioned<ArgusResult<HashMap<usize, Vec<u32>>>>,
+    >(json)
+    .unwrap();
+    assert_eq!(output.output.unwrap()[&0], vec![1]);
+
+    let missing = r#"{"Ok":{}}"#;
+    assert!(
+      serde_json::from_str::<Versioned<ArgusResult<HashMap<usize, ()>>>>(
+        missing
+      )
+      .is_err()
+    );
+  }
+}
@@ -0,0 +1,93 @@
+//! Definition paths, see `argus_ser::path`.
+//!
+//! Paths are printed by rustc's pretty printer into segments, generic
+//! arguments and impl self types are kept structured.
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  ty::{GenericArg, Symbol, Ty},
+  CharRange,
+};
+
+pub type PathDefNoArgs = DefinedPath;
+
+pub type PathDefWithArgs = DefinedPath;
+
+pub type AliasPath = DefinedPath;
+
+pub type ValuePathWithArgs = DefinedPath;
+
+pub type BasicPathNoArgs = DefinedPath;
+
+/// Index of a path in the shared tables of a bundle.
+pub type PathIdx = usize;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(untagged)]
+pub enum DefinedPath {
+  Path {
+    path: Vec<PathSegment>,
+    /// Where the item is defined, absent if not in a local file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    l: Option<DefLocation>,
+  },
+  /// Paths of a shared bundle are written once, see
+  /// [`SharedBundle`](crate::types::SharedBundle).
+  Shared(PathIdx),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(tag = "type")]
+pub enum PathSegment {
+  /// `::`
+  Colons,
+  /// `crate`
+  LocalCrate,
+  /// `r#`
+  RawGuess,
+  DefPathDataName {
+    name: Symbol,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    disambiguator: Option<u32>,
+  },
+  Ty {
+    ty: Ty,
+  },
+  /// `< ... >`
+  GenericDelimiters {
+    inner: Vec<PathSegment>,
+  },
+  GenericArgumentList {
+    entries: Vec<GenericArg>,
+  },
+  Impl {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path: Option<DefinedPath>,
+    ty: Ty,
+    kind: ImplKind,
+  },
+  AnonImpl {
+    range: CharRange,
+  },
+}
+
+/// How an impl path is printed, `<Ty as Trait>` or `<impl Trait for Ty>`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(tag = "type")]
+pub enum ImplKind {
+  As,
+  For,
+}
+
+/// A location equivalent to that provided by VSCode's LSP.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct DefLocation {
+  pub r: CharRange,
+  pub f: String,
+}
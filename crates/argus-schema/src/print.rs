@@ -0,0 +1,1176 @@
+//! Render serialized types and predicates as Rust syntax.
+//!
+//! This is the text counterpart of the IDE's `print` package, used by
+//! outputs that aren't rendered in the browser. Values are printed as
+//! rustc would in a diagnostic, e.g., `Vec<u32>: Clone`.
+use std::fmt::Write;
+
+use crate::{
+  argus::{
+    ClauseBound, ClauseWithBounds, GroupedClauses, ImplHeader, ImplHeaderRef,
+  },
+  path::{DefinedPath, ImplKind, PathSegment},
+  ty::*,
+  types::SharedBundle,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrintOptions {
+  /// Print paths from their last named segment, e.g., `Vec` rather than
+  /// `alloc::vec::Vec`.
+  pub trim_paths: bool,
+  /// Print `Fn*` traits as `Fn(A, B)` rather than `Fn<(A, B)>`.
+  pub sugar_fn_traits: bool,
+  /// Generic argument lists longer than this are cut short, e.g.,
+  /// `HashMap<K, ..>`.
+  pub max_generic_args: Option<usize>,
+}
+
+impl Default for PrintOptions {
+  fn default() -> Self {
+    PrintOptions {
+      trim_paths: true,
+      sugar_fn_traits: true,
+      max_generic_args: None,
+    }
+  }
+}
+
+const FN_TRAITS: &[&str] = &[
+  "Fn",
+  "FnMut",
+  "FnOnce",
+  "AsyncFn",
+  "AsyncFnMut",
+  "AsyncFnOnce",
+];
+
+/// Prints values whose types index into `tys`, the table of the body, item
+/// or tree they were serialized with.
+pub struct Printer<'a> {
+  tys: &'a [TyVal],
+  paths: &'a [DefinedPath],
+  impl_headers: &'a [ImplHeader],
+  placeholders: &'a [Ty],
+  options: PrintOptions,
+}
+
+impl<'a> Printer<'a> {
+  pub fn new(tys: &'a [TyVal], options: PrintOptions) -> Self {
+    Printer {
+      tys,
+      paths: &[],
+      impl_headers: &[],
+      placeholders: &[],
+      options,
+    }
+  }
+
+  /// Prints values of any body in `bundle`, from its shared tables.
+  pub fn shared(bundle: &'a SharedBundle, options: PrintOptions) -> Self {
+    Printer {
+      tys: &bundle.tys,
+      paths: &bundle.paths,
+      impl_headers: &bundle.impl_headers,
+      placeholders: &[],
+      options,
+    }
+  }
+
+  /// The header `header` refers to, absent if it's not in the tables.
+  pub fn resolve_impl_header<'b>(
+    &self,
+    header: &'b ImplHeaderRef,
+  ) -> Option<&'b ImplHeader>
+  where
+    'a: 'b,
+  {
+    match header {
+      ImplHeaderRef::Header(header) => Some(header),
+      ImplHeaderRef::Shared(idx) => self.impl_headers.get(*idx),
+    }
+  }
+
+  pub fn ty(&self, ty: Ty) -> String {
+    self.render(|p, out| p.write_ty(out, ty))
+  }
+
+  /// The abbreviated form of `ty`, its subterms in `placeholders` are
+  /// printed as `$n`, see [`TyAbbreviations`].
+  pub fn abbreviated_ty(&self, ty: Ty, placeholders: &[Ty]) -> String {
+    let printer = Printer {
+      placeholders,
+      ..*self
+    };
+    printer.ty(ty)
+  }
+
+  pub fn path(&self, path: &DefinedPath) -> String {
+    self.render(|p, out| p.write_path(out, path))
+  }
+
+  pub fn predicate(&self, predicate: &Predicate) -> String {
+    self.render(|p, out| p.write_predicate(out, predicate))
+  }
+
+  pub fn clause(&self, clause: &Clause) -> String {
+    self.render(|p, out| p.write_clause(out, clause))
+  }
+
+  /// The header on a single line, e.g., `impl<T> Clone for Vec<T> where T:
+  /// Clone`.
+  pub fn impl_header(&self, header: &ImplHeader) -> String {
+    self.render(|p, out| p.write_impl_header(out, header))
+  }
+
+  /// Comma separated clauses, empty if there are none.
+  pub fn clauses(&self, clauses: &GroupedClauses) -> String {
+    self.render(|p, out| p.write_clauses(out, clauses, &[]))
+  }
+
+  fn render(&self, f: impl FnOnce(&Self, &mut String)) -> String {
+    let mut out = String::new();
+    f(self, &mut out);
+    out
+  }
+
+  fn ty_val(&self, ty: Ty) -> Option<&'a TyVal> {
+    self.tys.get(ty)
+  }
+
+  fn is_unit(&self, ty: Ty) -> bool {
+    matches!(self.ty_val(ty), Some(TyKind::Tuple(tys)) if tys.is_empty())
+  }
+
+  fn comma_separated<T>(
+    &self,
+    out: &mut String,
+    items: impl IntoIterator<Item = T>,
+    mut f: impl FnMut(&Self, &mut String, T),
+  ) {
+    for (i, item) in items.into_iter().enumerate() {
+      if i > 0 {
+        out.push_str(", ");
+      }
+      f(self, out, item);
+    }
+  }
+
+  // --------------------------------------------------
+  // Types
+
+  fn write_ty(&self, out: &mut String, ty: Ty) {
+    if let Some(i) = self.placeholders.iter().position(|&p| p == ty) {
+      out.push_str(&format!("${}", i + 1));
+      return;
+    }
+    match self.ty_val(ty) {
+      Some(kind) => self.write_ty_kind(out, kind),
+      None => out.push_str("{unknown}"),
+    }
+  }
+
+  fn write_ty_kind(&self, out: &mut String, kind: &TyKind) {
+    match kind {
+      TyKind::Bool => out.push_str("bool"),
+      TyKind::Char => out.push_str("char"),
+      TyKind::Str => out.push_str("str"),
+      TyKind::Never => out.push('!'),
+      TyKind::Error => out.push_str("{type error}"),
+      TyKind::Int(i) => out.push_str(&format!("{i:?}").to_lowercase()),
+      TyKind::Uint(u) => out.push_str(&format!("{u:?}").to_lowercase()),
+      TyKind::Float(f) => out.push_str(&format!("{f:?}").to_lowercase()),
+      TyKind::Pat((ty,)) => self.write_ty(out, *ty),
+      TyKind::Adt(path)
+      | TyKind::Foreign(path)
+      | TyKind::Closure(path)
+      | TyKind::CoroutineWitness(path) => self.write_path(out, path),
+      TyKind::Array(ty, len) => {
+        out.push('[');
+        self.write_ty(out, *ty);
+        out.push_str("; ");
+        self.write_const(out, len);
+        out.push(']');
+      }
+      TyKind::Slice(ty) => {
+        out.push('[');
+        self.write_ty(out, *ty);
+        out.push(']');
+      }
+      TyKind::RawPtr(TypeAndMut { ty, mutbl }) => {
+        out.push_str(match mutbl {
+          Mutability::Not => "*const ",
+          Mutability::Mut => "*mut ",
+        });
+        self.write_ty(out, *ty);
+      }
+      TyKind::Ref(region, ty, mutbl) => {
+        out.push('&');
+        if let Region::Named { data } = region {
+          out.push_str(data);
+          out.push(' ');
+        }
+        if let Mutability::Mut = mutbl {
+          out.push_str("mut ");
+        }
+        self.write_ty(out, *ty);
+      }
+      TyKind::FnDef(FnDef { sig, path }) => {
+        self.write_poly_fn_sig(out, sig);
+        out.push_str(" {");
+        self.write_path(out, path);
+        out.push('}');
+      }
+      TyKind::FnPtr(sig) => self.write_poly_fn_sig(out, sig),
+      TyKind::Tuple(tys) => self.write_tuple(out, tys),
+      TyKind::Placeholder(PlaceholderBoundTy::Named { data })
+      | TyKind::Bound(BoundTy::Named { data }) => out.push_str(data),
+      TyKind::Placeholder(PlaceholderBoundTy::Anon) => out.push_str("{anon}"),
+      TyKind::Bound(BoundTy::Bound {
+        data: BoundVariable::Error(e),
+      }) => {
+        let _ = write!(out, "{{{e}}}");
+      }
+      TyKind::Infer(infer) => self.write_infer_ty(out, infer),
+      TyKind::Param(ParamTy { name }) => out.push_str(name),
+      TyKind::Alias(alias) => match alias {
+        AliasTyKind::OpaqueImpl { data } => self.write_opaque_impl(out, data),
+        AliasTyKind::AliasTy {
+          data: AliasTy::Inherent { data } | AliasTy::PathDef { data },
+        }
+        | AliasTyKind::DefPath { data } => self.write_path(out, data),
+      },
+      TyKind::Dynamic(dynamic) => self.write_dynamic(out, dynamic),
+      TyKind::Coroutine(coroutine) => {
+        if coroutine.should_print_movability
+          && matches!(coroutine.movability, Movability::Static)
+        {
+          out.push_str("static ");
+        }
+        self.write_path(out, &coroutine.path);
+      }
+      TyKind::CoroutineClosure(closure) => self.write_path(out, &closure.path),
+    }
+  }
+
+  fn write_tuple(&self, out: &mut String, tys: &[Ty]) {
+    out.push('(');
+    self.comma_separated(out, tys, |p, out, ty| p.write_ty(out, *ty));
+    if tys.len() == 1 {
+      out.push(',');
+    }
+    out.push(')');
+  }
+
+  fn write_infer_ty(&self, out: &mut String, infer: &InferTy) {
+    match infer {
+      InferTy::IntVar => out.push_str("{integer}"),
+      InferTy::FloatVar => out.push_str("{float}"),
+      InferTy::Unresolved => out.push('_'),
+      InferTy::Unnamed(path) => self.write_path(out, path),
+      InferTy::SourceInfo(s) => out.push_str(s),
+    }
+  }
+
+  fn write_dynamic(&self, out: &mut String, dynamic: &DynamicTyKind) {
+    out.push_str(match dynamic.kind {
+      DynKind::Dyn => "dyn ",
+      DynKind::DynStar => "dyn* ",
+    });
+    let preds = &dynamic.predicates;
+    let mut sep = Separator::new(" + ");
+    for path in preds.data.iter().chain(&preds.auto_traits) {
+      sep.push(out);
+      self.write_path(out, path);
+    }
+    if let Region::Named { .. } | Region::Static = dynamic.region {
+      out.push_str(" + ");
+      self.write_region(out, &dynamic.region);
+    }
+  }
+
+  fn write_poly_fn_sig(&self, out: &mut String, sig: &PolyFnSig) {
+    self.write_binder(out, &sig.bound_vars);
+    let FnSig {
+      inputs_and_output,
+      c_variadic,
+      safety,
+      abi,
+    } = &sig.value;
+    if let Safety::Unsafe = safety {
+      out.push_str("unsafe ");
+    }
+    if let Some(abi) = abi_name(abi) {
+      let _ = write!(out, "extern {abi:?} ");
+    }
+    out.push_str("fn");
+    let (output, inputs) = match inputs_and_output.split_last() {
+      Some((output, inputs)) => (Some(*output), inputs),
+      None => (None, &[][..]),
+    };
+    self.write_fn_inputs_and_output(out, inputs, *c_variadic, output);
+  }
+
+  /// `(A, B) -> C`, the output is omitted if it's the unit type. C-variadic
+  /// inputs end with `...`.
+  fn write_fn_inputs_and_output(
+    &self,
+    out: &mut String,
+    inputs: &[Ty],
+    c_variadic: bool,
+    output: Option<Ty>,
+  ) {
+    out.push('(');
+    self.comma_separated(out, inputs, |p, out, ty| p.write_ty(out, *ty));
+    if c_variadic {
+      out.push_str(if inputs.is_empty() { "..." } else { ", ..." });
+    }
+    out.push(')');
+    if let Some(output) = output.filter(|&ty| !self.is_unit(ty)) {
+      out.push_str(" -> ");
+      self.write_ty(out, output);
+    }
+  }
+
+  fn write_opaque_impl(&self, out: &mut String, opaque: &OpaqueImpl) {
+    out.push_str("impl ");
+    let mut sep = Separator::new(" + ");
+
+    for fn_trait in &opaque.fn_traits {
+      sep.push(out);
+      let _ = write!(out, "{:?}", fn_trait.kind);
+      self.write_fn_inputs_and_output(
+        out,
+        &fn_trait.params,
+        false,
+        fn_trait.ret_ty,
+      );
+    }
+    for t in &opaque.traits {
+      sep.push(out);
+      self.write_trait(out, t);
+    }
+    for region in &opaque.lifetimes {
+      sep.push(out);
+      self.write_region(out, region);
+    }
+
+    let any_bounds = !sep.first;
+    let add_sized = opaque.has_sized_bound
+      && (!any_bounds || opaque.has_negative_sized_bound);
+    let add_maybe_sized =
+      !opaque.has_sized_bound && !opaque.has_negative_sized_bound;
+    if add_sized || add_maybe_sized {
+      sep.push(out);
+      if add_maybe_sized {
+        out.push('?');
+      }
+      out.push_str("Sized");
+    }
+  }
+
+  /// `Trait<A, Assoc = B>`
+  fn write_trait(&self, out: &mut String, t: &Trait) {
+    self.write_polarity(out, t.polarity);
+    self.write_path(out, &t.trait_name);
+    if t.own_args.is_empty() && t.assoc_args.is_empty() {
+      return;
+    }
+    out.push('<');
+    self.comma_separated(out, &t.own_args, |p, out, arg| {
+      p.write_generic_arg(out, arg);
+    });
+    if !t.own_args.is_empty() && !t.assoc_args.is_empty() {
+      out.push_str(", ");
+    }
+    self.comma_separated(out, &t.assoc_args, |p, out, assoc| {
+      out.push_str(&assoc.name);
+      out.push_str(" = ");
+      p.write_term(out, &assoc.term);
+    });
+    out.push('>');
+  }
+
+  fn write_region(&self, out: &mut String, region: &Region) {
+    match region {
+      Region::Static => out.push_str("'static"),
+      Region::Named { data } => out.push_str(data),
+      Region::Anonymous => out.push_str("'_"),
+    }
+  }
+
+  fn write_generic_arg(&self, out: &mut String, arg: &GenericArg) {
+    match arg {
+      GenericArgKind::Lifetime(region) => self.write_region(out, region),
+      GenericArgKind::Type(ty) => self.write_ty(out, *ty),
+      GenericArgKind::Const(c) => self.write_const(out, c),
+    }
+  }
+
+  fn write_term(&self, out: &mut String, term: &Term) {
+    match term {
+      Term::Ty(ty) => self.write_ty(out, *ty),
+      Term::Const(c) => self.write_const(out, c),
+    }
+  }
+
+  fn write_polarity(&self, out: &mut String, polarity: Polarity) {
+    match polarity {
+      Polarity::Positive => {}
+      Polarity::Negative => out.push('!'),
+      Polarity::Maybe => out.push('?'),
+    }
+  }
+
+  /// `for<'a, T> `, if any of the bound variables are named.
+  fn write_binder(&self, out: &mut String, bound_vars: &[BoundVariableKind]) {
+    let names = bound_vars
+      .iter()
+      .filter_map(|var| match var {
+        BoundVariableKind::Region(BoundRegionKind::BrNamed((name,)))
+          if name != "'_" =>
+        {
+          Some(name)
+        }
+        BoundVariableKind::Ty(BoundTyKind::Param((name,))) => Some(name),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    if !names.is_empty() {
+      out.push_str("for<");
+      self.comma_separated(out, names, |_, out, name| out.push_str(name));
+      out.push_str("> ");
+    }
+  }
+
+  // --------------------------------------------------
+  // Paths
+
+  fn write_path(&self, out: &mut String, path: &DefinedPath) {
+    let segments = match path {
+      DefinedPath::Path { path, .. } => &path[..],
+      DefinedPath::Shared(idx) => match self.paths.get(*idx) {
+        Some(DefinedPath::Path { path, .. }) => &path[..],
+        _ => return out.push_str("{unknown}"),
+      },
+    };
+    if !self.options.trim_paths {
+      return self.write_segments(out, segments);
+    }
+
+    // Keep `<T as Trait>::Assoc` whole so that it reads as a projection.
+    if segments.len() > 1
+      && matches!(segments[0], PathSegment::GenericDelimiters { .. })
+    {
+      return self.write_segments(out, segments);
+    }
+
+    // Trim to the last named segment and its generic arguments.
+    let start = segments
+      .iter()
+      .rposition(|segment| {
+        matches!(
+          segment,
+          PathSegment::Ty { .. }
+            | PathSegment::DefPathDataName { .. }
+            | PathSegment::Impl { .. }
+        )
+      })
+      .unwrap_or(0);
+    self.write_segments(out, &segments[start ..]);
+  }
+
+  fn write_segments(&self, out: &mut String, segments: &[PathSegment]) {
+    for (i, segment) in segments.iter().enumerate() {
+      let fn_inputs = match segment {
+        PathSegment::GenericArgumentList { entries } => {
+          self.fn_trait_inputs(&segments[.. i], entries)
+        }
+        _ => None,
+      };
+      if let Some(inputs) = fn_inputs {
+        out.push('(');
+        self.comma_separated(out, inputs, |p, out, ty| p.write_ty(out, *ty));
+        out.push(')');
+        continue;
+      }
+      self.write_segment(out, segment);
+    }
+  }
+
+  /// Inputs of an `Fn*` trait whose generic arguments follow `prefix`, if
+  /// these should be sugared.
+  fn fn_trait_inputs(
+    &self,
+    prefix: &[PathSegment],
+    entries: &[GenericArg],
+  ) -> Option<&'a [Ty]> {
+    if !self.options.sugar_fn_traits {
+      return None;
+    }
+    let Some(PathSegment::DefPathDataName { name, .. }) = prefix.last() else {
+      return None;
+    };
+    let [GenericArgKind::Type(inputs)] = entries else {
+      return None;
+    };
+    // Abbreviated inputs are printed as a placeholder, `Fn<$1>`.
+    if self.placeholders.contains(inputs) {
+      return None;
+    }
+    match self.ty_val(*inputs) {
+      Some(TyKind::Tuple(inputs)) if FN_TRAITS.contains(&name.as_str()) => {
+        Some(inputs)
+      }
+      _ => None,
+    }
+  }
+
+  fn write_segment(&self, out: &mut String, segment: &PathSegment) {
+    match segment {
+      PathSegment::Colons => out.push_str("::"),
+      PathSegment::LocalCrate => out.push_str("crate"),
+      PathSegment::RawGuess => out.push_str("r#"),
+      PathSegment::DefPathDataName {
+        name,
+        disambiguator,
+      } => {
+        out.push_str(name);
+        if let Some(d) = disambiguator.filter(|&d| d != 0) {
+          let _ = write!(out, "#{d}");
+        }
+      }
+      PathSegment::Ty { ty } => self.write_ty(out, *ty),
+      PathSegment::GenericDelimiters { inner } => {
+        if !inner.is_empty() {
+          out.push('<');
+          self.write_segments(out, inner);
+          out.push('>');
+        }
+      }
+      PathSegment::GenericArgumentList { entries } => {
+        // Anonymous lifetimes are elided, as in the source.
+        let named = entries
+          .iter()
+          .filter(|arg| {
+            !matches!(arg, GenericArgKind::Lifetime(Region::Anonymous))
+          })
+          .collect::<Vec<_>>();
+        if named.is_empty() {
+          return;
+        }
+        let shown = self
+          .options
+          .max_generic_args
+          .map_or(named.len(), |max| max.min(named.len()));
+        out.push('<');
+        self.comma_separated(out, &named[.. shown], |p, out, arg| {
+          p.write_generic_arg(out, arg);
+        });
+        if shown < named.len() {
+          out.push_str(if shown == 0 { ".." } else { ", .." });
+        }
+        out.push('>');
+      }
+      PathSegment::Impl { path, ty, kind } => match kind {
+        ImplKind::For => {
+          out.push_str("impl ");
+          if let Some(path) = path {
+            self.write_path(out, path);
+            out.push_str(" for ");
+          }
+          self.write_ty(out, *ty);
+        }
+        ImplKind::As => {
+          self.write_ty(out, *ty);
+          if let Some(path) = path {
+            out.push_str(" as ");
+            self.write_path(out, path);
+          }
+        }
+      },
+      PathSegment::AnonImpl { range } => {
+        let _ = write!(
+          out,
+          "impl@{}:{}",
+          range.start.line + 1,
+          range.start.column + 1
+        );
+      }
+    }
+  }
+
+  // --------------------------------------------------
+  // Constants
+
+  fn write_const(&self, out: &mut String, c: &Const) {
+    match c {
+      Const::Error => out.push_str("{const error}"),
+      Const::Param { data } => out.push_str(data),
+      Const::Infer {
+        data: InferConst::Anon,
+      }
+      | Const::Placeholder => out.push('_'),
+      Const::Bound {
+        data: BoundVariable::Error(e),
+      } => {
+        let _ = write!(out, "{{{e}}}");
+      }
+      Const::Unevaluated { data } => match data {
+        UnevaluatedConst::ValuePath { data } => self.write_path(out, data),
+        UnevaluatedConst::AnonSnippet { data } => out.push_str(data),
+        UnevaluatedConst::AnonLocation { krate, path } => {
+          out.push_str(krate);
+          out.push_str("::");
+          self.write_path(out, path);
+        }
+      },
+      Const::Value { data } => self.write_val_tree(out, data),
+      Const::Expr { data } => self.write_const_expr(out, data),
+    }
+  }
+
+  fn write_const_expr(&self, out: &mut String, expr: &ExprDef) {
+    match expr {
+      ExprDef::Binop(BinOp::Cmp, lhs, rhs) => {
+        self.write_const(out, lhs);
+        out.push_str(".cmp(");
+        self.write_const(out, rhs);
+        out.push(')');
+      }
+      ExprDef::Binop(op, lhs, rhs) => {
+        self.write_const(out, lhs);
+        let _ = write!(out, " {} ", bin_op(*op));
+        self.write_const(out, rhs);
+      }
+      ExprDef::UnOp(op, expr) => {
+        out.push(match op {
+          UnOp::Not => '!',
+          UnOp::Neg => '-',
+        });
+        self.write_const(out, expr);
+      }
+      ExprDef::FunctionCall(callable, args) => {
+        self.write_const(out, callable);
+        out.push('(');
+        self.comma_separated(out, args, |p, out, arg| p.write_const(out, arg));
+        out.push(')');
+      }
+      ExprDef::Cast(_, expr, ty) => {
+        self.write_const(out, expr);
+        out.push_str(" as ");
+        self.write_ty(out, *ty);
+      }
+    }
+  }
+
+  fn write_val_tree(&self, out: &mut String, tree: &ValTree) {
+    match tree {
+      ValTree::String { data, is_deref } => {
+        if *is_deref {
+          out.push('*');
+        }
+        out.push_str(data);
+      }
+      ValTree::Ref { inner } => {
+        out.push('&');
+        self.write_val_tree(out, inner);
+      }
+      ValTree::Leaf { data, kind } => {
+        if let LeafKind::Ref = kind {
+          out.push('&');
+        }
+        match data {
+          ConstScalarInt::False => out.push_str("false"),
+          ConstScalarInt::True => out.push_str("true"),
+          ConstScalarInt::Float { data, is_finite } => {
+            out.push_str(data);
+            if !is_finite {
+              out.push('_');
+            }
+          }
+          ConstScalarInt::Int { data }
+          | ConstScalarInt::Char { data }
+          | ConstScalarInt::Misc { data } => out.push_str(data),
+        }
+      }
+      ValTree::Aggregate { fields, kind } => {
+        let write_fields = |out: &mut String| {
+          self.comma_separated(out, fields, |p, out, c| p.write_const(out, c));
+        };
+        match kind {
+          ValTreeAggregateKind::Array => {
+            out.push('[');
+            write_fields(out);
+            out.push(']');
+          }
+          ValTreeAggregateKind::Tuple => {
+            out.push('(');
+            write_fields(out);
+            if fields.len() == 1 {
+              out.push(',');
+            }
+            out.push(')');
+          }
+          ValTreeAggregateKind::AdtNoVariants => out.push_str("unreachable()"),
+          ValTreeAggregateKind::Adt { data, kind } => match kind {
+            AdtAggregateKind::Fn => {
+              self.write_path(out, data);
+              out.push('(');
+              write_fields(out);
+              out.push(')');
+            }
+            AdtAggregateKind::Const => self.write_path(out, data),
+            AdtAggregateKind::Misc { names } => {
+              self.write_path(out, data);
+              out.push_str(" { ");
+              self.comma_separated(
+                out,
+                names.iter().zip(fields),
+                |p, out, (name, field)| {
+                  out.push_str(name);
+                  out.push_str(": ");
+                  p.write_const(out, field);
+                },
+              );
+              out.push_str(" }");
+            }
+          },
+        }
+      }
+    }
+  }
+
+  // --------------------------------------------------
+  // Predicates
+
+  fn write_predicate(&self, out: &mut String, predicate: &Predicate) {
+    self.write_binder(out, &predicate.bound_vars);
+    match &predicate.value {
+      PredicateKind::Clause(clause) => self.write_clause_kind(out, clause),
+      PredicateKind::ObjectSafe(path) => {
+        out.push_str("the trait `");
+        self.write_path(out, path);
+        out.push_str("` is object-safe");
+      }
+      PredicateKind::Subtype(SubtypePredicate { a, b, .. }) => {
+        self.write_ty(out, *a);
+        out.push_str(" <: ");
+        self.write_ty(out, *b);
+      }
+      PredicateKind::Coerce(CoercePredicate { a, b }) => {
+        self.write_ty(out, *a);
+        out.push_str(" -> ");
+        self.write_ty(out, *b);
+      }
+      PredicateKind::ConstEquate(a, b) => {
+        self.write_const(out, a);
+        out.push_str(" == ");
+        self.write_const(out, b);
+      }
+      PredicateKind::Ambiguous => out.push_str("ambiguous"),
+      PredicateKind::NormalizesTo(NormalizesTo { alias, term }) => {
+        self.write_path(out, alias);
+        out.push_str(" normalizes to ");
+        self.write_term(out, term);
+      }
+      PredicateKind::AliasRelate(a, b, direction) => {
+        self.write_term(out, a);
+        out.push_str(match direction {
+          AliasRelationDirection::Equate => " == ",
+          AliasRelationDirection::Subtype => " <: ",
+        });
+        self.write_term(out, b);
+      }
+    }
+  }
+
+  fn write_clause(&self, out: &mut String, clause: &Clause) {
+    self.write_binder(out, &clause.bound_vars);
+    self.write_clause_kind(out, &clause.value);
+  }
+
+  fn write_clause_kind(&self, out: &mut String, clause: &ClauseKind) {
+    match clause {
+      ClauseKind::Trait(TraitPredicate {
+        self_ty,
+        constness,
+        trait_ref,
+        polarity,
+      }) => {
+        self.write_ty(out, *self_ty);
+        out.push_str(": ");
+        if let BoundConstness::C = constness {
+          out.push_str("const ");
+        }
+        self.write_polarity(out, *polarity);
+        self.write_path(out, trait_ref);
+      }
+      ClauseKind::RegionOutlives(RegionOutlivesRegion { a, b }) => {
+        self.write_region(out, a);
+        out.push_str(": ");
+        self.write_region(out, b);
+      }
+      ClauseKind::TypeOutlives(TyOutlivesRegion { a, b }) => {
+        self.write_ty(out, *a);
+        out.push_str(": ");
+        self.write_region(out, b);
+      }
+      ClauseKind::Projection(ProjectionPredicate {
+        projection_term,
+        term,
+      }) => {
+        self.write_path(out, projection_term);
+        out.push_str(" == ");
+        self.write_term(out, term);
+      }
+      ClauseKind::ConstArgHasType(c, ty) => {
+        out.push_str("const ");
+        self.write_const(out, c);
+        out.push_str(": ");
+        self.write_ty(out, *ty);
+      }
+      ClauseKind::WellFormed(arg) => {
+        self.write_generic_arg(out, arg);
+        out.push_str(" well-formed");
+      }
+      ClauseKind::ConstEvaluatable(c) => {
+        self.write_const(out, c);
+        out.push_str(" can be evaluated");
+      }
+    }
+  }
+
+  fn write_impl_header(&self, out: &mut String, header: &ImplHeader) {
+    out.push_str("impl");
+    if !header.args.is_empty() {
+      out.push('<');
+      self.comma_separated(out, &header.args, |p, out, arg| {
+        p.write_generic_arg(out, arg);
+      });
+      out.push('>');
+    }
+    out.push(' ');
+    self.write_path(out, &header.name);
+    out.push_str(" for ");
+    self.write_ty(out, header.self_ty);
+
+    let predicates = &header.predicates;
+    if !predicates.grouped.is_empty()
+      || !predicates.other.is_empty()
+      || !header.tys_without_default_bounds.is_empty()
+    {
+      out.push_str(" where ");
+      self.write_clauses(out, predicates, &header.tys_without_default_bounds);
+    }
+  }
+
+  fn write_clauses(
+    &self,
+    out: &mut String,
+    clauses: &GroupedClauses,
+    unsized_tys: &[Ty],
+  ) {
+    let mut sep = Separator::new(", ");
+    for group in &clauses.grouped {
+      sep.push(out);
+      self.write_binder(out, &group.bound_vars);
+      self.write_clause_with_bounds(out, &group.value);
+    }
+    for clause in &clauses.other {
+      sep.push(out);
+      self.write_clause(out, clause);
+    }
+    for ty in unsized_tys {
+      sep.push(out);
+      self.write_ty(out, *ty);
+      out.push_str(": ?Sized");
+    }
+  }
+
+  /// `T: Fn(A) -> B + Trait + 'a`
+  fn write_clause_with_bounds(
+    &self,
+    out: &mut String,
+    clause: &ClauseWithBounds,
+  ) {
+    self.write_ty(out, clause.ty);
+    out.push_str(": ");
+    let mut bounds = clause.bounds.iter().collect::<Vec<_>>();
+    bounds.sort_by_key(|bound| match bound {
+      ClauseBound::FnTrait(..) => 0,
+      ClauseBound::Trait(..) | ClauseBound::AssocTrait(..) => 1,
+      ClauseBound::Region(..) => 2,
+    });
+    for (i, bound) in bounds.into_iter().enumerate() {
+      if i > 0 {
+        out.push_str(" + ");
+      }
+      match bound {
+        ClauseBound::Trait(polarity, path) => {
+          self.write_polarity(out, *polarity);
+          self.write_path(out, path);
+        }
+        ClauseBound::FnTrait(polarity, path, output) => {
+          self.write_polarity(out, *polarity);
+          self.write_fn_trait_bound(out, path, *output);
+        }
+        ClauseBound::AssocTrait(t) => self.write_trait(out, t),
+        ClauseBound::Region(region) => self.write_region(out, region),
+      }
+    }
+  }
+
+  /// `Fn(A) -> B`, or `Fn<(A,), Output = B>` without sugar.
+  fn write_fn_trait_bound(
+    &self,
+    out: &mut String,
+    path: &DefinedPath,
+    output: Ty,
+  ) {
+    self.write_path(out, path);
+    if self.is_unit(output) {
+      return;
+    }
+    if self.options.sugar_fn_traits {
+      out.push_str(" -> ");
+      self.write_ty(out, output);
+    } else {
+      // Add to the generic list if the path has one.
+      if out.ends_with('>') {
+        out.pop();
+        out.push_str(", ");
+      } else {
+        out.push('<');
+      }
+      out.push_str("Output = ");
+      self.write_ty(out, output);
+      out.push('>');
+    }
+  }
+}
+
+/// Pushes `sep` before every item but the first.
+struct Separator {
+  sep: &'static str,
+  first: bool,
+}
+
+impl Separator {
+  fn new(sep: &'static str) -> Self {
+    Separator { sep, first: true }
+  }
+
+  fn push(&mut self, out: &mut String) {
+    if !self.first {
+      out.push_str(self.sep);
+    }
+    self.first = false;
+  }
+}
+
+/// The name of a non-Rust ABI, as written in `extern "C"`.
+fn abi_name(abi: &Abi) -> Option<&'static str> {
+  Some(match abi {
+    Abi::Rust => return None,
+    Abi::C { .. } => "C",
+    Abi::Cdecl { .. } => "cdecl",
+    Abi::Stdcall { .. } => "stdcall",
+    Abi::Fastcall { .. } => "fastcall",
+    Abi::Vectorcall { .. } => "vectorcall",
+    Abi::Thiscall { .. } => "thiscall",
+    Abi::Aapcs { .. } => "aapcs",
+    Abi::Win64 { .. } => "win64",
+    Abi::SysV64 { .. } => "sysv64",
+    Abi::PtxKernel => "ptx-kernel",
+    Abi::Msp430Interrupt => "msp430-interrupt",
+    Abi::X86Interrupt => "x86-interrupt",
+    Abi::EfiApi => "efiapi",
+    Abi::AvrInterrupt => "avr-interrupt",
+    Abi::AvrNonBlockingInterrupt => "avr-non-blocking-interrupt",
+    Abi::CCmseNonSecureCall => "C-cmse-nonsecure-call",
+    Abi::Wasm => "wasm",
+    Abi::System { .. } => "system",
+    Abi::RustIntrinsic => "rust-intrinsic",
+    Abi::RustCall => "rust-call",
+    Abi::Unadjusted => "unadjusted",
+    Abi::RustCold => "rust-cold",
+    Abi::RiscvInterruptM => "riscv-interrupt-m",
+    Abi::RiscvInterruptS => "riscv-interrupt-s",
+  })
+}
+
+fn bin_op(op: BinOp) -> &'static str {
+  match op {
+    BinOp::Add | BinOp::AddUnchecked => "+",
+    BinOp::Sub | BinOp::SubUnchecked => "-",
+    BinOp::Mul | BinOp::MulUnchecked => "*",
+    BinOp::Div => "/",
+    BinOp::Rem => "%",
+    BinOp::BitXor => "^",
+    BinOp::BitAnd => "&",
+    BinOp::BitOr => "|",
+    BinOp::Shl | BinOp::ShlUnchecked => "<<",
+    BinOp::Shr | BinOp::ShrUnchecked => ">>",
+    BinOp::Eq => "==",
+    BinOp::Lt => "<",
+    BinOp::Le => "<=",
+    BinOp::Ne => "!=",
+    BinOp::Ge => ">=",
+    BinOp::Gt => ">",
+    BinOp::Offset => ".",
+    BinOp::Cmp => "cmp",
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{PrintOptions, Printer};
+  use crate::{
+    argus::{
+      ClauseBound, ClauseWithBounds, GroupedClauses, PolyClauseWithBounds,
+    },
+    path::{DefinedPath, PathSegment},
+    ty::{
+      Abi, FnSig, GenericArgKind, IntTy, ParamTy, Polarity, PolyFnSig, Safety,
+      Ty, TyKind, UintTy,
+    },
+  };
+
+  fn name(name: &str) -> PathSegment {
+    PathSegment::DefPathDataName {
+      name: name.to_owned(),
+      disambiguator: None,
+    }
+  }
+
+  fn path(segments: Vec<PathSegment>) -> DefinedPath {
+    DefinedPath::Path {
+      path: segments,
+      l: None,
+    }
+  }
+
+  fn param(name: &str) -> TyKind {
+    TyKind::Param(ParamTy {
+      name: name.to_owned(),
+    })
+  }
+
+  /// `F: Fn(u32) -> output`
+  fn fn_bound(output: Ty) -> GroupedClauses {
+    let fn_path = path(vec![name("Fn"), PathSegment::GenericArgumentList {
+      entries: vec![GenericArgKind::Type(1)],
+    }]);
+    fn_path_bound(fn_path, output)
+  }
+
+  /// `F: fn_path -> output`
+  fn fn_path_bound(fn_path: DefinedPath, output: Ty) -> GroupedClauses {
+    GroupedClauses {
+      grouped: vec![PolyClauseWithBounds {
+        value: ClauseWithBounds {
+          ty: 2,
+          bounds: vec![ClauseBound::FnTrait(
+            Polarity::Positive,
+            fn_path,
+            output,
+          )],
+        },
+        bound_vars: vec![],
+      }],
+      other: vec![],
+    }
+  }
+
+  #[test]
+  fn fn_trait_bounds() {
+    let tys = [
+      TyKind::Uint(UintTy::U32),
+      TyKind::Tuple(vec![0]),
+      param("F"),
+      TyKind::Bool,
+      TyKind::Tuple(vec![]),
+    ];
+    let sugared = Printer::new(&tys, PrintOptions::default());
+    let unsugared = Printer::new(&tys, PrintOptions {
+      sugar_fn_traits: false,
+      ..PrintOptions::default()
+    });
+
+    assert_eq!(sugared.clauses(&fn_bound(3)), "F: Fn(u32) -> bool");
+    assert_eq!(
+      unsugared.clauses(&fn_bound(3)),
+      "F: Fn<(u32,), Output = bool>"
+    );
+
+    // A unit output is left out in both forms.
+    assert_eq!(sugared.clauses(&fn_bound(4)), "F: Fn(u32)");
+    assert_eq!(unsugared.clauses(&fn_bound(4)), "F: Fn<(u32,)>");
+
+    // Without a generic list the output gets one of its own.
+    let bare = fn_path_bound(path(vec![name("Fn")]), 3);
+    assert_eq!(unsugared.clauses(&bare), "F: Fn<Output = bool>");
+  }
+
+  #[test]
+  fn c_variadic_fn_ptrs() {
+    let fn_ptr = |inputs_and_output: Vec<Ty>| {
+      TyKind::FnPtr(PolyFnSig {
+        value: FnSig {
+          inputs_and_output,
+          c_variadic: true,
+          safety: Safety::Unsafe,
+          abi: Abi::C { unwind: false },
+        },
+        bound_vars: vec![],
+      })
+    };
+    let tys = [
+      TyKind::Int(IntTy::I32),
+      TyKind::Uint(UintTy::U8),
+      TyKind::Tuple(vec![1, 1]),
+      TyKind::Tuple(vec![]),
+      fn_ptr(vec![0, 2]),
+      fn_ptr(vec![3]),
+    ];
+    let printer = Printer::new(&tys, PrintOptions::default());
+
+    // The `...` closes the inputs, before the output.
+    assert_eq!(
+      printer.ty(4),
+      "unsafe extern \"C\" fn(i32, ...) -> (u8, u8)"
+    );
+    assert_eq!(printer.ty(5), "unsafe extern \"C\" fn(...)");
+  }
+
+  #[test]
+  fn long_generic_args() {
+    let args = ["K", "V", "S"].into_iter().map(param);
+    let map = TyKind::Adt(path(vec![
+      name("std"),
+      PathSegment::Colons,
+      name("collections"),
+      PathSegment::Colons,
+      name("HashMap"),
+      PathSegment::GenericArgumentList {
+        entries: (0 .. 3).map(GenericArgKind::Type).collect(),
+      },
+    ]));
+    let tys = args.chain([map]).collect::<Vec<_>>();
+    let print = |trim_paths, max_generic_args| {
+      Printer::new(&tys, PrintOptions {
+        trim_paths,
+        max_generic_args,
+        ..PrintOptions::default()
+      })
+      .ty(3)
+    };
+
+    assert_eq!(print(true, None), "HashMap<K, V, S>");
+    assert_eq!(print(true, Some(3)), "HashMap<K, V, S>");
+    assert_eq!(print(true, Some(1)), "HashMap<K, ..>");
+    assert_eq!(print(true, Some(0)), "HashMap<..>");
+    assert_eq!(print(false, Some(2)), "std::collections::HashMap<K, V, ..>");
+  }
+}
@@ -0,0 +1,281 @@
+//! Serialized proof trees, see `argus_lib::proof_tree`.
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  argus::{ArgDiff, ImplHeaderRef},
+  ty::{GoalPredicate, Ty, TyAbbreviations, TyIdx, TyVal},
+  types::{EvaluationResult, ObligationNecessity},
+  CharRange,
+};
+
+pub type ProofNodeIdx = usize;
+
+pub type GoalIdx = usize;
+
+pub type CandidateIdx = usize;
+
+pub type ResultIdx = usize;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedTree {
+  pub root: ProofNodeIdx,
+  pub nodes: Vec<Node>,
+  pub goals: Vec<GoalData>,
+  pub candidates: Vec<CandidateData>,
+  pub results: Vec<EvaluationResult>,
+  /// Types referenced by [`Ty`](crate::ty::Ty) indices in the tree.
+  pub tys: Vec<TyVal>,
+  #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+  pub ty_abbreviations: TyAbbreviations,
+  /// Aliases in the tree mapped to the types they are equated with.
+  pub projection_values: HashMap<TyIdx, TyIdx>,
+  /// Impls of the trait of each trait goal, not only those rustc tried.
+  pub all_impl_candidates: HashMap<ProofNodeIdx, Vec<CandidateIdx>>,
+  /// Where trait goals differ from their impl candidates, by goal node.
+  #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+  pub impl_diffs: HashMap<ProofNodeIdx, Vec<ImplDiff>>,
+  pub topology: TreeTopology,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub cycle: Option<ProofCycle>,
+  pub analysis: AnalysisResults,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum Node {
+  Goal(GoalIdx),
+  Candidate(CandidateIdx),
+  Result(ResultIdx),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct GoalData {
+  pub value: GoalPredicate,
+  pub necessity: ObligationNecessity,
+  pub num_vars: usize,
+  /// Is one of the main components a type variable?
+  pub is_main_tv: bool,
+  pub result: ResultIdx,
+  /// Library-authored message from `#[diagnostic::on_unimplemented]`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub diagnostic: Option<DiagnosticNote>,
+  /// Only emitted by debug builds of Argus.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub debug_comparison: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticNote {
+  pub message: Option<String>,
+  pub label: Option<String>,
+  pub notes: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum CandidateData {
+  Impl {
+    hd: ImplHeaderRef,
+    is_user_visible: bool,
+  },
+  ParamEnv(usize),
+  Any(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct TreeTopology {
+  pub children: HashMap<ProofNodeIdx, HashSet<ProofNodeIdx>>,
+  pub parent: HashMap<ProofNodeIdx, ProofNodeIdx>,
+}
+
+/// Nodes of a cycle found while building the tree, from the root.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct ProofCycle(pub Vec<ProofNodeIdx>);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct ImplDiff {
+  pub candidate: CandidateIdx,
+  pub args: Vec<ArgDiff>,
+}
+
+// --------------------------------------------------
+// Root cause analysis
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisResults {
+  pub problematic_sets: Vec<SetHeuristic>,
+  pub held_across_await: Vec<HeldAcrossAwait>,
+}
+
+impl AnalysisResults {
+  /// Goals in the order they're presented to the user, sets are ordered by
+  /// momentum and those involving a bad unification come last.
+  pub fn ranked_goals(&self) -> Vec<ProofNodeIdx> {
+    let mut sets = self.problematic_sets.iter().collect::<Vec<_>>();
+    sets.sort_by_key(|s| (s.has_bad_unification(), s.momentum));
+
+    let mut goals = vec![];
+    for idx in sets.into_iter().flat_map(SetHeuristic::goals) {
+      if !goals.contains(&idx) {
+        goals.push(idx);
+      }
+    }
+    goals
+  }
+}
+
+/// A set of failing goals that together explain the error.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SetHeuristic {
+  pub momentum: usize,
+  pub velocity: usize,
+  pub goals: Vec<Heuristic>,
+}
+
+impl SetHeuristic {
+  pub fn goals(&self) -> impl Iterator<Item = ProofNodeIdx> + '_ {
+    self.goals.iter().map(|h| h.idx)
+  }
+
+  /// Does the set contain a function with the wrong parameters? These are
+  /// hidden by the frontend unless all sets contain one.
+  pub fn has_bad_unification(&self) -> bool {
+    self.goals.iter().any(|h| {
+      matches!(
+        h.kind,
+        GoalKind::DeleteFnParams { .. }
+          | GoalKind::AddFnParams { .. }
+          | GoalKind::IncorrectParams { .. }
+      )
+    })
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Heuristic {
+  pub idx: ProofNodeIdx,
+  pub kind: GoalKind,
+  pub do_not_recommend: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(tag = "type")]
+pub enum GoalKind {
+  Trait {
+    #[serde(rename = "_self")]
+    self_: Location,
+    #[serde(rename = "_trait")]
+    trait_: Location,
+  },
+  ForbiddenImpl(ForbiddenImpl),
+  TyChange,
+  FnToTrait {
+    #[serde(rename = "_trait")]
+    trait_: Location,
+    arity: usize,
+  },
+  TyAsCallable {
+    arity: usize,
+  },
+  DeleteFnParams {
+    delta: usize,
+  },
+  AddFnParams {
+    delta: usize,
+  },
+  IncorrectParams {
+    arity: usize,
+  },
+  /// Either type binds lifetimes.
+  HigherRankedSubtype {
+    sub: Ty,
+    sup: Ty,
+  },
+  /// A failed `Unsize` or `CoerceUnsized` goal.
+  Unsize {
+    source: Ty,
+    target: Ty,
+  },
+  Misc,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(tag = "type")]
+pub enum Location {
+  Local,
+  External,
+}
+
+/// A trait predicate the user cannot fix by writing an impl.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ForbiddenImpl {
+  pub rule: ImplRestriction,
+  /// Ordered by preference.
+  pub alternatives: Vec<Alternative>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(tag = "type")]
+pub enum ImplRestriction {
+  Orphan,
+  Overlap,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(tag = "type")]
+pub enum Alternative {
+  Newtype,
+  LocalTrait,
+  TyChange,
+}
+
+/// A value held across an `.await` that makes the future fail an auto
+/// trait, e.g., `Send`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct HeldAcrossAwait {
+  pub witness: ProofNodeIdx,
+  pub held: ProofNodeIdx,
+  pub leaf: ProofNodeIdx,
+  /// Name of the local, `None` for temporaries.
+  pub name: Option<String>,
+  /// Ranges of the held value and the await, `None` for coroutines whose
+  /// layout isn't available, e.g., in bodies with errors.
+  pub value_range: Option<CharRange>,
+  pub await_range: Option<CharRange>,
+  pub field: Option<FieldCause>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct FieldCause {
+  pub owner: ProofNodeIdx,
+  pub field: String,
+}
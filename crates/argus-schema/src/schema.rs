@@ -0,0 +1,71 @@
+//! JSON Schema documents of the CLI outputs.
+//!
+//! These describe the same types as the TS bindings, there is one document
+//! per command and each pins `schemaVersion` to [`SCHEMA_VERSION`].
+//!
+//! Like the bindings they're generated rather than checked in,
+//! `cargo make init-bindings` writes them to `crates/argus-schema/schemas`.
+//! The tutorial workflow publishes them to GitHub Pages with the book, as
+//! `schemas/<command>.schema.json`.
+use std::{fs, io, path::Path};
+
+use schemars::{gen::SchemaGenerator, schema::RootSchema, JsonSchema};
+
+use crate::{
+  BundleOutput, CrateSummaryOutput, EvaluateOutput, ItemsOutput,
+  ObligationsOutput, SharedBundleOutput, SummaryOutput, TreeOutput,
+  SCHEMA_VERSION,
+};
+
+/// The schema of every command's output, keyed by command name.
+pub fn documents() -> Vec<(&'static str, RootSchema)> {
+  vec![
+    document::<TreeOutput>("tree"),
+    document::<ObligationsOutput>("obligations"),
+    document::<ItemsOutput>("items"),
+    document::<BundleOutput>("bundle"),
+    document::<SharedBundleOutput>("bundle-shared"),
+    document::<EvaluateOutput>("evaluate"),
+    document::<CrateSummaryOutput>("crate-summary"),
+    document::<SummaryOutput>("summary"),
+  ]
+}
+
+/// Write each document to `dir` as `<command>.schema.json`.
+pub fn export(dir: &Path) -> io::Result<()> {
+  fs::create_dir_all(dir)?;
+  for (command, schema) in documents() {
+    let path = dir.join(format!("{command}.schema.json"));
+    let json = serde_json::to_string_pretty(&schema)?;
+    fs::write(path, json + "\n")?;
+  }
+  Ok(())
+}
+
+fn document<T: JsonSchema>(
+  command: &'static str,
+) -> (&'static str, RootSchema) {
+  let mut root = SchemaGenerator::default().into_root_schema_for::<T>();
+  root.schema.metadata().title = Some(format!("cargo argus {command}"));
+  let version = root
+    .schema
+    .object()
+    .properties
+    .get_mut("schemaVersion")
+    .expect("outputs are versioned");
+  let mut object = version.clone().into_object();
+  object.const_value = Some(SCHEMA_VERSION.into());
+  *version = object.into();
+  (command, root)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::path::Path;
+
+  #[test]
+  fn export_schemas() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas");
+    super::export(&dir).unwrap();
+  }
+}
@@ -0,0 +1,47 @@
+//! Workspace trait error summaries, see `argus_lib::summary`.
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Source location of a trait error, zero-indexed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorLocation {
+  pub filename: String,
+  pub line: usize,
+  pub column: usize,
+}
+
+/// A trait error reported in a body, the output of `cargo argus
+/// crate-summary`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TraitErrorSite {
+  pub location: ErrorLocation,
+  pub failing_trait: Option<String>,
+  pub self_ty: Option<String>,
+  pub root_cause: Option<String>,
+}
+
+/// Errors sharing a key, most frequent first.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorGroup {
+  pub key: String,
+  pub count: usize,
+  pub locations: Vec<ErrorLocation>,
+}
+
+/// The output of `cargo argus summary`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Summary {
+  pub errors: usize,
+  pub by_trait: Vec<ErrorGroup>,
+  pub by_self_ty: Vec<ErrorGroup>,
+  pub by_root_cause: Vec<ErrorGroup>,
+}
@@ -0,0 +1,694 @@
+//! Types, constants and predicates, see `argus_ser::ty`.
+//!
+//! Types are interned, a [`Ty`] is an index into the `tys` table of the
+//! enclosing body or proof tree.
+use std::collections::HashMap;
+
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  argus::GroupedClauses,
+  path::{
+    AliasPath, BasicPathNoArgs, DefinedPath, PathDefNoArgs, PathDefWithArgs,
+    ValuePathWithArgs,
+  },
+};
+
+pub type TyIdx = usize;
+
+pub type Ty = TyIdx;
+
+pub type TyVal = TyKind;
+
+/// Placeholders of the abbreviated form of types past the depth and width
+/// limits, by type. Placeholder `$n` stands for the `n - 1`th entry.
+pub type TyAbbreviations = HashMap<TyIdx, Vec<TyIdx>>;
+
+pub type Symbol = String;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum TyKind {
+  Bool,
+  Char,
+  Int(IntTy),
+  Uint(UintTy),
+  Float(FloatTy),
+  /// The pattern is dropped, leaving a one element tuple.
+  Pat((Ty,)),
+  Adt(PathDefWithArgs),
+  Str,
+  Array(Ty, Const),
+  Slice(Ty),
+  RawPtr(TypeAndMut),
+  Ref(Region, Ty, Mutability),
+  FnDef(FnDef),
+  FnPtr(PolyFnSig),
+  Never,
+  Tuple(Vec<Ty>),
+  Placeholder(PlaceholderBoundTy),
+  Infer(InferTy),
+  Error,
+  Foreign(PathDefNoArgs),
+  Closure(PathDefWithArgs),
+  Param(ParamTy),
+  Bound(BoundTy),
+  Alias(AliasTyKind),
+  Dynamic(DynamicTyKind),
+  Coroutine(CoroutineTyKind),
+  CoroutineClosure(CoroutineClosureTyKind),
+  CoroutineWitness(CoroutineWitnessTyKind),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(tag = "type")]
+pub enum AliasTyKind {
+  OpaqueImpl { data: OpaqueImpl },
+  AliasTy { data: AliasTy },
+  DefPath { data: PathDefWithArgs },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(tag = "type")]
+pub enum AliasTy {
+  Inherent { data: AliasPath },
+  PathDef { data: PathDefWithArgs },
+}
+
+pub type AliasTerm = PathDefWithArgs;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct DynamicTyKind {
+  pub predicates: PolyExistentialPredicates,
+  pub region: Region,
+  pub kind: DynKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct PolyExistentialPredicates {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub data: Option<PathDefNoArgs>,
+  pub auto_traits: Vec<PathDefNoArgs>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum DynKind {
+  Dyn,
+  DynStar,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CoroutineTyKind {
+  pub path: PathDefWithArgs,
+  pub movability: Movability,
+  pub upvar_tys: Ty,
+  pub witness: Ty,
+  pub should_print_movability: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CoroutineClosureTyKind {
+  pub path: PathDefWithArgs,
+  pub closure_kind: Ty,
+  pub signature_parts: Ty,
+  pub upvar_tys: Ty,
+  pub captures_by_ref: Ty,
+  pub witness: Ty,
+}
+
+pub type CoroutineWitnessTyKind = PathDefWithArgs;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum Movability {
+  Static,
+  Movable,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct FnDef {
+  pub sig: PolyFnSig,
+  pub path: ValuePathWithArgs,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct PolyFnSig {
+  pub value: FnSig,
+  pub bound_vars: Vec<BoundVariableKind>,
+}
+
+/// The last of `inputs_and_output` is the return type.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct FnSig {
+  pub inputs_and_output: Vec<Ty>,
+  pub c_variadic: bool,
+  pub safety: Safety,
+  pub abi: Abi,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum Safety {
+  Unsafe,
+  Safe,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum Abi {
+  Rust,
+  C { unwind: bool },
+  Cdecl { unwind: bool },
+  Stdcall { unwind: bool },
+  Fastcall { unwind: bool },
+  Vectorcall { unwind: bool },
+  Thiscall { unwind: bool },
+  Aapcs { unwind: bool },
+  Win64 { unwind: bool },
+  SysV64 { unwind: bool },
+  PtxKernel,
+  Msp430Interrupt,
+  X86Interrupt,
+  EfiApi,
+  AvrInterrupt,
+  AvrNonBlockingInterrupt,
+  CCmseNonSecureCall,
+  Wasm,
+  System { unwind: bool },
+  RustIntrinsic,
+  RustCall,
+  Unadjusted,
+  RustCold,
+  RiscvInterruptM,
+  RiscvInterruptS,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(tag = "type")]
+pub enum PlaceholderBoundTy {
+  Named { data: Symbol },
+  Anon,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(tag = "type")]
+pub enum BoundTy {
+  Named { data: Symbol },
+  Bound { data: BoundVariable },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum BoundVariable {
+  Error(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum BoundVariableKind {
+  Ty(BoundTyKind),
+  Region(BoundRegionKind),
+  Const,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum BoundRegionKind {
+  BrAnon,
+  /// The definition is dropped, leaving a one element tuple.
+  BrNamed((Symbol,)),
+  BrEnv,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum BoundTyKind {
+  Anon,
+  /// The definition is dropped, leaving a one element tuple.
+  Param((Symbol,)),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum IntTy {
+  Isize,
+  I8,
+  I16,
+  I32,
+  I64,
+  I128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum UintTy {
+  Usize,
+  U8,
+  U16,
+  U32,
+  U64,
+  U128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum FloatTy {
+  F16,
+  F32,
+  F64,
+  F128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct TypeAndMut {
+  pub ty: Ty,
+  pub mutbl: Mutability,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum Mutability {
+  Not,
+  Mut,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(tag = "type")]
+pub enum Region {
+  Named { data: Symbol },
+  Anonymous,
+  Static,
+}
+
+pub type GenericArg = GenericArgKind;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum GenericArgKind {
+  Lifetime(Region),
+  Type(Ty),
+  Const(Const),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum InferTy {
+  IntVar,
+  FloatVar,
+  Unnamed(PathDefNoArgs),
+  SourceInfo(String),
+  Unresolved,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct ParamTy {
+  pub name: Symbol,
+}
+
+// --------------------------------------------------
+// Constants
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(tag = "type")]
+pub enum Const {
+  Unevaluated { data: UnevaluatedConst },
+  Param { data: ParamConst },
+  Infer { data: InferConst },
+  Bound { data: BoundVariable },
+  Placeholder,
+  Value { data: ValTree },
+  Error,
+  Expr { data: Box<ExprDef> },
+}
+
+pub type ParamConst = Symbol;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum InferConst {
+  Anon,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(tag = "type")]
+pub enum UnevaluatedConst {
+  ValuePath {
+    data: ValuePathWithArgs,
+  },
+  AnonSnippet {
+    data: String,
+  },
+  AnonLocation {
+    krate: Symbol,
+    path: BasicPathNoArgs,
+  },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(tag = "type")]
+pub enum ValTree {
+  Ref {
+    inner: Box<ValTree>,
+  },
+  #[serde(rename_all = "camelCase")]
+  String {
+    data: String,
+    is_deref: bool,
+  },
+  Aggregate {
+    fields: Vec<Const>,
+    kind: ValTreeAggregateKind,
+  },
+  Leaf {
+    data: ConstScalarInt,
+    kind: LeafKind,
+  },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(tag = "type")]
+pub enum ValTreeAggregateKind {
+  Array,
+  Tuple,
+  AdtNoVariants,
+  Adt {
+    data: ValuePathWithArgs,
+    kind: AdtAggregateKind,
+  },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(tag = "type")]
+pub enum AdtAggregateKind {
+  Fn,
+  Const,
+  Misc { names: Vec<Symbol> },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(tag = "type")]
+pub enum LeafKind {
+  Ref,
+  Scalar,
+}
+
+/// Scalars are pre-formatted, e.g., `3_u8` or `'a'`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(tag = "type")]
+pub enum ConstScalarInt {
+  False,
+  True,
+  #[serde(rename_all = "camelCase")]
+  Float {
+    data: String,
+    is_finite: bool,
+  },
+  Int {
+    data: String,
+  },
+  Char {
+    data: String,
+  },
+  Misc {
+    data: String,
+  },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum ExprDef {
+  Binop(BinOp, Const, Const),
+  UnOp(UnOp, Const),
+  FunctionCall(Const, Vec<Const>),
+  Cast(CastKind, Const, Ty),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum BinOp {
+  Add,
+  AddUnchecked,
+  Cmp,
+  Sub,
+  SubUnchecked,
+  Mul,
+  MulUnchecked,
+  Div,
+  Rem,
+  BitXor,
+  BitAnd,
+  BitOr,
+  Shl,
+  ShlUnchecked,
+  Shr,
+  ShrUnchecked,
+  Eq,
+  Lt,
+  Le,
+  Ne,
+  Ge,
+  Gt,
+  Offset,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum UnOp {
+  Not,
+  Neg,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum CastKind {
+  As,
+  Use,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum Term {
+  Ty(Ty),
+  Const(Const),
+}
+
+// --------------------------------------------------
+// Opaque types
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct OpaqueImpl {
+  pub fn_traits: Vec<FnTrait>,
+  pub traits: Vec<Trait>,
+  pub lifetimes: Vec<Region>,
+  pub has_sized_bound: bool,
+  pub has_negative_sized_bound: bool,
+}
+
+/// Sugared `Fn*` bound, e.g., `impl Fn(u32) -> String`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct FnTrait {
+  pub params: Vec<Ty>,
+  pub ret_ty: Option<Ty>,
+  pub kind: ClosureKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Trait {
+  pub polarity: Polarity,
+  pub trait_name: DefinedPath,
+  pub own_args: Vec<GenericArg>,
+  pub assoc_args: Vec<AssocItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct AssocItem {
+  pub name: Symbol,
+  pub term: Term,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum ClosureKind {
+  Fn,
+  FnMut,
+  FnOnce,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum Polarity {
+  Positive,
+  Negative,
+  Maybe,
+}
+
+// --------------------------------------------------
+// Predicates
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct PredicateObligation {
+  pub param_env: ParamEnv,
+  pub predicate: Predicate,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct GoalPredicate {
+  pub predicate: Predicate,
+  pub param_env: ParamEnv,
+}
+
+pub type ParamEnv = GroupedClauses;
+
+pub type Predicate = PolyPredicateKind;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct PolyPredicateKind {
+  pub bound_vars: Vec<BoundVariableKind>,
+  pub value: PredicateKind,
+}
+
+pub type Clause = PolyClauseKind;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct PolyClauseKind {
+  pub bound_vars: Vec<BoundVariableKind>,
+  pub value: ClauseKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum PredicateKind {
+  Clause(ClauseKind),
+  ObjectSafe(PathDefNoArgs),
+  Subtype(SubtypePredicate),
+  Coerce(CoercePredicate),
+  ConstEquate(Const, Const),
+  Ambiguous,
+  NormalizesTo(NormalizesTo),
+  AliasRelate(Term, Term, AliasRelationDirection),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum ClauseKind {
+  Trait(TraitPredicate),
+  RegionOutlives(RegionOutlivesRegion),
+  TypeOutlives(TyOutlivesRegion),
+  Projection(ProjectionPredicate),
+  ConstArgHasType(Const, Ty),
+  WellFormed(GenericArg),
+  ConstEvaluatable(Const),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct TraitPredicate {
+  pub self_ty: Ty,
+  pub constness: BoundConstness,
+  pub trait_ref: TraitRefPrintTraitSugared,
+  pub polarity: Polarity,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum BoundConstness {
+  C,
+  No,
+}
+
+pub type TraitRefPrintOnlyTraitPath = PathDefWithArgs;
+
+pub type TraitRefPrintTraitSugared = PathDefWithArgs;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct RegionOutlivesRegion {
+  pub a: Region,
+  pub b: Region,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct TyOutlivesRegion {
+  pub a: Ty,
+  pub b: Region,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct ProjectionPredicate {
+  pub projection_term: AliasTerm,
+  pub term: Term,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct SubtypePredicate {
+  pub a_is_expected: bool,
+  pub a: Ty,
+  pub b: Ty,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct CoercePredicate {
+  pub a: Ty,
+  pub b: Ty,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct NormalizesTo {
+  pub alias: AliasTerm,
+  pub term: Term,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum AliasRelationDirection {
+  Equate,
+  Subtype,
+}
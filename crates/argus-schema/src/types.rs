@@ -0,0 +1,325 @@
+//! Obligations of bodies and items, see `argus_lib::types`.
+use std::collections::HashMap;
+
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  argus::ImplHeader,
+  hir,
+  path::{DefinedPath, PathDefNoArgs},
+  proof_tree::SerializedTree,
+  ty::{Predicate, PredicateObligation, Ty, TyAbbreviations, TyVal},
+  CharRange,
+};
+
+pub type ExprIdx = usize;
+
+pub type ObligationIdx = usize;
+
+/// A body with the proof trees of its necessary obligations, the output
+/// of `cargo argus bundle`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BodyBundle {
+  pub filename: String,
+  pub body: ObligationsInBody,
+  pub trees: HashMap<ObligationHash, SerializedTree>,
+}
+
+/// Bundles of every body in a crate, the output of `cargo argus bundle
+/// --shared-tables`. Types, paths and impl headers are written once in the
+/// tables, the `tys` of each body and tree are empty. Each crate of a
+/// workspace is written with its own tables.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SharedBundle {
+  pub tys: Vec<TyVal>,
+  #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+  pub ty_abbreviations: TyAbbreviations,
+  pub paths: Vec<DefinedPath>,
+  pub impl_headers: Vec<ImplHeader>,
+  pub bodies: Vec<BodyBundle>,
+}
+
+/// An item with the proof trees of its necessary obligations, the output
+/// of `cargo argus items`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ItemBundle {
+  pub filename: String,
+  pub item: ObligationsInItem,
+  pub trees: HashMap<ObligationHash, SerializedTree>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ObligationsInBody {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub name: Option<PathDefNoArgs>,
+  pub hash: BodyHash,
+  /// Range of the represented body.
+  pub range: CharRange,
+  pub ambiguity_errors: Vec<AmbiguityError>,
+  pub trait_errors: Vec<TraitError>,
+  pub obligations: Vec<Obligation>,
+  pub exprs: Vec<Expr>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub method_lookups: Vec<MethodLookup>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub closures: Vec<ClosureLink>,
+  /// Types referenced by [`Ty`] indices in the body.
+  pub tys: Vec<TyVal>,
+  #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+  pub ty_abbreviations: TyAbbreviations,
+}
+
+/// Obligations of an impl block, trait or type definition checked outside of
+/// any body, i.e., during well-formedness and coherence checking.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ObligationsInItem {
+  pub name: PathDefNoArgs,
+  /// Range of the represented item.
+  pub range: CharRange,
+  pub coherence_errors: Vec<CoherenceError>,
+  pub ambiguity_errors: Vec<AmbiguityError>,
+  pub trait_errors: Vec<TraitError>,
+  pub obligations: Vec<Obligation>,
+  pub exprs: Vec<Expr>,
+  /// Types referenced by [`Ty`] indices in the item.
+  pub tys: Vec<TyVal>,
+  #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+  pub ty_abbreviations: TyAbbreviations,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Obligation {
+  pub obligation: PredicateObligation,
+  pub hash: ObligationHash,
+  /// Hash of the predicate alone, shared by equal predicates at different
+  /// locations.
+  pub predicate_hash: ObligationHash,
+  pub range: CharRange,
+  pub kind: ObligationKind,
+  pub necessity: ObligationNecessity,
+  pub result: EvaluationResult,
+  /// Whether the obligation is shown to the user and has a proof tree.
+  pub is_necessary: bool,
+  /// Macro invocations the obligation was expanded from, innermost first.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub expansion: Vec<MacroFrame>,
+  /// The type or bound the obligation points to as written by the user.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub hir: Option<hir::Node>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct MacroFrame {
+  pub name: String,
+  pub call_site: CharRange,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub def_site: Option<CharRange>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum ObligationKind {
+  Success,
+  Ambiguous,
+  Failure,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum ObligationNecessity {
+  No,
+  OnError,
+  Yes,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum EvaluationResult {
+  Yes,
+  MaybeOverflow,
+  MaybeAmbiguity,
+  No,
+}
+
+#[derive(
+  Serialize,
+  Deserialize,
+  Debug,
+  Clone,
+  Copy,
+  PartialEq,
+  Eq,
+  Hash,
+  PartialOrd,
+  Ord,
+)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct ObligationHash(
+  #[serde(with = "crate::string")]
+  #[cfg_attr(feature = "json-schema", schemars(with = "String"))]
+  pub u64,
+);
+
+/// Identity of a body that is stable across compilations.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct BodyHash(pub String);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Expr {
+  pub range: CharRange,
+  pub snippet: String,
+  pub obligations: Vec<ObligationIdx>,
+  pub kind: ExprKind,
+  pub is_body: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum ExprKind {
+  Misc,
+  CallableExpr,
+  Call,
+  CallArg,
+  FnSigTy,
+  WhereClause,
+  FieldTy,
+  ImplHeader,
+  AssocBinding,
+  Closure,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ClosureLink {
+  pub closure: ExprIdx,
+  /// Obligations whose self type is the closure.
+  pub requirements: Vec<ObligationIdx>,
+  /// Necessary obligations within the closure body that failed.
+  pub failures: Vec<ObligationIdx>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct AmbiguityError {
+  pub idx: ExprIdx,
+  pub range: CharRange,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TraitError {
+  pub idx: ExprIdx,
+  pub range: CharRange,
+  pub hashes: Vec<ObligationHash>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CoherenceError {
+  pub kind: CoherenceErrorKind,
+  pub range: CharRange,
+  /// Headers of the other impls that overlap with this one.
+  pub conflicting_impls: Vec<ImplHeader>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum CoherenceErrorKind {
+  Orphan,
+  Overlap,
+}
+
+// --------------------------------------------------
+// Method resolution
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct MethodLookup {
+  /// Range of the method name and arguments.
+  pub range: CharRange,
+  pub name: String,
+  /// The method picked by rustc, absent if resolution failed.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub resolved: Option<PathDefNoArgs>,
+  /// Receiver types in the order they were probed.
+  pub steps: Vec<ReceiverStep>,
+  pub candidates: Vec<MethodCandidate>,
+  /// Traits not in scope that provide the method for the receiver.
+  pub extension_candidates: ExtensionCandidates,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiverStep {
+  pub ty: Ty,
+  pub autoderefs: usize,
+  pub from_unsafe_deref: bool,
+  pub unsize: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct MethodCandidate {
+  pub kind: MethodCandidateKind,
+  pub method: PathDefNoArgs,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub receiver: Option<ReceiverMatch>,
+  pub unmet_bounds: Vec<Predicate>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum MethodCandidateKind {
+  Inherent,
+  Trait,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiverMatch {
+  pub step: usize,
+  pub autoref: Autoref,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum Autoref {
+  ByValue,
+  Ref,
+  RefMut,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ExtensionCandidates {
+  pub data: Vec<crate::ty::TraitRefPrintOnlyTraitPath>,
+}
@@ -4,9 +4,14 @@ use std::path::PathBuf;
 use argus_ext::ty::TyCtxtExt;
 use itertools::Itertools;
 use rustc_data_structures::fx::FxIndexMap;
-use rustc_hir::def_id::DefId;
+use rustc_hir::{self as hir, def_id::DefId};
+use rustc_infer::{
+  infer::{DefineOpaqueTypes, InferCtxt},
+  traits::ObligationCause,
+};
 use rustc_macros::TypeVisitable;
 use rustc_middle::ty::{self, Upcast};
+use rustc_span::DUMMY_SP;
 use rustc_utils::source_map::range::CharRange;
 use serde::Serialize;
 #[cfg(feature = "testing")]
@@ -80,6 +85,131 @@ pub struct ImplHeader<'tcx> {
   #[serde(with = "myty::Slice__TyDef")]
   #[cfg_attr(feature = "testing", ts(type = "Ty[]"))]
   pub tys_without_default_bounds: Vec<ty::Ty<'tcx>>,
+
+  /// The header as written, only for local impls.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[serde(with = "crate::hir::Option__ImplDef")]
+  #[cfg_attr(feature = "testing", ts(type = "HirImpl | undefined"))]
+  pub hir: Option<&'tcx hir::Impl<'tcx>>,
+}
+
+/// Subterms of a goal's trait reference that don't unify with those of an
+/// impl, e.g., `u8` and `u16` for the goal `Vec<u8>: Show` and `impl Show
+/// for Vec<u16>`.
+#[derive(Serialize)]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
+pub struct ArgDiff<'tcx> {
+  #[serde(with = "myty::TyDef")]
+  #[cfg_attr(feature = "testing", ts(type = "Ty"))]
+  pub goal: ty::Ty<'tcx>,
+
+  #[serde(rename = "impl")]
+  #[serde(with = "myty::TyDef")]
+  #[cfg_attr(feature = "testing", ts(type = "Ty", rename = "impl"))]
+  pub impl_ty: ty::Ty<'tcx>,
+}
+
+impl<'tcx> ArgDiff<'tcx> {
+  /// Where `goal` fails to unify with the trait reference of impl `def_id`,
+  /// outermost subterms first.
+  ///
+  /// The impl is instantiated with fresh variables, a generic parameter is
+  /// only reported once an earlier subterm bound it to another type, e.g.,
+  /// `u16` and `T` for the goal `(u8, u16): Show` and `impl<T> Show for (T,
+  /// T)`. Types with a different constructor aren't compared further, e.g.,
+  /// `Vec<u8>` and `[T]`. Nothing is left unified in `infcx`.
+  pub fn between(
+    infcx: &InferCtxt<'tcx>,
+    param_env: ty::ParamEnv<'tcx>,
+    goal: ty::PolyTraitRef<'tcx>,
+    def_id: DefId,
+  ) -> Vec<Self> {
+    let tcx = infcx.tcx;
+    let Some(impl_ref) = tcx.impl_trait_ref(def_id) else {
+      return vec![];
+    };
+    let fresh_args = infcx.fresh_args_for_item(DUMMY_SP, def_id);
+    let fresh_ref = impl_ref.instantiate(tcx, fresh_args);
+    let impl_ref = impl_ref.instantiate_identity();
+
+    infcx.probe(|_| {
+      infcx.enter_forall(goal, |goal| {
+        let mut diffs = vec![];
+        let subterms = goal
+          .args
+          .types()
+          .zip(fresh_ref.args.types())
+          .zip(impl_ref.args.types());
+        for ((goal, fresh), impl_ty) in subterms {
+          Self::compare(infcx, param_env, [goal, fresh, impl_ty], &mut diffs);
+        }
+        diffs
+      })
+    })
+  }
+
+  /// Compare `goal` with `fresh`, the impl type `impl_ty` instantiated with
+  /// fresh variables. Subterms that unify stay unified, later subterms see
+  /// the variables they bound.
+  fn compare(
+    infcx: &InferCtxt<'tcx>,
+    param_env: ty::ParamEnv<'tcx>,
+    [goal, fresh, impl_ty]: [ty::Ty<'tcx>; 3],
+    diffs: &mut Vec<Self>,
+  ) {
+    let unified = infcx.commit_if_ok(|_| {
+      infcx.at(&ObligationCause::dummy(), param_env).eq(
+        DefineOpaqueTypes::No,
+        goal,
+        fresh,
+      )
+    });
+    if unified.is_ok() {
+      return;
+    }
+
+    // `fresh` only differs from `impl_ty` where it has a variable, these
+    // aren't compared further.
+    match (Self::subterms(goal, fresh), Self::subterms(goal, impl_ty)) {
+      (Some(fresh_subterms), Some(impl_subterms)) => {
+        for ((goal, fresh), (_, impl_ty)) in
+          fresh_subterms.into_iter().zip(impl_subterms)
+        {
+          Self::compare(infcx, param_env, [goal, fresh, impl_ty], diffs);
+        }
+      }
+      _ => diffs.push(ArgDiff { goal, impl_ty }),
+    }
+  }
+
+  /// Pairs of the immediate subterms of `a` and `b`, if both have the same
+  /// constructor.
+  fn subterms(
+    a: ty::Ty<'tcx>,
+    b: ty::Ty<'tcx>,
+  ) -> Option<Vec<(ty::Ty<'tcx>, ty::Ty<'tcx>)>> {
+    use ty::TyKind as T;
+    let subterms = match (a.kind(), b.kind()) {
+      (T::Adt(d1, a1), T::Adt(d2, a2)) if d1 == d2 => {
+        a1.types().zip(a2.types()).collect()
+      }
+      (T::Ref(_, t1, m1), T::Ref(_, t2, m2))
+      | (T::RawPtr(t1, m1), T::RawPtr(t2, m2))
+        if m1 == m2 =>
+      {
+        vec![(*t1, *t2)]
+      }
+      (T::Slice(t1), T::Slice(t2)) | (T::Array(t1, _), T::Array(t2, _)) => {
+        vec![(*t1, *t2)]
+      }
+      (T::Tuple(ts1), T::Tuple(ts2)) if ts1.len() == ts2.len() => {
+        ts1.iter().zip(ts2.iter()).collect()
+      }
+      _ => return None,
+    };
+    Some(subterms)
+  }
 }
 
 #[derive(Debug, Clone, TypeVisitable, Serialize)]
@@ -157,7 +287,7 @@ pub struct ClauseWithBounds<'tcx> {
   pub bounds: Vec<ClauseBound<'tcx>>,
 }
 
-#[derive(Debug, Copy, Clone, TypeVisitable, Serialize)]
+#[derive(Debug, Clone, TypeVisitable, Serialize)]
 #[cfg_attr(feature = "testing", derive(TS))]
 #[cfg_attr(feature = "testing", ts(export))]
 pub enum ClauseBound<'tcx> {
@@ -181,6 +311,61 @@ pub enum ClauseBound<'tcx> {
     #[cfg_attr(feature = "testing", ts(type = "Region"))]
     ty::Region<'tcx>,
   ),
+  /// A trait bound with its associated types, e.g., `Iterator<Item = u32>`.
+  AssocTrait(
+    #[cfg_attr(feature = "testing", ts(type = "Trait"))] AssocBound<'tcx>,
+  ),
+}
+
+#[derive(Debug, Clone, TypeVisitable)]
+pub struct AssocBound<'tcx> {
+  polarity: myty::Polarity,
+  trait_ref: ty::TraitRef<'tcx>,
+  projections: Vec<ty::ProjectionPredicate<'tcx>>,
+}
+
+impl Serialize for AssocBound<'_> {
+  fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+  where
+    S: serde::Serializer,
+  {
+    use rustc_infer::infer::InferCtxt;
+
+    use super::DynCtxt;
+
+    InferCtxt::access(|infcx| {
+      let tcx = infcx.tcx;
+      let assoc_args = self
+        .projections
+        .iter()
+        .map(|p| {
+          let name = tcx.item_name(p.projection_term.def_id);
+          myty::AssocItemDef::new(name, p.term)
+        })
+        .collect();
+      myty::Trait::new(tcx, self.polarity, self.trait_ref, assoc_args)
+        .serialize(s)
+    })
+  }
+}
+
+/// Is `def_id` one of the `Fn*` or `AsyncFn*` traits?
+fn is_fn_like_trait(tcx: ty::TyCtxt, def_id: DefId) -> bool {
+  tcx.is_fn_trait(def_id)
+    || tcx.async_fn_trait_kind_from_def_id(def_id).is_some()
+}
+
+/// Is the projection the `Output` of `FnOnce` or `AsyncFnOnce`, i.e., the
+/// return type of an `Fn*` or `AsyncFn*` bound?
+fn is_fn_output(
+  tcx: ty::TyCtxt,
+  projection: ty::PolyProjectionPredicate,
+) -> bool {
+  let def_id = projection.projection_def_id();
+  let lang_items = tcx.lang_items();
+  Some(def_id) == lang_items.fn_once_output()
+    || (Some(tcx.parent(def_id)) == lang_items.async_fn_once_trait()
+      && tcx.item_name(def_id) == rustc_span::sym::Output)
 }
 
 pub(crate) fn group_predicates_by_ty<'tcx>(
@@ -191,12 +376,9 @@ pub(crate) fn group_predicates_by_ty<'tcx>(
   let mut grouped = FxIndexMap::<_, Vec<_>>::default();
   let mut other = vec![];
 
-  // TODO: this only looks at the output of an `FnOnce`, we probably also need
-  // to handle `AsyncFnOnce` and consider doing the same for the output of
-  // a `Future`. A further goal could be sugaring all associated type bounds
-  // back into the signature but that would require more work (not sure how much).
-  let fn_trait_output = tcx.lang_items().fn_once_output();
-  let mut fn_output_projections = vec![];
+  // Projections are folded back into the bound of their trait, e.g.,
+  // `F: Fn(A) -> R` or `I: Iterator<Item = X>`.
+  let mut projections = vec![];
 
   for p in predicates {
     if let Some(poly_trait_pred) = p.as_trait_clause() {
@@ -217,11 +399,8 @@ pub(crate) fn group_predicates_by_ty<'tcx>(
         .entry(ty)
         .or_default()
         .push(poly_ty_outl.rebind(bound));
-    } else if let Some(poly_projection) = p.as_projection_clause()
-      && let Some(output_defid) = fn_trait_output
-      && poly_projection.projection_def_id() == output_defid
-    {
-      fn_output_projections.push(poly_projection);
+    } else if let Some(poly_projection) = p.as_projection_clause() {
+      projections.push((p, poly_projection));
     } else {
       other.push(p);
     }
@@ -236,36 +415,55 @@ pub(crate) fn group_predicates_by_ty<'tcx>(
       let unbounds = bounds
         .into_iter()
         .map(|bclause| {
-          let clause = bclause.skip_binder();
-          if let ClauseBound::Trait(p, tref) = clause
-            && tcx.is_fn_trait(tref.def_id)
-            && let poly_tr = bclause.rebind(tref)
-            && let matching_projections = fn_output_projections
-              .extract_if(move |p| {
-                tcx.does_trait_ref_occur_in(
-                  poly_tr,
-                  p.map_bound(|p| {
-                    ty::PredicateKind::Clause(ty::ClauseKind::Projection(p))
-                  })
-                  .upcast(tcx),
-                )
+          let clause = bclause.clone().skip_binder();
+          let ClauseBound::Trait(p, tref) = clause else {
+            return clause;
+          };
+
+          // The output of an `Fn*` bound is a projection of its `FnOnce`
+          // supertrait, e.g., `<F as FnOnce<(A,)>>::Output == R`.
+          if is_fn_like_trait(tcx, tref.def_id) {
+            let poly_tr = bclause.rebind(tref);
+            let matching_projections = projections
+              .extract_if(|(_, p)| {
+                is_fn_output(tcx, *p)
+                  && tcx.does_trait_ref_occur_in(
+                    poly_tr,
+                    p.map_bound(|p| {
+                      ty::PredicateKind::Clause(ty::ClauseKind::Projection(p))
+                    })
+                    .upcast(tcx),
+                  )
               })
+              .map(|(_, p)| p)
               .unique()
-              .collect::<smallvec::SmallVec<[_; 2]>>()
-            && !matching_projections.is_empty()
-          {
+              .collect::<smallvec::SmallVec<[_; 2]>>();
+            let Some(output) = matching_projections.first() else {
+              return clause;
+            };
             log::debug!(
               "Matching projections for {bclause:?} {matching_projections:#?}"
             );
-            let ret_ty = matching_projections[0]
-              .term()
-              .skip_binder()
-              .ty()
-              .expect("FnOnce::Output Ty");
             debug_assert!(matching_projections.len() == 1);
-            ClauseBound::FnTrait(p, tref, ret_ty)
-          } else {
+            let ret_ty = output.term().skip_binder().ty().expect("Output Ty");
+            return ClauseBound::FnTrait(p, tref, ret_ty);
+          }
+
+          // Other associated types are constrained on the trait itself.
+          let assoc = projections
+            .extract_if(|(_, proj)| {
+              proj.skip_binder().projection_term.trait_ref(tcx) == tref
+            })
+            .map(|(_, proj)| proj.skip_binder())
+            .collect::<Vec<_>>();
+          if assoc.is_empty() {
             clause
+          } else {
+            ClauseBound::AssocTrait(AssocBound {
+              polarity: p,
+              trait_ref: tref,
+              projections: assoc,
+            })
           }
         })
         .collect();
@@ -279,10 +477,8 @@ pub(crate) fn group_predicates_by_ty<'tcx>(
     })
     .collect::<Vec<_>>();
 
-  assert!(
-    fn_output_projections.is_empty(),
-    "Remaining output projections {fn_output_projections:#?}"
-  );
+  // Projections without a matching bound, e.g., those of a supertrait.
+  other.extend(projections.into_iter().map(|(p, _)| p));
 
   GroupedClauses { grouped, other }
 }
@@ -341,6 +537,13 @@ pub fn get_opt_impl_header(
   // XXX: This function is not in the dynamic context, we have to
   // pass the `TyCtxt` explicitly.
   let l = DefLocation::from_def_id_tcx(impl_def_id, tcx);
+  let hir = match tcx.hir().get_if_local(impl_def_id) {
+    Some(hir::Node::Item(hir::Item {
+      kind: hir::ItemKind::Impl(impl_),
+      ..
+    })) => Some(*impl_),
+    _ => None,
+  };
 
   Some(ImplHeader {
     l,
@@ -349,5 +552,6 @@ pub fn get_opt_impl_header(
     self_ty,
     predicates: grouped_clauses,
     tys_without_default_bounds,
+    hir,
   })
 }
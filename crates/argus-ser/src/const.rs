@@ -4,7 +4,7 @@ use rustc_apfloat::{
 };
 use rustc_hir::def::DefKind;
 use rustc_middle::ty::*;
-use rustc_span::Symbol;
+use rustc_span::{Symbol, DUMMY_SP};
 use rustc_target::abi::Size;
 use serde::Serialize;
 #[cfg(feature = "testing")]
@@ -83,7 +83,10 @@ impl<'a, 'tcx: 'a> From<&Const<'tcx>> for ConstKindDef<'tcx> {
     let kind = value.kind();
 
     match kind {
-      ConstKind::Unevaluated(uc) => Self::Unevaluated { data: uc },
+      ConstKind::Unevaluated(uc) => match evaluate_anon_const(uc) {
+        Some(data) => Self::Value { data },
+        None => Self::Unevaluated { data: uc },
+      },
       ConstKind::Param(v) => Self::Param { data: v },
       ConstKind::Value(v) => Self::Value {
         data: ValTreeDef::new(v, self_ty),
@@ -100,6 +103,32 @@ impl<'a, 'tcx: 'a> From<&Const<'tcx>> for ConstKindDef<'tcx> {
   }
 }
 
+/// Value of an anonymous const defined in another crate, e.g., the length
+/// in `impl<T> Default for [T; 0]`. Local ones are printed as written.
+///
+/// Without `generic_const_exprs` anonymous consts can't use the generics of
+/// their parent, so they're evaluated for their identity arguments. Those
+/// that do use them fail to evaluate and aren't printed as a value.
+fn evaluate_anon_const(uc: UnevaluatedConst) -> Option<ValTreeDef> {
+  InferCtxt::access(|infcx| {
+    let tcx = infcx.tcx;
+    if uc.def.is_local() || tcx.def_kind(uc.def) != DefKind::AnonConst {
+      return None;
+    }
+
+    let args = GenericArgs::identity_for_item(tcx, uc.def);
+    let tree = tcx
+      .const_eval_resolve_for_typeck(
+        tcx.param_env(uc.def),
+        UnevaluatedConst::new(uc.def, args),
+        DUMMY_SP,
+      )
+      .ok()??;
+    let ty = tcx.type_of(uc.def).instantiate(tcx, uc.args);
+    Some(ValTreeDef::new(tree, ty))
+  })
+}
+
 #[derive(Serialize)]
 #[cfg_attr(feature = "testing", derive(TS))]
 #[cfg_attr(feature = "testing", ts(export, rename = "InferConst"))]
@@ -186,6 +215,8 @@ impl<'tcx> UnevaluatedConstDef<'tcx> {
           {
             Self::AnonSnippet { data: snip }
           } else {
+            // Only reached when evaluating the const failed, see `evaluate_anon_const`.
+            //
             // Do not call `print_value_path` as if a parent of this anon const is an impl it will
             // attempt to print out the impl trait ref i.e. `<T as Trait>::{constant#0}`. This would
             // cause printing to enter an infinite recursion if the anon const is in the self type i.e.
@@ -1,7 +1,7 @@
 use rustc_infer::infer::InferCtxt;
 
 // These types are safe for dependents to use.
-use crate::interner::TyInterner;
+use crate::interner::{SharedInterner, TyInterner};
 
 pub trait DynCtxt<'tcx>
 where
@@ -41,6 +41,21 @@ where
       f(vdyn)
     })
   }
+
+  /// Like [`DynCtxt::access`], for contexts that may not be set.
+  fn try_access<T>(
+    f: impl for<'a> FnOnce(Option<&'a Self::Dynamic>) -> T,
+  ) -> T {
+    let cell = Self::tls();
+    cell.get(|v_opt| {
+      let vdyn = v_opt.map(|v| {
+        let v: &'static Self::Static = v;
+        let vdyn: &Self::Dynamic = unsafe { std::mem::transmute(v) };
+        vdyn
+      });
+      f(vdyn)
+    })
+  }
 }
 
 // NOTE: setting the dynamic TCX should *only* happen
@@ -48,6 +63,7 @@ where
 // that the 'tcx lifetime is the same as that of the serialized item.
 fluid_let::fluid_let! {static INFCX: &'static InferCtxt<'static>}
 fluid_let::fluid_let! {static TY_BUF: &'static TyInterner<'static>}
+fluid_let::fluid_let! {static SHARED: &'static SharedInterner}
 
 impl<'tcx> DynCtxt<'tcx> for InferCtxt<'tcx> {
   type Static = InferCtxt<'static>;
@@ -66,3 +82,12 @@ impl<'tcx> DynCtxt<'tcx> for TyInterner<'tcx> {
     &TY_BUF
   }
 }
+
+impl<'tcx> DynCtxt<'tcx> for SharedInterner {
+  type Static = SharedInterner;
+  type Dynamic = SharedInterner;
+
+  fn tls() -> &'static fluid_let::DynamicVariable<&'static Self::Static> {
+    &SHARED
+  }
+}
@@ -0,0 +1,128 @@
+//! Values serialized ahead of time.
+//!
+//! Rustc types can only be serialized while their `InferCtxt` is alive, so
+//! results hold them in serialized form. Instead of a `serde_json::Value`
+//! tree these are kept as compact MessagePack bytes, when the result is
+//! written they are transcoded straight into the output serializer. The
+//! output format is thus independent of the encoding used here, and the
+//! schema is the same as that of the TS bindings.
+use std::fmt;
+
+use serde::{Serialize, Serializer};
+
+pub type Error = rmp_serde::encode::Error;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Encoded(Box<[u8]>);
+
+impl Encoded {
+  /// Encode `value`, this must happen within the dynamic context of the
+  /// `argus-ser` crate for rustc types, see [`crate::to_value`].
+  pub fn new<T: Serialize + ?Sized>(value: &T) -> Result<Self, Error> {
+    let mut bytes = Vec::new();
+    // Struct fields are encoded by name, they must be transcoded
+    // into a JSON object with the same keys.
+    let mut serializer = rmp_serde::Serializer::new(&mut bytes)
+      .with_struct_map()
+      .with_human_readable();
+    value.serialize(&mut serializer)?;
+    Ok(Encoded(bytes.into_boxed_slice()))
+  }
+
+  /// Size of the encoded value in bytes.
+  pub fn len(&self) -> usize {
+    self.0.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+}
+
+impl Serialize for Encoded {
+  fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+    let mut deserializer =
+      rmp_serde::Deserializer::from_read_ref(&self.0).with_human_readable();
+    serde_transcode::transcode(&mut deserializer, s)
+  }
+}
+
+impl fmt::Display for Encoded {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let json = serde_json::to_string(self).map_err(|_| fmt::Error)?;
+    f.write_str(&json)
+  }
+}
+
+impl fmt::Debug for Encoded {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "Encoded({self})")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde::Serialize;
+
+  use super::Encoded;
+
+  #[derive(Serialize)]
+  #[serde(rename_all = "camelCase")]
+  struct Goal {
+    predicate: Predicate,
+    bound_vars: Vec<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+    parent: Option<usize>,
+  }
+
+  #[derive(Serialize)]
+  enum Predicate {
+    Trait {
+      self_ty: usize,
+      path: Vec<&'static str>,
+    },
+    WellFormed(i64),
+    Ambiguous,
+  }
+
+  #[derive(Serialize)]
+  #[serde(tag = "type")]
+  enum Segment {
+    Colons,
+    Name { name: &'static str },
+  }
+
+  #[test]
+  fn transcodes_to_the_same_json() {
+    let goals = vec![
+      Goal {
+        predicate: Predicate::Trait {
+          self_ty: 0,
+          path: vec!["core", "marker", "Send"],
+        },
+        bound_vars: vec![],
+        note: None,
+        parent: None,
+      },
+      Goal {
+        predicate: Predicate::WellFormed(-1),
+        bound_vars: vec![1, 2],
+        note: Some("from a `where` clause".into()),
+        parent: Some(0),
+      },
+      Goal {
+        predicate: Predicate::Ambiguous,
+        bound_vars: vec![],
+        note: None,
+        parent: Some(1),
+      },
+    ];
+    let segments = [Segment::Colons, Segment::Name { name: "Send" }];
+
+    let direct = serde_json::to_string(&(&goals, &segments)).unwrap();
+    let encoded = Encoded::new(&(&goals, &segments)).unwrap();
+    assert_eq!(serde_json::to_string(&encoded).unwrap(), direct);
+    assert!(encoded.len() < direct.len());
+  }
+}
@@ -1,4 +1,9 @@
-//! Serializing for HIR items, this is used for serializing impl blocks.
+//! Serializing for HIR items, this is used for serializing impl blocks
+//! and the user-written types and bounds that obligations point to.
+//!
+//! Unlike their `rustc_middle::ty` counterparts these are serialized as
+//! written, type aliases aren't expanded and elided lifetimes stay elided.
+//! The TS definitions are hand-written, see `types.ts`.
 
 use rustc_ast::ast::TraitObjectSyntax;
 use rustc_hir::{
@@ -12,7 +17,34 @@ use rustc_span::{
 };
 use serde::{Serialize, Serializer};
 
-use super::{serialize_custom_seq, ty::SymbolDef, NOOP};
+use super::{serialize_custom_seq, ty::SymbolDef};
+
+/// A HIR node written by the user, one that obligations can point to.
+#[derive(Serialize)]
+pub enum NodeDef<'hir> {
+  Ty(#[serde(with = "TyDef")] &'hir Ty<'hir>),
+  TraitRef(#[serde(with = "TraitRefDef")] &'hir TraitRef<'hir>),
+  TypeBinding(#[serde(with = "TypeBindingDef")] &'hir TypeBinding<'hir>),
+  WhereBoundPredicate(
+    #[serde(with = "WhereBoundPredicateDef")] &'hir WhereBoundPredicate<'hir>,
+  ),
+  GenericParam(#[serde(with = "GenericParamDef")] &'hir GenericParam<'hir>),
+}
+
+impl<'hir> NodeDef<'hir> {
+  pub fn new(node: Node<'hir>) -> Option<Self> {
+    Some(match node {
+      Node::Ty(ty) | Node::Field(&FieldDef { ty, .. }) => NodeDef::Ty(ty),
+      Node::TraitRef(trait_ref) => NodeDef::TraitRef(trait_ref),
+      Node::TypeBinding(binding) => NodeDef::TypeBinding(binding),
+      Node::WhereBoundPredicate(predicate) => {
+        NodeDef::WhereBoundPredicate(predicate)
+      }
+      Node::GenericParam(param) => NodeDef::GenericParam(param),
+      _ => return None,
+    })
+  }
+}
 
 #[derive(Serialize)]
 #[serde(remote = "Ident")]
@@ -46,10 +78,7 @@ pub struct PathSegmentDef<'hir> {
 
 pub struct Slice__PathSegmentDef;
 impl Slice__PathSegmentDef {
-  fn serialize<'hir, S>(
-    value: &[PathSegment<'hir>],
-    s: S,
-  ) -> Result<S::Ok, S::Error>
+  fn serialize<S>(value: &[PathSegment<'_>], s: S) -> Result<S::Ok, S::Error>
   where
     S: Serializer,
   {
@@ -61,15 +90,15 @@ impl Slice__PathSegmentDef {
 
 pub struct Option__GenericArgsDef;
 impl Option__GenericArgsDef {
-  fn serialize<'hir, S>(
-    value: &Option<&'hir GenericArgs<'hir>>,
+  fn serialize<S>(
+    value: &Option<&GenericArgs<'_>>,
     s: S,
   ) -> Result<S::Ok, S::Error>
   where
     S: Serializer,
   {
     match value {
-      None => NOOP.serialize(s),
+      None => s.serialize_none(),
       Some(ga) => GenericArgsDef::serialize(ga, s),
     }
   }
@@ -93,10 +122,7 @@ pub struct GenericArgsDef<'hir> {
 
 pub struct Slice__GenericArgDef;
 impl Slice__GenericArgDef {
-  fn serialize<'hir, S>(
-    value: &[GenericArg<'hir>],
-    s: S,
-  ) -> Result<S::Ok, S::Error>
+  fn serialize<S>(value: &[GenericArg<'_>], s: S) -> Result<S::Ok, S::Error>
   where
     S: Serializer,
   {
@@ -119,7 +145,7 @@ pub enum GenericArgsParenthesesDef {
 pub enum GenericArgDef<'hir> {
   Lifetime(#[serde(with = "LifetimeDef")] &'hir Lifetime),
   Type(#[serde(with = "TyDef")] &'hir Ty<'hir>),
-  Const(#[serde(with = "ConstArgDef")] ConstArg),
+  Const(#[serde(with = "ConstArgDef")] ConstArg<'hir>),
   Infer(#[serde(with = "InferArgDef")] InferArg),
 }
 
@@ -138,12 +164,9 @@ pub struct LifetimeDef {
 
 #[derive(Serialize)]
 #[serde(remote = "ConstArg")]
-pub struct ConstArgDef {
+pub struct ConstArgDef<'hir> {
   #[serde(with = "AnonConstDef")]
-  pub value: AnonConst,
-
-  #[serde(skip)]
-  pub span: Span,
+  pub value: &'hir AnonConst,
 
   #[serde(skip)]
   pub is_desugared_from_effects: bool,
@@ -174,7 +197,7 @@ pub struct TyDef<'hir> {
 
 pub struct Slice__TyDef;
 impl Slice__TyDef {
-  fn serialize<'hir, S>(value: &[Ty<'hir>], s: S) -> Result<S::Ok, S::Error>
+  fn serialize<S>(value: &[Ty<'_>], s: S) -> Result<S::Ok, S::Error>
   where
     S: Serializer,
   {
@@ -186,15 +209,12 @@ impl Slice__TyDef {
 
 pub struct Option__TyDef;
 impl Option__TyDef {
-  fn serialize<'hir, S>(
-    value: &Option<&Ty<'hir>>,
-    s: S,
-  ) -> Result<S::Ok, S::Error>
+  fn serialize<S>(value: &Option<&Ty<'_>>, s: S) -> Result<S::Ok, S::Error>
   where
     S: Serializer,
   {
     match value {
-      None => NOOP.serialize(s),
+      None => s.serialize_none(),
       Some(ga) => TyDef::serialize(ga, s),
     }
   }
@@ -226,7 +246,7 @@ pub enum TyKindDef<'hir> {
 
   Array(
     #[serde(with = "TyDef")] &'hir Ty<'hir>,
-    #[serde(skip)] ArrayLen,
+    #[serde(skip)] ArrayLen<'hir>,
   ),
 
   Ptr(#[serde(with = "MutTyDef")] MutTy<'hir>),
@@ -242,6 +262,8 @@ pub enum TyKindDef<'hir> {
 
   Tup(#[serde(with = "Slice__TyDef")] &'hir [Ty<'hir>]),
 
+  AnonAdt(#[serde(skip)] ItemId),
+
   Path(#[serde(with = "QPathDef")] QPath<'hir>),
 
   OpaqueDef(
@@ -257,11 +279,16 @@ pub enum TyKindDef<'hir> {
   ),
 
   // NOTE: after reading the documentation I'm still not sure what this is.
-  Typeof(#[serde(skip)] AnonConst),
+  Typeof(#[serde(skip)] &'hir AnonConst),
 
   Infer,
 
   Err(#[serde(skip)] ErrorGuaranteed),
+
+  Pat(
+    #[serde(with = "TyDef")] &'hir Ty<'hir>,
+    #[serde(skip)] &'hir Pat<'hir>,
+  ),
 }
 
 #[derive(Serialize)]
@@ -290,15 +317,12 @@ pub struct TraitRefDef<'hir> {
 
 pub struct Option__TraitRefDef;
 impl Option__TraitRefDef {
-  fn serialize<'hir, S>(
-    value: &Option<TraitRef<'hir>>,
-    s: S,
-  ) -> Result<S::Ok, S::Error>
+  fn serialize<S>(value: &Option<TraitRef<'_>>, s: S) -> Result<S::Ok, S::Error>
   where
     S: Serializer,
   {
     match value {
-      None => NOOP.serialize(s),
+      None => s.serialize_none(),
       Some(ga) => TraitRefDef::serialize(ga, s),
     }
   }
@@ -338,10 +362,7 @@ pub struct TypeBindingDef<'hir> {
 
 pub struct Slice__TypeBindingDef;
 impl Slice__TypeBindingDef {
-  fn serialize<'hir, S>(
-    value: &[TypeBinding<'hir>],
-    s: S,
-  ) -> Result<S::Ok, S::Error>
+  fn serialize<S>(value: &[TypeBinding<'_>], s: S) -> Result<S::Ok, S::Error>
   where
     S: Serializer,
   {
@@ -375,7 +396,7 @@ pub enum TermDef<'hir> {
 #[serde(remote = "Impl")]
 pub struct ImplDef<'hir> {
   #[serde(skip)]
-  pub unsafety: Unsafety,
+  pub safety: Safety,
 
   #[serde(with = "ImplPolarityDef")]
   pub polarity: ImplPolarity,
@@ -402,15 +423,15 @@ pub struct ImplDef<'hir> {
 
 pub struct Option__ImplDef;
 impl Option__ImplDef {
-  pub fn serialize<'hir, S>(
-    value: &Option<&'hir Impl<'hir>>,
+  pub fn serialize<S>(
+    value: &Option<&Impl<'_>>,
     s: S,
   ) -> Result<S::Ok, S::Error>
   where
     S: Serializer,
   {
     match value {
-      None => NOOP.serialize(s),
+      None => s.serialize_none(),
       Some(ga) => ImplDef::serialize(ga, s),
     }
   }
@@ -444,10 +465,7 @@ pub struct GenericsDef<'hir> {
 
 pub struct Slice__GenericParamDef;
 impl Slice__GenericParamDef {
-  fn serialize<'hir, S>(
-    value: &[GenericParam<'hir>],
-    s: S,
-  ) -> Result<S::Ok, S::Error>
+  fn serialize<S>(value: &[GenericParam<'_>], s: S) -> Result<S::Ok, S::Error>
   where
     S: Serializer,
   {
@@ -514,7 +532,7 @@ pub enum GenericParamKindDef<'hir> {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(with = "Option__AnonConstDef")]
-    default: Option<AnonConst>,
+    default: Option<&'hir AnonConst>,
 
     #[serde(skip)]
     is_host_effect: bool,
@@ -523,10 +541,7 @@ pub enum GenericParamKindDef<'hir> {
 
 pub struct Slice__WherePredicateDef;
 impl Slice__WherePredicateDef {
-  fn serialize<'hir, S>(
-    value: &[WherePredicate<'hir>],
-    s: S,
-  ) -> Result<S::Ok, S::Error>
+  fn serialize<S>(value: &[WherePredicate<'_>], s: S) -> Result<S::Ok, S::Error>
   where
     S: Serializer,
   {
@@ -614,10 +629,7 @@ pub enum GenericBoundDef<'hir> {
 pub type GenericBoundsDef = Slice__GenericBoundDef;
 pub struct Slice__GenericBoundDef;
 impl Slice__GenericBoundDef {
-  fn serialize<'hir, S>(
-    value: &[GenericBound<'hir>],
-    s: S,
-  ) -> Result<S::Ok, S::Error>
+  fn serialize<S>(value: &[GenericBound<'_>], s: S) -> Result<S::Ok, S::Error>
   where
     S: Serializer,
   {
@@ -639,10 +651,7 @@ pub enum TraitBoundModifierDef {
 
 pub struct Slice__PolyTraitRefDef;
 impl Slice__PolyTraitRefDef {
-  fn serialize<'hir, S>(
-    value: &[PolyTraitRef<'hir>],
-    s: S,
-  ) -> Result<S::Ok, S::Error>
+  fn serialize<S>(value: &[PolyTraitRef<'_>], s: S) -> Result<S::Ok, S::Error>
   where
     S: Serializer,
   {
@@ -676,19 +685,19 @@ pub struct AnonConstDef {
 
   #[serde(skip)]
   pub body: BodyId,
+
+  #[serde(skip)]
+  pub span: Span,
 }
 
 pub struct Option__AnonConstDef;
 impl Option__AnonConstDef {
-  fn serialize<'hir, S>(
-    value: &Option<AnonConst>,
-    s: S,
-  ) -> Result<S::Ok, S::Error>
+  fn serialize<S>(value: &Option<&AnonConst>, s: S) -> Result<S::Ok, S::Error>
   where
     S: Serializer,
   {
     match value {
-      None => NOOP.serialize(s),
+      None => s.serialize_none(),
       Some(ga) => AnonConstDef::serialize(ga, s),
     }
   }
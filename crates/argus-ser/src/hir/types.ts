@@ -1,153 +1,179 @@
 // NOTE: this file needs to stay up to date with
 // the serializeid hir types, but don't remove me!
-export type Symbol = string;
+//
+// Names are prefixed with `Hir` to not clash with the `ty` bindings.
 
-export type Impl = {
-  polarity: ImplPolarity;
-  generics: Generics;
-  of_trait: TraitRef | undefined;
-  self_ty: Ty;
+export type HirNode =
+  | { Ty: HirTy }
+  | { TraitRef: HirTraitRef }
+  | { TypeBinding: HirTypeBinding }
+  | { WhereBoundPredicate: HirWhereBoundPredicate }
+  | { GenericParam: HirGenericParam };
+
+export type HirImpl = {
+  polarity: HirImplPolarity;
+  generics: HirGenerics;
+  of_trait?: HirTraitRef;
+  self_ty: HirTy;
 };
 
-export type ImplPolarity = "Positive" | "Negative";
+export type HirImplPolarity = "Positive" | "Negative";
 
-export type Generics = {
-  params: GenericParam[];
-  predicates: WherePredicate[];
+export type HirGenerics = {
+  params: HirGenericParam[];
+  predicates: HirWherePredicate[];
 };
 
-export type TraitRef = {
-  path: Path;
+export type HirTraitRef = {
+  path: HirPath;
 };
 
-export type PolyTraitRef = {
-  bound_generic_params: GenericParam[];
-  trait_ref: TraitRef;
+export type HirPolyTraitRef = {
+  bound_generic_params: HirGenericParam[];
+  trait_ref: HirTraitRef;
 };
 
-export type Ty = {
-  kind: TyKind;
+export type HirTy = {
+  kind: HirTyKind;
 };
 
-export type TyKind =
-  | { InferDelegation: {} }
-  | { Slice: Ty }
-  | { Array: Ty }
-  | { Ptr: MutTy }
-  | { Ref: [Lifetime, MutTy] }
-  | { BareFn: {} }
+export type HirTyKind =
+  | { InferDelegation: [] }
+  | { Slice: HirTy }
+  | { Array: [HirTy] }
+  | { Ptr: HirMutTy }
+  | { Ref: [HirLifetime, HirMutTy] }
+  | "BareFn"
   | "Never"
-  | { Tup: Ty[] }
-  | { Path: QPath }
-  | { OpaqueDef: {} }
-  | { TraitObject: [PolyTraitRef[], Lifetime] }
-  | { Typeof: {} }
+  | { Tup: HirTy[] }
+  | "AnonAdt"
+  | { Path: HirQPath }
+  | { OpaqueDef: [] }
+  | { TraitObject: [HirPolyTraitRef[], HirLifetime] }
+  | "Typeof"
   | "Infer"
-  | "Err";
+  | "Err"
+  | { Pat: [HirTy] };
 
-export type MutTy = {
-  ty: Ty;
-  mutbl: Mutability;
+export type HirMutTy = {
+  ty: HirTy;
+  mutbl: HirMutability;
 };
 
-export type Mutability = "Mut" | "Not";
+export type HirMutability = "Mut" | "Not";
 
-export type QPath =
-  | { Resolved: [Ty | null, Path] }
-  | { TypeRelative: [Ty, PathSegment] }
-  | "LangItem";
+export type HirQPath =
+  | { Resolved: [HirTy | null, HirPath] }
+  | { TypeRelative: [HirTy, HirPathSegment] }
+  | { LangItem: [] };
 
-export type GenericParam = {
-  name: ParamName;
-  kind: GenericParamKind;
+export type HirGenericParam = {
+  name: HirParamName;
+  kind: HirGenericParamKind;
 };
 
-export type GenericParamKind =
-  | { Lifetime: Lifetime }
-  | { Type: { default: Ty | undefined } }
-  | { Const: { ty: Ty; default: AnonConst | undefined } };
+export type HirGenericParamKind =
+  | { Lifetime: {} }
+  | { Type: { default?: HirTy } }
+  | { Const: { ty: HirTy; default?: HirAnonConst } };
 
-export type ParamName = { Plain: Ident } | "Fresh" | "Error";
+export type HirParamName = { Plain: HirIdent } | "Fresh" | "Error";
 
-export type WherePredicate =
-  | { BoundPredicate: WhereBoundPredicate }
-  | { RegionPredicate: WhereRegionPredicate }
-  | { EqPredicate: WhereEqPredicate };
+export type HirWherePredicate =
+  | { BoundPredicate: HirWhereBoundPredicate }
+  | { RegionPredicate: HirWhereRegionPredicate }
+  | { EqPredicate: HirWhereEqPredicate };
 
-export type WhereBoundPredicate = {
-  bound_generic_params: GenericParam[];
-  bounded_ty: Ty;
-  bounds: GenericBound[];
+export type HirWhereBoundPredicate = {
+  bound_generic_params: HirGenericParam[];
+  bounded_ty: HirTy;
+  bounds: HirGenericBound[];
 };
 
-export type WhereRegionPredicate = {
-  lifetime: Lifetime;
-  bounds: GenericBound[];
+export type HirWhereRegionPredicate = {
+  lifetime: HirLifetime;
+  bounds: HirGenericBound[];
 };
 
-export type WhereEqPredicate = {
-  lhs_ty: Ty;
-  rhs_ty: Ty;
+export type HirWhereEqPredicate = {
+  lhs_ty: HirTy;
+  rhs_ty: HirTy;
 };
 
-export type Lifetime = {
-  ident: Ident;
+// An elided lifetime has an empty name.
+export type HirLifetime = {
+  ident: HirIdent;
 };
 
-export type Ident = {
+export type HirIdent = {
   name: Symbol;
 };
 
-export type Path = {
-  segments: PathSegment[];
+export type HirPath = {
+  segments: HirPathSegment[];
 };
 
-export type PathSegment = {
-  ident: Ident;
-  args: GenericArgs | undefined;
+export type HirPathSegment = {
+  ident: HirIdent;
+  res: HirRes;
+  args?: HirGenericArgs;
 };
 
-export type GenericArgs = {
-  args: GenericArg[];
-  bindings: TypeBinding[];
-  parenthesized: GenericArgsParentheses;
+export type HirRes =
+  | { Def: [] }
+  | "PrimTy"
+  | { SelfTyParam: {} }
+  | { SelfTyAlias: {} }
+  | "SelfCtor"
+  | "Local"
+  | "ToolMod"
+  | "NonMacroAttr"
+  | "Err";
+
+export type HirGenericArgs = {
+  args: HirGenericArg[];
+  bindings: HirTypeBinding[];
+  parenthesized: HirGenericArgsParentheses;
 };
 
-export type GenericArg =
-  | { Lifetime: Lifetime }
-  | { Type: Ty }
-  | { Const: ConstArg }
-  | { Infer: InferArg };
+export type HirGenericArg =
+  | { Lifetime: HirLifetime }
+  | { Type: HirTy }
+  | { Const: HirConstArg }
+  | { Infer: HirInferArg };
 
-export type AnonConst = {};
+export type HirAnonConst = {};
 
-export type ConstArg = {
-  value: AnonConst;
+export type HirConstArg = {
+  value: HirAnonConst;
 };
 
-export type InferArg = {};
+export type HirInferArg = {};
 
-export type GenericArgsParentheses = "No" | "ReturnTypeNotation" | "ParenSugar";
+export type HirGenericArgsParentheses =
+  | "No"
+  | "ReturnTypeNotation"
+  | "ParenSugar";
 
-export type TypeBinding = {
-  ident: Ident;
-  gen_args: GenericArgs;
-  kind: TypeBindingKind;
+export type HirTypeBinding = {
+  ident: HirIdent;
+  gen_args: HirGenericArgs;
+  kind: HirTypeBindingKind;
 };
 
-export type TypeBindingKind =
-  | { Constraint: { bounds: GenericBound[] } }
-  | { Equality: { term: Term } };
+export type HirTypeBindingKind =
+  | { Constraint: { bounds: HirGenericBound[] } }
+  | { Equality: { term: HirTerm } };
 
-export type GenericBound =
-  | { Trait: [PolyTraitRef, TraitBoundModifier] }
-  | { Outlives: Lifetime };
+export type HirGenericBound =
+  | { Trait: [HirPolyTraitRef, HirTraitBoundModifier] }
+  | { Outlives: HirLifetime };
 
-export type TraitBoundModifier =
+export type HirTraitBoundModifier =
   | "None"
   | "Negative"
   | "Maybe"
   | "MaybeConst"
   | "Const";
 
-export type Term = { Ty: Ty } | { Const: AnonConst };
+export type HirTerm = { Ty: HirTy } | { Const: HirAnonConst };
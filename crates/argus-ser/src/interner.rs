@@ -2,20 +2,42 @@ use std::collections::HashMap; // FIXME: change back to above
 use std::{
   cell::RefCell,
   cmp::{Eq, PartialEq},
+  fmt,
   hash::Hash,
+  mem::size_of,
 };
 
 use index_vec::{Idx, IndexVec};
 // use rustc_data_structures::fx::FxHashMap as HashMap;
+use rustc_hir::def_id::DefId;
 use rustc_middle::ty;
 
+use crate::Encoded;
+
 crate::define_idx! {
   usize,
-  TyIdx
+  TyIdx,
+  PathIdx,
+  ImplHeaderIdx
+}
+
+pub type TyInterner<'tcx> = RefCell<Interner<ty::Ty<'tcx>, TyIdx, TyEntry>>;
+
+/// An interned type and the subterms replaced by numbered placeholders in its
+/// abbreviated form, the placeholder `$n` stands for `placeholders[n - 1]`.
+///
+/// Subterms are abbreviated when nested deeper, or among more compound
+/// siblings, than the limits of [`crate::SerializeOptions`]. Types without
+/// subterms of their own, e.g., `u32` or `T`, are never abbreviated.
+pub struct TyEntry {
+  pub value: Encoded,
+  pub placeholders: Vec<TyIdx>,
 }
 
-pub type TyInterner<'tcx> =
-  RefCell<Interner<ty::Ty<'tcx>, TyIdx, serde_json::Value>>;
+/// Placeholders of the abbreviated types of a table, see [`TyEntry`].
+pub type TyAbbreviations = HashMap<TyIdx, Vec<TyIdx>>;
+
+pub type SharedInterner = RefCell<SharedTables>;
 
 pub struct Interner<K: PartialEq + Eq + Hash, I: Idx, D> {
   values: IndexVec<I, D>,
@@ -27,14 +49,40 @@ where
   K: PartialEq + Eq + Hash,
   I: Idx,
 {
+  // Interners grow on demand, most bodies only intern a few hundred values.
   fn default() -> Self {
     Self {
-      values: IndexVec::with_capacity(1_000_000),
-      keys: HashMap::with_capacity(1_000_000),
+      values: IndexVec::new(),
+      keys: HashMap::new(),
     }
   }
 }
 
+/// Number of entries an interner keeps room for after a [`Interner::reset`].
+const RETAINED_CAPACITY: usize = 1024;
+
+/// Size of an interner, reported in profiling output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InternerStats {
+  pub values: usize,
+  pub keys: usize,
+  /// Bytes reserved by the value vector and key map, an approximation that
+  /// ignores heap data owned by the entries themselves.
+  pub reserved_bytes: usize,
+}
+
+impl fmt::Display for InternerStats {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{} values, {} keys, {:.1} KiB reserved",
+      self.values,
+      self.keys,
+      self.reserved_bytes as f64 / 1024.0
+    )
+  }
+}
+
 impl<K, I, D> Interner<K, I, D>
 where
   K: PartialEq + Eq + Hash,
@@ -61,4 +109,127 @@ where
   pub fn consume(self) -> IndexVec<I, D> {
     self.values
   }
+
+  pub fn len(&self) -> usize {
+    self.values.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.values.is_empty()
+  }
+
+  pub fn stats(&self) -> InternerStats {
+    InternerStats {
+      values: self.values.len(),
+      keys: self.keys.len(),
+      reserved_bytes: self.values.raw.capacity() * size_of::<D>()
+        + self.keys.capacity() * size_of::<(K, I)>(),
+    }
+  }
+
+  /// Drop all entries, keeping a small allocation around so the next body
+  /// doesn't start from scratch nor hold on to the memory of a large one.
+  pub fn reset(&mut self) {
+    self.values.raw.clear();
+    self.values.raw.shrink_to(RETAINED_CAPACITY);
+    self.keys.clear();
+    self.keys.shrink_to(RETAINED_CAPACITY);
+  }
+
+  /// Forget the keys not matching `f`, their values are kept.
+  pub fn retain_keys(&mut self, mut f: impl FnMut(&K) -> bool) {
+    self.keys.retain(|k, _| f(k));
+  }
+}
+
+impl<K> Interner<K, TyIdx, TyEntry>
+where
+  K: PartialEq + Eq + Hash,
+{
+  /// The interned types, and the placeholders of those abbreviated.
+  pub fn consume_tys(self) -> (IndexVec<TyIdx, Encoded>, TyAbbreviations) {
+    let mut abbreviations = TyAbbreviations::new();
+    let tys = self
+      .values
+      .into_iter_enumerated()
+      .map(|(idx, entry)| {
+        if !entry.placeholders.is_empty() {
+          abbreviations.insert(idx, entry.placeholders);
+        }
+        entry.value
+      })
+      .collect();
+    (tys, abbreviations)
+  }
+}
+
+/// Paths and impl headers written once and referred to by index, e.g., by
+/// every body of a bundle, see [`crate::with_shared_tables`].
+#[derive(Default)]
+pub struct SharedTables {
+  // Paths are keyed by their encoding, the same path is built anew for
+  // every type or predicate that mentions it.
+  paths: Interner<Encoded, PathIdx, Encoded>,
+  impl_headers: Interner<DefId, ImplHeaderIdx, Encoded>,
+}
+
+impl SharedTables {
+  pub(crate) fn intern_path(&mut self, path: Encoded) -> PathIdx {
+    if let Some(idx) = self.paths.get_idx(&path) {
+      return idx;
+    }
+    self.paths.insert(path.clone(), path)
+  }
+
+  pub fn impl_header_idx(&self, def_id: DefId) -> Option<ImplHeaderIdx> {
+    self.impl_headers.get_idx(&def_id)
+  }
+
+  pub fn insert_impl_header(
+    &mut self,
+    def_id: DefId,
+    header: Encoded,
+  ) -> ImplHeaderIdx {
+    self.impl_headers.insert(def_id, header)
+  }
+
+  pub fn paths_stats(&self) -> InternerStats {
+    self.paths.stats()
+  }
+
+  pub fn impl_headers_stats(&self) -> InternerStats {
+    self.impl_headers.stats()
+  }
+
+  pub fn consume(
+    self,
+  ) -> (IndexVec<PathIdx, Encoded>, IndexVec<ImplHeaderIdx, Encoded>) {
+    (self.paths.consume(), self.impl_headers.consume())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn grows_on_demand_and_resets() {
+    let mut interner = Interner::<u32, TyIdx, u64>::default();
+    assert_eq!(interner.stats().reserved_bytes, 0);
+
+    for i in 0 .. 10_000 {
+      interner.insert(i, u64::from(i));
+    }
+    assert_eq!(interner.len(), 10_000);
+    let grown = interner.stats();
+    assert_eq!(grown.keys, 10_000);
+
+    interner.reset();
+    assert!(interner.is_empty());
+    assert!(interner.get_idx(&0).is_none());
+    assert!(interner.stats().reserved_bytes < grown.reserved_bytes);
+
+    // Indices start over after a reset.
+    assert_eq!(interner.insert(7, 7), TyIdx::from_usize(0));
+  }
 }
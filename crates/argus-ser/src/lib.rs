@@ -33,12 +33,14 @@
 )]
 #![allow(non_camel_case_types, non_snake_case)]
 extern crate rustc_apfloat;
+extern crate rustc_ast;
 extern crate rustc_ast_ir;
 extern crate rustc_data_structures;
 extern crate rustc_hir;
 extern crate rustc_infer;
 extern crate rustc_macros;
 extern crate rustc_middle;
+extern crate rustc_session;
 extern crate rustc_span;
 extern crate rustc_target;
 extern crate rustc_trait_selection;
@@ -47,23 +49,25 @@ extern crate rustc_trait_selection;
 pub mod argus;
 pub mod r#const;
 mod r#dyn;
+mod encoded;
+pub mod hir;
 mod path;
 mod safe;
 pub mod term;
 pub mod ty;
-use std::cell::Cell;
+use std::{cell::Cell, thread::LocalKey};
 pub mod interner;
 
 pub use argus::*;
+pub use encoded::Encoded;
 pub(crate) use r#dyn::DynCtxt;
 use rustc_infer::infer::InferCtxt;
 use rustc_trait_selection::traits::solve::Goal;
 // These types are safe for dependents to use.
 pub use safe::*;
 use serde::Serialize;
-use serde_json as json;
 
-use crate::interner::TyInterner;
+use crate::interner::{SharedInterner, TyInterner};
 
 /// # Panics
 ///
@@ -74,23 +78,37 @@ pub fn to_value_expect<'a, 'tcx: 'a, T: Serialize + 'a>(
   infcx: &'a InferCtxt<'tcx>,
   ty_interner: &'a TyInterner<'tcx>,
   value: &T,
-) -> json::Value {
+) -> Encoded {
   to_value(infcx, ty_interner, value).expect("failed to serialize value")
 }
 
-/// Entry function to serialize anything from rustc.
+/// Entry function to serialize anything from rustc, see [`Encoded`].
 pub fn to_value<'a, 'tcx: 'a, T: Serialize + 'a>(
   infcx: &'a InferCtxt<'tcx>,
   ty_interner: &'a TyInterner<'tcx>,
   value: &T,
-) -> Result<json::Value, json::Error> {
+) -> Result<Encoded, encoded::Error> {
   log::trace!("Setting Interner");
   TyInterner::invoke_in(ty_interner, || {
     log::trace!("Setting InferCtxt");
-    InferCtxt::invoke_in(infcx, || json::to_value(value))
+    InferCtxt::invoke_in(infcx, || Encoded::new(value))
   })
 }
 
+/// Run `f` with paths written once to `tables`, values serialized within
+/// refer to them by their [`PathIdx`](interner::PathIdx).
+pub fn with_shared_tables<T>(
+  tables: &SharedInterner,
+  f: impl FnOnce() -> T,
+) -> T {
+  SharedInterner::invoke_in(tables, f)
+}
+
+/// Are values serialized with shared tables, see [`with_shared_tables`]?
+pub fn has_shared_tables() -> bool {
+  SharedInterner::try_access(|tables| tables.is_some())
+}
+
 trait InferCtxtSerializeExt {
   fn should_print_verbose(&self) -> bool;
 }
@@ -116,13 +134,93 @@ macro_rules! serialize_custom_seq {
 // ----------------------------------------
 // Parameters
 
+// NOTE: unlike rustc, paths are neither trimmed nor visible by default, see
+// `SerializeOptions`.
 thread_local! {
     static FORCE_IMPL_FILENAME_LINE: Cell<bool> = const { Cell::new(false) };
     static SHOULD_PREFIX_WITH_CRATE: Cell<bool> = const { Cell::new(false) };
-    static NO_TRIMMED_PATH: Cell<bool> = const { Cell::new(false) };
+    static NO_TRIMMED_PATH: Cell<bool> = const { Cell::new(true) };
     static FORCE_TRIMMED_PATH: Cell<bool> = const { Cell::new(false) };
     static NO_QUERIES: Cell<bool> = const { Cell::new(false) };
-    static NO_VISIBLE_PATH: Cell<bool> = const { Cell::new(false) };
+    static NO_VISIBLE_PATH: Cell<bool> = const { Cell::new(true) };
+    static MAX_TY_DEPTH: Cell<Option<usize>> = const { Cell::new(None) };
+    static MAX_TY_WIDTH: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// How definition paths are serialized.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PathStyle {
+  /// The full definition path, e.g., `alloc::vec::Vec`.
+  #[default]
+  Qualified,
+  /// A path through public re-exports, e.g., `std::vec::Vec`.
+  Visible,
+  /// Only the name of types, traits and functions, e.g., `Vec`, falling back
+  /// to the visible path for other items.
+  Trimmed,
+}
+
+/// Options for the paths and types within serialized values, see
+/// [`SerializeOptions::apply`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SerializeOptions {
+  pub paths: PathStyle,
+  /// Prefix paths of the local crate with `crate::`.
+  pub crate_prefix: bool,
+  /// Name local impls by their location rather than their types.
+  pub impl_filename_line: bool,
+  /// Types nested deeper than this are abbreviated, see
+  /// [`interner::TyEntry`]. Types are only abbreviated when a limit is set.
+  pub max_ty_depth: Option<usize>,
+  /// Types with more compound arguments than this are abbreviated.
+  pub max_ty_width: Option<usize>,
+}
+
+impl SerializeOptions {
+  /// Run `f` with every path serialized within following these options.
+  pub fn apply<T>(&self, f: impl FnOnce() -> T) -> T {
+    let trimmed = self.paths == PathStyle::Trimmed;
+    let _guards = [
+      ParamGuard::new(&NO_TRIMMED_PATH, !trimmed),
+      ParamGuard::new(&FORCE_TRIMMED_PATH, trimmed),
+      ParamGuard::new(&NO_VISIBLE_PATH, self.paths == PathStyle::Qualified),
+      ParamGuard::new(&SHOULD_PREFIX_WITH_CRATE, self.crate_prefix),
+      ParamGuard::new(&FORCE_IMPL_FILENAME_LINE, self.impl_filename_line),
+    ];
+    let _limits = [
+      ParamGuard::new(&MAX_TY_DEPTH, self.max_ty_depth),
+      ParamGuard::new(&MAX_TY_WIDTH, self.max_ty_width),
+    ];
+    f()
+  }
+}
+
+/// Thresholds past which types are abbreviated, see [`SerializeOptions`].
+/// Absent if neither is set, an unset limit is unbounded.
+pub(crate) fn ty_limits() -> Option<(usize, usize)> {
+  let depth = MAX_TY_DEPTH.with(Cell::get);
+  let width = MAX_TY_WIDTH.with(Cell::get);
+  (depth.is_some() || width.is_some())
+    .then(|| (depth.unwrap_or(usize::MAX), width.unwrap_or(usize::MAX)))
+}
+
+/// Sets a parameter until dropped, when its previous value is restored.
+struct ParamGuard<T: Copy + 'static> {
+  param: &'static LocalKey<Cell<T>>,
+  previous: T,
+}
+
+impl<T: Copy> ParamGuard<T> {
+  fn new(param: &'static LocalKey<Cell<T>>, value: T) -> Self {
+    let previous = param.with(|c| c.replace(value));
+    ParamGuard { param, previous }
+  }
+}
+
+impl<T: Copy> Drop for ParamGuard<T> {
+  fn drop(&mut self) {
+    self.param.with(|c| c.set(self.previous));
+  }
 }
 
 macro_rules! define_helper {
@@ -181,6 +279,13 @@ define_helper!(
     fn with_forced_impl_filename_line(ForcedImplGuard, FORCE_IMPL_FILENAME_LINE);
     /// Adds the `crate::` prefix to paths where appropriate.
     fn with_crate_prefix(CratePrefixGuard, SHOULD_PREFIX_WITH_CRATE);
+    /// Prevent path trimming if it is turned on.
+    fn with_no_trimmed_paths(NoTrimmedGuard, NO_TRIMMED_PATH);
+    /// Trim paths of types, traits and functions to their name.
+    fn with_forced_trimmed_paths(ForceTrimmedGuard, FORCE_TRIMMED_PATH);
+    /// Prevent selection of visible paths, e.g., when printing the path of
+    /// an `extern crate` item that is itself printed within a visible path.
+    fn with_no_visible_paths(NoVisibleGuard, NO_VISIBLE_PATH);
 );
 
 // ----------------------------------------
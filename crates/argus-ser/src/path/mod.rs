@@ -70,19 +70,51 @@ impl<'tcx> ValuePathWithArgs<'tcx> {
 pub struct BasicPathNoArgs<'tcx>(DefinedPath<'tcx>);
 impl<'tcx> BasicPathNoArgs<'tcx> {
   pub fn new(def_id: DefId) -> Self {
-    Self(PathBuilder::compile_value_path(def_id, &[]))
+    Self(PathBuilder::compile_verbose_def_path(def_id))
   }
 }
 
-#[derive(Serialize)]
 #[cfg_attr(feature = "testing", derive(TS))]
 #[cfg_attr(feature = "testing", ts(export))]
 struct DefinedPath<'tcx> {
   path: Vec<PathSegment<'tcx>>,
-  #[serde(skip_serializing_if = "Option::is_none")]
+  #[cfg_attr(feature = "testing", ts(optional))]
   l: Option<DefLocation>,
 }
 
+impl Serialize for DefinedPath<'_> {
+  fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+  where
+    S: serde::Serializer,
+  {
+    use serde::ser::Error;
+
+    use crate::{interner::SharedInterner, DynCtxt};
+
+    #[derive(Serialize)]
+    struct Inline<'a, 'tcx> {
+      path: &'a [PathSegment<'tcx>],
+      #[serde(skip_serializing_if = "Option::is_none")]
+      l: &'a Option<DefLocation>,
+    }
+
+    let inline = Inline {
+      path: &self.path,
+      l: &self.l,
+    };
+    SharedInterner::try_access(|tables| match tables {
+      None => inline.serialize(s),
+      Some(tables) => {
+        // Nested paths are interned while encoding, the table can't be
+        // borrowed until it's done.
+        let encoded = crate::Encoded::new(&inline).map_err(S::Error::custom)?;
+        let idx = tables.borrow_mut().intern_path(encoded);
+        idx.serialize(s)
+      }
+    })
+  }
+}
+
 #[derive(Serialize)]
 #[cfg_attr(feature = "testing", derive(TS))]
 #[cfg_attr(feature = "testing", ts(export))]
@@ -207,6 +239,19 @@ impl<'a, 'tcx: 'a> PathBuilder<'tcx> {
     builder.into()
   }
 
+  // Mirrors `DefPath::to_string_no_crate_verbose`, parents are never pretty
+  // printed so an impl won't print its self type.
+  pub fn compile_verbose_def_path(def_id: DefId) -> DefinedPath<'tcx> {
+    let mut builder = Self::new(def_id);
+    for (i, data) in builder.tcx().def_path(def_id).data.iter().enumerate() {
+      if i > 0 {
+        builder.segments.push(PathSegment::Colons);
+      }
+      builder.fmt_maybe_verbose(data, true);
+    }
+    builder.into()
+  }
+
   pub fn compile_inherent_projection(
     alias_ty: &ty::AliasTy<'tcx>,
   ) -> DefinedPath<'tcx> {
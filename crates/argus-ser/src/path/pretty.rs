@@ -10,11 +10,15 @@
 
 use default::PathBuilderDefault;
 use rustc_hir::{
-  def_id::{CrateNum, DefId, LOCAL_CRATE},
-  definitions::{DefPathData, DefPathDataName, DisambiguatedDefPathData},
+  def::DefKind,
+  def_id::{CrateNum, DefId, ModDefId, LOCAL_CRATE},
+  definitions::{
+    DefKey, DefPathData, DefPathDataName, DisambiguatedDefPathData,
+  },
 };
 use rustc_middle::ty::{self, *};
-use rustc_span::symbol::Ident;
+use rustc_session::cstore::{ExternCrate, ExternCrateSource};
+use rustc_span::symbol::{kw, Ident, Symbol};
 use rustc_utils::source_map::range::CharRange;
 
 use super::*;
@@ -27,7 +31,17 @@ impl<'tcx> PathBuilder<'tcx> {
     args: &'tcx [GenericArg<'tcx>],
   ) {
     // CHANGE
-    // No longer trying to print a trimmed or "visible" def path.
+    // Trimmed and visible paths are only printed when asked for, see
+    // `SerializeOptions`.
+    if args.is_empty() {
+      if self.try_print_trimmed_def_path(def_id) {
+        return;
+      }
+
+      if self.try_print_visible_def_path(def_id) {
+        return;
+      }
+    }
 
     let key = self.tcx().def_key(def_id);
     if let DefPathData::Impl = key.disambiguated_data.data {
@@ -79,6 +93,260 @@ impl<'tcx> PathBuilder<'tcx> {
     self.default_print_def_path(def_id, args)
   }
 
+  /// If possible, this returns a global path resolving to `def_id` that is visible
+  /// from at least one local module, and returns `true`. If the crate defining `def_id` is
+  /// declared with an `extern crate`, the path is guaranteed to use the `extern crate`.
+  fn try_print_visible_def_path(&mut self, def_id: DefId) -> bool {
+    if with_no_visible_paths() {
+      return false;
+    }
+
+    // CHANGE: drop the segments of a path that couldn't be completed.
+    let (len, empty_path) = (self.segments.len(), self.empty_path);
+    let mut callers = Vec::new();
+    let printed = self.try_print_visible_def_path_recur(def_id, &mut callers);
+    if !printed {
+      self.segments.truncate(len);
+      self.empty_path = empty_path;
+    }
+    printed
+  }
+
+  // Given a `DefId`, produce a short name. For types and traits, it prints *only* its name,
+  // For associated items on traits it prints out the trait's name and the associated item's name.
+  // For enum variants we print the enum name and the variant name. Otherwise, we do not print
+  // anything and let the caller use the `print_def_path` fallback.
+  fn force_print_trimmed_def_path(&mut self, def_id: DefId) -> bool {
+    let key = self.tcx().def_key(def_id);
+    let kind = self.tcx().def_kind(def_id);
+
+    // CHANGE: not checking the `trimmed_def_paths` query for unique variant
+    // names, it must only be used when emitting a diagnostic.
+    let Some(symbol) = key.get_opt_name() else {
+      return false;
+    };
+
+    if let DefKind::AssocConst
+    | DefKind::AssocFn
+    | DefKind::AssocTy
+    | DefKind::Variant = kind
+      && let Some(parent) = self.tcx().opt_parent(def_id)
+      && let parent_key = self.tcx().def_key(parent)
+      && let Some(parent_symbol) = parent_key.get_opt_name()
+    {
+      // For associated items and variants, we want the "full" path, namely,
+      // include the parent trait or enum in the path. For example, `Iterator::Item`.
+      // CHANGE: self.write_str(..)?; self.write_str("::")?;
+      let name = self.local_name(parent_symbol, parent, parent_key);
+      self.segments.push(PathSegment::unambiguous_name(name));
+      self.segments.push(PathSegment::Colons);
+    } else if !matches!(
+      kind,
+      DefKind::Struct
+        | DefKind::Union
+        | DefKind::Enum
+        | DefKind::Trait
+        | DefKind::TyAlias
+        | DefKind::Fn
+        | DefKind::Const
+        | DefKind::Static { .. }
+    ) {
+      // If not covered above, like for example items out of `impl` blocks, fallback.
+      return false;
+    }
+
+    // CHANGE: self.write_str(get_local_name(..).as_str())?;
+    let name = self.local_name(symbol, def_id, key);
+    self.segments.push(PathSegment::unambiguous_name(name));
+    self.empty_path = false;
+    true
+  }
+
+  /// The name `def_id` is re-exported as by its visible parent, if any.
+  fn local_name(&self, name: Symbol, def_id: DefId, key: DefKey) -> Symbol {
+    let tcx = self.tcx();
+    if let Some(visible_parent) = tcx.visible_parent_map(()).get(&def_id)
+      && let actual_parent = tcx.opt_parent(def_id)
+      && let DefPathData::TypeNs(_) = key.disambiguated_data.data
+      && Some(*visible_parent) != actual_parent
+    {
+      tcx
+        .module_children(ModDefId::new_unchecked(*visible_parent))
+        .iter()
+        .filter(|child| child.res.opt_def_id() == Some(def_id))
+        .find(|child| {
+          child.vis.is_public() && child.ident.name != kw::Underscore
+        })
+        .map_or(name, |child| child.ident.name)
+    } else {
+      name
+    }
+  }
+
+  /// Try to see if this path can be trimmed to a unique symbol name.
+  fn try_print_trimmed_def_path(&mut self, def_id: DefId) -> bool {
+    // CHANGE: rustc only trims unique names found by the `trimmed_def_paths`
+    // query, a trimmed path here is always forced.
+    let trim = with_forced_trimmed_paths()
+      || !(with_no_trimmed_paths() || with_crate_prefix());
+    trim && self.force_print_trimmed_def_path(def_id)
+  }
+
+  /// Does the work of `try_print_visible_def_path`, building the
+  /// full definition path recursively before attempting to
+  /// post-process it into the valid and visible version that
+  /// accounts for re-exports.
+  ///
+  /// This method should only be called by itself or
+  /// `try_print_visible_def_path`.
+  ///
+  /// `callers` is a chain of visible_parent's leading to `def_id`,
+  /// to support cycle detection during recursion.
+  ///
+  /// This method returns false if we can't print the visible path, so
+  /// `print_def_path` can fall back on the item's real definition path.
+  fn try_print_visible_def_path_recur(
+    &mut self,
+    def_id: DefId,
+    callers: &mut Vec<DefId>,
+  ) -> bool {
+    log::trace!("try_print_visible_def_path: def_id={:?}", def_id);
+
+    // If `def_id` is a direct or injected extern crate, return the
+    // path to the crate followed by the path to the item within the crate.
+    if let Some(cnum) = def_id.as_crate_root() {
+      if cnum == LOCAL_CRATE {
+        self.path_crate(cnum);
+        return true;
+      }
+
+      // In local mode, when we encounter a crate other than
+      // LOCAL_CRATE, execution proceeds in one of two ways:
+      //
+      // 1. For a direct dependency, where user added an
+      //    `extern crate` manually, we put the `extern
+      //    crate` as the parent. So you wind up with
+      //    something relative to the current crate.
+      // 2. For an extern inferred from a path or an indirect crate,
+      //    where there is no explicit `extern crate`, we just prepend
+      //    the crate name.
+      match self.tcx().extern_crate(def_id) {
+        Some(&ExternCrate {
+          src,
+          dependency_of,
+          span,
+          ..
+        }) => match (src, dependency_of) {
+          (ExternCrateSource::Extern(def_id), LOCAL_CRATE) => {
+            // NOTE(eddyb) the only reason `span` might be dummy,
+            // that we're aware of, is that it's the `std`/`core`
+            // `extern crate` injected by default.
+            if span.is_dummy() {
+              self.path_crate(cnum);
+              return true;
+            }
+
+            // Disable `try_print_trimmed_def_path` behavior within
+            // the `print_def_path` call, to avoid infinite recursion
+            // in cases where the `extern crate foo` has non-trivial
+            // parents, e.g. it's nested in `impl foo::Trait for Bar`
+            // (see also issues #55779 and #87932).
+            with_no_visible_paths!(self.print_def_path(def_id, &[]));
+
+            return true;
+          }
+          (ExternCrateSource::Path, LOCAL_CRATE) => {
+            self.path_crate(cnum);
+            return true;
+          }
+          _ => {}
+        },
+        None => {
+          self.path_crate(cnum);
+          return true;
+        }
+      }
+    }
+
+    if def_id.is_local() {
+      return false;
+    }
+
+    let visible_parent_map = self.tcx().visible_parent_map(());
+
+    let mut cur_def_key = self.tcx().def_key(def_id);
+
+    // For a constructor, we want the name of its parent rather than <unnamed>.
+    if let DefPathData::Ctor = cur_def_key.disambiguated_data.data {
+      let parent = DefId {
+        krate: def_id.krate,
+        index: cur_def_key
+          .parent
+          .expect("`DefPathData::Ctor` / `VariantData` missing a parent"),
+      };
+
+      cur_def_key = self.tcx().def_key(parent);
+    }
+
+    let Some(visible_parent) = visible_parent_map.get(&def_id).copied() else {
+      return false;
+    };
+
+    let actual_parent = self.tcx().opt_parent(def_id);
+
+    let mut data = cur_def_key.disambiguated_data.data;
+
+    match data {
+      // In order to output a path that could actually be imported (valid and visible),
+      // we need to handle re-exports correctly, e.g., `std::os::unix::process::CommandExt`
+      // is defined in the private module `std::sys::unix::ext::process`. When the visible
+      // parent differs from the actual parent we use the name the item was re-exported as.
+      DefPathData::TypeNs(ref mut name)
+        if Some(visible_parent) != actual_parent =>
+      {
+        // Item might be re-exported several times, but filter for the one
+        // that's public and whose identifier isn't `_`.
+        let reexport = self
+          .tcx()
+          .module_children(ModDefId::new_unchecked(visible_parent))
+          .iter()
+          .filter(|child| child.res.opt_def_id() == Some(def_id))
+          .find(|child| {
+            child.vis.is_public() && child.ident.name != kw::Underscore
+          })
+          .map(|child| child.ident.name);
+
+        if let Some(new_name) = reexport {
+          *name = new_name;
+        } else {
+          // There is no name that is public and isn't `_`, so bail.
+          return false;
+        }
+      }
+      // Re-exported `extern crate` (#43189).
+      DefPathData::CrateRoot => {
+        data = DefPathData::TypeNs(self.tcx().crate_name(def_id.krate));
+      }
+      _ => {}
+    }
+
+    if callers.contains(&visible_parent) {
+      return false;
+    }
+    callers.push(visible_parent);
+    // HACK(eddyb) this bypasses `path_append`'s prefix printing to avoid
+    // knowing ahead of time whether the entire path will succeed or not.
+    if !self.try_print_visible_def_path_recur(visible_parent, callers) {
+      return false;
+    }
+    callers.pop();
+    self.path_append(|_| {}, &DisambiguatedDefPathData {
+      data,
+      disambiguator: 0,
+    });
+    true
+  }
+
   pub fn path_crate(&mut self, cnum: CrateNum) {
     self.empty_path = true;
     if cnum == LOCAL_CRATE {
@@ -187,6 +455,7 @@ impl<'tcx> PathBuilder<'tcx> {
         | ty::Float(_) => {
           // CHANGE: return self_ty.print(self);
           self.segments.push(PathSegment::Ty { ty: self_ty });
+          return;
         }
 
         _ => {}
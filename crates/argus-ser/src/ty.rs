@@ -31,28 +31,75 @@ impl TyDef {
   where
     S: serde::Serializer,
   {
-    let ty_idx;
-    if let Some(tyidx) =
-      TyInterner::access(|interner| interner.borrow().get_idx(value))
-    {
-      ty_idx = tyidx;
-    } else {
-      // FIXME: we shouldn't be calling `to_value` directly here, rather, we should use the current
-      // serializer and specialize on it and save the values only when we're serializing to JSON.
-      // If we don't need specialization the `min_specialization` feature can be removed.
-      // if let Some(ty_json) = <S::Ok as SerAsJSON<S>>::as_json(&ty_val) {
-      // } else {
-      //   todo!("type interning only implemented for JSON values");
-      // }
-
-      let ty_val = serde_json::to_value(TyVal(value.kind())).expect("TODO");
-
-      ty_idx = TyInterner::access(|interner| {
-        interner.borrow_mut().insert(*value, ty_val)
-      });
+    Self(intern_ty(*value)).serialize(s)
+  }
+}
+
+/// Index of `ty` in the type interner, it's encoded on first use.
+fn intern_ty(ty: ty::Ty) -> TyIdx {
+  if let Some(idx) =
+    TyInterner::access(|interner| interner.borrow().get_idx(&ty))
+  {
+    return idx;
+  }
+
+  // Interned types are encoded independently of the current serializer,
+  // they're transcoded into the output format when written.
+  let value = crate::Encoded::new(&TyVal(ty.kind())).expect("TODO");
+  let placeholders = abbreviate(ty);
+  TyInterner::access(|interner| {
+    interner.borrow_mut().insert(ty, interner::TyEntry {
+      value,
+      placeholders,
+    })
+  })
+}
+
+/// Subterms of `ty` replaced by placeholders in its abbreviated form, see
+/// [`interner::TyEntry`].
+fn abbreviate(ty: ty::Ty) -> Vec<TyIdx> {
+  fn cut<'tcx>(
+    ty: ty::Ty<'tcx>,
+    depth: usize,
+    limits: (usize, usize),
+    placeholders: &mut Vec<ty::Ty<'tcx>>,
+  ) {
+    let (max_depth, max_width) = limits;
+    let compound = subterms(ty)
+      .into_iter()
+      .filter(|sub| !subterms(*sub).is_empty());
+    for (i, sub) in compound.enumerate() {
+      if depth + 1 > max_depth || i >= max_width {
+        if !placeholders.contains(&sub) {
+          placeholders.push(sub);
+        }
+      } else {
+        cut(sub, depth + 1, limits, placeholders);
+      }
     }
-    Self(ty_idx).serialize(s)
-    // Self(TyIdx::from_usize(0)).serialize(s)
+  }
+
+  let Some(limits) = crate::ty_limits() else {
+    return vec![];
+  };
+  let mut placeholders = vec![];
+  cut(ty, 0, limits, &mut placeholders);
+  placeholders.into_iter().map(intern_ty).collect()
+}
+
+/// Types printed within `ty`, those of a closure or function definition are
+/// only printed as their path.
+fn subterms(ty: ty::Ty) -> Vec<ty::Ty> {
+  match ty.kind() {
+    ty::TyKind::Adt(_, args) => args.types().collect(),
+    ty::TyKind::Alias(_, alias_ty) => alias_ty.args.types().collect(),
+    ty::TyKind::Ref(_, ty, _)
+    | ty::TyKind::RawPtr(ty, _)
+    | ty::TyKind::Array(ty, _)
+    | ty::TyKind::Slice(ty) => vec![*ty],
+    ty::TyKind::Tuple(tys) => tys.to_vec(),
+    ty::TyKind::FnPtr(sig) => sig.skip_binder().inputs_and_output.to_vec(),
+    _ => vec![],
   }
 }
 
@@ -1823,11 +1870,10 @@ pub struct FnTrait<'tcx> {
 #[cfg_attr(feature = "testing", derive(TS))]
 #[cfg_attr(feature = "testing", ts(export))]
 pub struct Trait<'tcx> {
-  #[serde(with = "Polarity")]
-  #[cfg_attr(feature = "testing", ts(type = "Polarity"))]
-  polarity: ty::PredicatePolarity,
+  polarity: Polarity,
+  // Only the name, the arguments are printed with the associated items.
   #[cfg_attr(feature = "testing", ts(type = "DefinedPath"))]
-  trait_name: TraitRefPrintOnlyTraitPathDef<'tcx>,
+  trait_name: path::PathDefNoArgs<'tcx>,
   #[serde(with = "Slice__GenericArgDef")]
   #[cfg_attr(feature = "testing", ts(type = "GenericArg[]"))]
   own_args: &'tcx [ty::GenericArg<'tcx>],
@@ -1846,6 +1892,29 @@ pub struct AssocItemDef<'tcx> {
   term: ty::Term<'tcx>,
 }
 
+impl<'tcx> Trait<'tcx> {
+  pub(crate) fn new(
+    tcx: ty::TyCtxt<'tcx>,
+    polarity: Polarity,
+    trait_ref: ty::TraitRef<'tcx>,
+    assoc_args: Vec<AssocItemDef<'tcx>>,
+  ) -> Self {
+    let generics = tcx.generics_of(trait_ref.def_id);
+    Self {
+      polarity,
+      trait_name: path::PathDefNoArgs::new(trait_ref.def_id),
+      own_args: generics.own_args_no_defaults(tcx, trait_ref.args),
+      assoc_args,
+    }
+  }
+}
+
+impl<'tcx> AssocItemDef<'tcx> {
+  pub(crate) fn new(name: Symbol, term: ty::Term<'tcx>) -> Self {
+    Self { name, term }
+  }
+}
+
 impl<'tcx> OpaqueImpl<'tcx> {
   fn insert_trait_and_projection(
     tcx: ty::TyCtxt<'tcx>,
@@ -2061,10 +2130,6 @@ impl<'tcx> OpaqueImpl<'tcx> {
       // Print the rest of the trait types (that aren't Fn* family of traits)
       for ((trait_ref, polarity), assoc_items) in traits {
         Self::wrap_binder(&trait_ref, |trait_ref| {
-          let trait_name = TraitRefPrintOnlyTraitPathDef::new(trait_ref);
-
-          let generics = tcx.generics_of(trait_ref.def_id);
-          let own_args = generics.own_args_no_defaults(tcx, trait_ref.args);
           let mut assoc_args = vec![];
 
           for (assoc_item_def_id, term) in assoc_items {
@@ -2094,12 +2159,12 @@ impl<'tcx> OpaqueImpl<'tcx> {
             assoc_args.push(AssocItemDef { name, term });
           }
 
-          here_opaque_type.traits.push(Trait {
-            polarity,
-            trait_name,
-            own_args,
+          here_opaque_type.traits.push(Trait::new(
+            tcx,
+            polarity.into(),
+            *trait_ref,
             assoc_args,
-          });
+          ));
         });
       }
 
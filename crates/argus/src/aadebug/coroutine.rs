@@ -0,0 +1,180 @@
+//! Explain auto trait failures on futures by pointing at the value
+//! held across a suspension point.
+//!
+//! The proof tree for `impl Future: Send` bottoms out in auto trait goals on
+//! the types stored in the coroutine witness. We walk up from each failing
+//! leaf to the closest witness, and use the coroutine layout to find the
+//! local (or temporary) with the held type and the `.await` it lives across.
+//!
+//! The layout comes from the coroutine's MIR, which isn't available for
+//! bodies with errors. Those are still explained by the held type and the
+//! field that contains the offending type, without ranges.
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::{self, Ty, TyCtxt};
+use rustc_utils::source_map::range::CharRange;
+use serde::Serialize;
+#[cfg(feature = "testing")]
+use ts_rs::TS;
+
+use super::tree::{Goal, I, T};
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
+pub struct HeldAcrossAwait {
+  /// Goal on the coroutine witness.
+  witness: I,
+  /// Goal on the type of the held value.
+  held: I,
+  /// Failing goal that makes the held type fail the auto trait.
+  leaf: I,
+  /// Name of the local, `None` for temporaries.
+  name: Option<String>,
+  /// Range of the local or temporary held across the await, `None` if the
+  /// coroutine layout isn't available.
+  value_range: Option<CharRange>,
+  /// Range of the `.await` (or `yield`) the value is live across.
+  await_range: Option<CharRange>,
+  /// Field closest to the leaf that contains the offending type.
+  field: Option<FieldCause>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
+pub struct FieldCause {
+  /// Goal on the type that owns the field.
+  owner: I,
+  /// Name of the field, the index for tuple structs.
+  field: String,
+}
+
+/// Find the values held across an await for every failing leaf in the tree.
+pub fn held_across_await(tree: &T) -> Vec<HeldAcrossAwait> {
+  let mut leaves = tree
+    .dnf()
+    .iter_conjuncts()
+    .flat_map(|and| and.iter().copied().collect::<Vec<_>>())
+    .collect::<Vec<_>>();
+  leaves.sort();
+  leaves.dedup();
+
+  leaves
+    .into_iter()
+    .filter_map(|leaf| explain_leaf(tree, leaf))
+    .collect()
+}
+
+fn explain_leaf(tree: &T, leaf: I) -> Option<HeldAcrossAwait> {
+  // Goals from the leaf up to the root, skipping candidates.
+  let goals = tree
+    .topology
+    .path_to_root(leaf)
+    .iter_inclusive()
+    .filter_map(|&i| tree.goal(i))
+    .collect::<Vec<_>>();
+
+  let witness_pos = goals.iter().position(|g| witness_of(g).is_some())?;
+  // The witness itself can't be the leaf, it needs a held type below it.
+  let held_pos = witness_pos.checked_sub(1)?;
+  let witness = &goals[witness_pos];
+  let held = &goals[held_pos];
+
+  let tcx = witness.tcx();
+  let (coroutine_did, args) = witness_of(witness)?;
+  let trait_def_id = witness.predicate().as_trait_clause()?.def_id();
+  if !tcx.trait_is_auto(trait_def_id) || !coroutine_did.is_local() {
+    return None;
+  }
+
+  let held_ty = erase(tcx, self_ty(held)?);
+  let (name, value_range, await_range) =
+    held_local(tcx, coroutine_did, args, held_ty).unwrap_or_default();
+
+  let field = goals[..= held_pos]
+    .windows(2)
+    .find_map(|w| field_cause(tcx, &w[1], &w[0]));
+
+  Some(HeldAcrossAwait {
+    witness: witness.into(),
+    held: held.into(),
+    leaf,
+    name,
+    value_range,
+    await_range,
+    field,
+  })
+}
+
+/// Name and range of the local with type `held_ty` in the layout of
+/// `coroutine_did`, and the range of the suspension point it lives across.
+fn held_local<'tcx>(
+  tcx: TyCtxt<'tcx>,
+  coroutine_did: DefId,
+  args: ty::GenericArgsRef<'tcx>,
+  held_ty: Ty<'tcx>,
+) -> Option<(Option<String>, Option<CharRange>, Option<CharRange>)> {
+  let layout = tcx.mir_coroutine_witnesses(coroutine_did).as_ref()?;
+  let (local, await_span) = layout
+    .variant_fields
+    .iter()
+    .zip(&layout.variant_source_info)
+    .find_map(|(variant, source_info)| {
+      variant
+        .iter()
+        .find(|&&local| {
+          let decl = &layout.field_tys[local];
+          let ty = ty::EarlyBinder::bind(decl.ty).instantiate(tcx, args);
+          !decl.ignore_for_traits && erase(tcx, ty) == held_ty
+        })
+        .map(|&local| (local, source_info.span))
+    })?;
+
+  let source_map = tcx.sess.source_map();
+  let decl = &layout.field_tys[local];
+  let value_range = CharRange::from_span(decl.source_info.span, source_map);
+  let await_range = CharRange::from_span(await_span, source_map);
+  let name = layout.field_names[local].map(|s| s.to_string());
+  Some((name, value_range.ok(), await_range.ok()))
+}
+
+/// The field of `owner`'s self type whose type is the self type of `child`.
+fn field_cause<'tcx>(
+  tcx: TyCtxt<'tcx>,
+  owner: &Goal<'_, 'tcx>,
+  child: &Goal<'_, 'tcx>,
+) -> Option<FieldCause> {
+  let ty::Adt(def, args) = self_ty(owner)?.kind() else {
+    return None;
+  };
+
+  let child_ty = erase(tcx, self_ty(child)?);
+  def.all_fields().find_map(|field| {
+    (erase(tcx, field.ty(tcx, args)) == child_ty).then(|| FieldCause {
+      owner: owner.into(),
+      field: field.name.to_string(),
+    })
+  })
+}
+
+fn witness_of<'tcx>(
+  goal: &Goal<'_, 'tcx>,
+) -> Option<(DefId, ty::GenericArgsRef<'tcx>)> {
+  match self_ty(goal)?.kind() {
+    ty::CoroutineWitness(did, args) => Some((*did, args)),
+    _ => None,
+  }
+}
+
+fn self_ty<'tcx>(goal: &Goal<'_, 'tcx>) -> Option<Ty<'tcx>> {
+  goal
+    .predicate()
+    .as_trait_clause()
+    .map(|t| t.skip_binder().self_ty())
+}
+
+fn erase<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> Ty<'tcx> {
+  tcx.erase_regions(ty)
+}
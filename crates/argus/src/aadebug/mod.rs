@@ -1,10 +1,11 @@
+mod coroutine;
 mod dnf;
 pub(crate) mod tree;
 
-use std::time::Instant;
+use std::{collections::HashMap, time::Instant};
 
 use anyhow::Result;
-use argus_ext::ty::EvaluationResultExt;
+use argus_ext::ty::{EvaluationResultExt, TyCtxtExt};
 use index_vec::IndexVec;
 use rustc_infer::traits::solve::GoalSource;
 use rustc_trait_selection::solve::inspect::{InspectCandidate, InspectGoal};
@@ -26,6 +27,33 @@ pub struct Storage<'tcx> {
 #[cfg_attr(feature = "testing", ts(export))]
 pub struct AnalysisResults {
   pub problematic_sets: Vec<tree::SetHeuristic>,
+  pub held_across_await: Vec<coroutine::HeldAcrossAwait>,
+
+  /// Rendered goals of the problematic sets, used for evaluation.
+  #[serde(skip)]
+  pub(crate) rendered: HashMap<ProofNodeIdx, String>,
+}
+
+impl AnalysisResults {
+  /// Goals in the order they're presented to the user, sets are ordered by
+  /// momentum and those involving a bad unification come last.
+  pub fn ranked_goals(&self) -> Vec<ProofNodeIdx> {
+    let mut sets = self.problematic_sets.iter().collect::<Vec<_>>();
+    sets.sort_by_key(|s| (s.has_bad_unification(), s.momentum));
+
+    let mut goals = vec![];
+    for idx in sets.into_iter().flat_map(tree::SetHeuristic::goals) {
+      if !goals.contains(&idx) {
+        goals.push(idx);
+      }
+    }
+    goals
+  }
+
+  /// The rendered predicate of a goal in one of the problematic sets.
+  pub fn rendered(&self, idx: ProofNodeIdx) -> Option<&str> {
+    self.rendered.get(&idx).map(String::as_str)
+  }
 }
 
 impl<'tcx> Storage<'tcx> {
@@ -84,10 +112,14 @@ impl<'tcx> Storage<'tcx> {
           })
       });
 
+    let do_not_recommend =
+      goal.infcx().tcx.is_annotated_do_not_recommend(candidate);
+
     let new_idx = self.ns.push(tree::N::C {
       kind: candidate.kind(),
       result: candidate.result(),
       retain,
+      do_not_recommend,
     });
 
     // TODO: the topology is stored elsewhere, we need to make
@@ -111,11 +143,19 @@ impl<'tcx> Storage<'tcx> {
 
     let mut sets = vec![];
     tree.for_correction_set(|conjunct| sets.push(tree.weight(conjunct)));
+    let held_across_await = coroutine::held_across_await(tree);
+    let rendered = sets
+      .iter()
+      .flat_map(tree::SetHeuristic::goals)
+      .map(|idx| (idx, tree.render_goal(idx)))
+      .collect();
 
     timer::elapsed("aadeg::into_results", tree_start);
 
     AnalysisResults {
       problematic_sets: sets,
+      held_across_await,
+      rendered,
     }
   }
 }
@@ -1,13 +1,21 @@
-use std::{cell::RefCell, ops::Deref, time::Instant};
+use std::{cell::RefCell, collections::HashMap, ops::Deref, time::Instant};
 
-use argus_ext::ty::{EvaluationResultExt, TyCtxtExt, TyExt};
+use argus_ext::{
+  infer::InferCtxtExt,
+  ty::{EvaluationResultExt, TyCtxtExt, TyExt},
+};
+use argus_ser as ser;
 use index_vec::IndexVec;
-use rustc_infer::infer::InferCtxt;
+use rustc_infer::{
+  infer::{BoundRegionConversionTime, InferCtxt},
+  traits::ObligationCause,
+};
 use rustc_middle::{
   traits::solve::{CandidateSource, Goal as RGoal},
-  ty::{self, TyCtxt},
+  ty::{self, TyCtxt, TypeVisitableExt},
 };
-use rustc_trait_selection::solve::inspect::ProbeKind;
+use rustc_span::DUMMY_SP;
+use rustc_trait_selection::solve::{deeply_normalize, inspect::ProbeKind};
 use rustc_utils::timer;
 use serde::Serialize;
 #[cfg(feature = "testing")]
@@ -17,6 +25,7 @@ use super::dnf::{And, Dnf};
 use crate::{
   analysis::EvaluationResult,
   proof_tree::{topology::TreeTopology, ProofNodeIdx},
+  tls,
 };
 
 pub type I = ProofNodeIdx;
@@ -38,6 +47,42 @@ pub struct SetHeuristic {
 pub struct Heuristic {
   idx: I,
   kind: GoalKind,
+  /// Is the goal nested under an impl marked `#[diagnostic::do_not_recommend]`?
+  do_not_recommend: bool,
+}
+
+impl Heuristic {
+  // Library authors hide these impls from diagnostics, goals they introduce
+  // are ranked after anything that doesn't require type changes.
+  const DO_NOT_RECOMMEND_PENALTY: usize = 10;
+
+  fn weight(&self) -> usize {
+    let penalty = if self.do_not_recommend {
+      Self::DO_NOT_RECOMMEND_PENALTY
+    } else {
+      0
+    };
+    self.kind.weight() + penalty
+  }
+}
+
+impl SetHeuristic {
+  pub fn goals(&self) -> impl Iterator<Item = I> + '_ {
+    self.goals.iter().map(|h| h.idx)
+  }
+
+  /// Does the set contain a function with the wrong parameters? These are
+  /// hidden by the frontend unless all sets contain one.
+  pub fn has_bad_unification(&self) -> bool {
+    self.goals.iter().any(|h| {
+      matches!(
+        h.kind,
+        GoalKind::DeleteFnParams { .. }
+          | GoalKind::AddFnParams { .. }
+          | GoalKind::IncorrectParams { .. }
+      )
+    })
+  }
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -49,22 +94,135 @@ enum Location {
   External,
 }
 
+impl Location {
+  fn from_local(is_local: bool) -> Self {
+    if is_local {
+      Location::Local
+    } else {
+      Location::External
+    }
+  }
+}
+
+/// Coherence rule that forbids the user from writing the impl directly.
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(tag = "type")]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
+enum ImplRestriction {
+  /// Neither the trait nor the self type are local (E0117).
+  Orphan,
+  /// An existing impl already covers the self type (E0119).
+  Overlap,
+}
+
+/// Fixes available when the direct impl is forbidden.
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(tag = "type")]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
+enum Alternative {
+  /// Wrap the self type in a local newtype and implement the trait for it.
+  Newtype,
+  /// Define a local trait mirroring the external one.
+  LocalTrait,
+  /// Use a different type that already satisfies the bound.
+  TyChange,
+}
+
+/// A trait predicate the user cannot fix by writing an impl.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
+struct ForbiddenImpl {
+  rule: ImplRestriction,
+  // Ordered by preference.
+  alternatives: Vec<Alternative>,
+}
+
+impl ForbiddenImpl {
+  fn new(rule: ImplRestriction) -> Self {
+    let alternatives = match rule {
+      ImplRestriction::Orphan => vec![
+        Alternative::Newtype,
+        Alternative::LocalTrait,
+        Alternative::TyChange,
+      ],
+      ImplRestriction::Overlap => {
+        vec![Alternative::Newtype, Alternative::TyChange]
+      }
+    };
+    ForbiddenImpl { rule, alternatives }
+  }
+
+  /// Cost of the cheapest alternative.
+  fn weight(&self) -> usize {
+    self
+      .alternatives
+      .iter()
+      .map(|a| a.weight())
+      .min()
+      .unwrap_or(GoalKind::Misc.weight())
+  }
+}
+
+impl Alternative {
+  fn weight(self) -> usize {
+    match self {
+      Alternative::Newtype => 3,
+      Alternative::LocalTrait => 4,
+      Alternative::TyChange => GoalKind::TyChange.weight(),
+    }
+  }
+}
+
 #[derive(Serialize, Debug, Clone)]
 #[serde(tag = "type")]
 #[cfg_attr(feature = "testing", derive(TS))]
 #[cfg_attr(feature = "testing", ts(export))]
 enum GoalKind {
-  Trait { _self: Location, _trait: Location },
+  Trait {
+    _self: Location,
+    _trait: Location,
+  },
+  ForbiddenImpl(ForbiddenImpl),
   TyChange,
-  FnToTrait { _trait: Location, arity: usize },
-  TyAsCallable { arity: usize },
-  DeleteFnParams { delta: usize },
-  AddFnParams { delta: usize },
+  FnToTrait {
+    _trait: Location,
+    arity: usize,
+  },
+  TyAsCallable {
+    arity: usize,
+  },
+  DeleteFnParams {
+    delta: usize,
+  },
+  AddFnParams {
+    delta: usize,
+  },
   // Represents a function with the correct number of parameters,
   // but the parameters trait bounds or types are unsatisifed.
   // TODO if it's worth the extra effort, we could figure out which
   // parameters are incorrect and highlight them to the user.
-  IncorrectParams { arity: usize },
+  IncorrectParams {
+    arity: usize,
+  },
+  // Either type binds lifetimes, e.g., a closure inferred as
+  // `fn(&'a T)` where `for<'a> fn(&'a T)` was expected.
+  HigherRankedSubtype {
+    #[cfg_attr(feature = "testing", ts(type = "Ty"))]
+    sub: ser::Encoded,
+    #[cfg_attr(feature = "testing", ts(type = "Ty"))]
+    sup: ser::Encoded,
+  },
+  // A failed `Unsize` or `CoerceUnsized` goal.
+  Unsize {
+    #[cfg_attr(feature = "testing", ts(type = "Ty"))]
+    source: ser::Encoded,
+    #[cfg_attr(feature = "testing", ts(type = "Ty"))]
+    target: ser::Encoded,
+  },
   Misc,
 }
 
@@ -93,6 +251,12 @@ impl GoalKind {
         _trait: E,
       } => 2,
 
+      GK::ForbiddenImpl(forbidden) => forbidden.weight(),
+
+      // Usually fixed by annotating the closure parameters, or
+      // with an explicit cast or boxing the value.
+      GK::HigherRankedSubtype { .. } | GK::Unsize { .. } => 3,
+
       GK::TyChange => 4,
       GK::IncorrectParams { arity: delta }
       | GK::AddFnParams { delta }
@@ -146,6 +310,10 @@ impl<'a, 'tcx> Goal<'a, 'tcx> {
     self.goal.predicate
   }
 
+  pub fn tcx(&self) -> TyCtxt<'tcx> {
+    self.infcx.tcx
+  }
+
   pub fn last_ancestor_pre_builtin(&self) -> Self {
     let not_builtin = |kind| {
       !matches!(kind, ProbeKind::TraitCandidate {
@@ -168,6 +336,104 @@ impl<'a, 'tcx> Goal<'a, 'tcx> {
     tree.goal(i).expect("invalid ancestor")
   }
 
+  /// Is any candidate between the goal and the root an impl marked
+  /// `#[diagnostic::do_not_recommend]`?
+  fn is_under_do_not_recommend(&self) -> bool {
+    self
+      .tree
+      .topology
+      .path_to_root(self.idx)
+      .iter_inclusive()
+      .any(|&i| {
+        matches!(self.tree.ns[i], N::C {
+          do_not_recommend: true,
+          ..
+        })
+      })
+  }
+
+  /// Check whether coherence forbids implementing the trait predicate
+  /// directly in the local crate.
+  ///
+  /// Each check builds an intercrate inference context, results are shared
+  /// by all goals of the tree with the same trait ref.
+  fn impl_restriction(
+    &self,
+    t: ty::TraitPredicate<'tcx>,
+  ) -> Option<ImplRestriction> {
+    let trait_ref = self
+      .infcx
+      .resolve_vars_if_possible(self.predicate().kind().rebind(t.trait_ref));
+    // Inference variables belong to the goal's context, and coherence can't
+    // tell whether the impl is allowed anyways.
+    if trait_ref.has_infer() {
+      return None;
+    }
+
+    let key = self.tcx().erase_regions(trait_ref);
+    *self
+      .tree
+      .impl_restrictions
+      .borrow_mut()
+      .entry(key)
+      .or_insert_with(|| {
+        if !self.infcx.orphan_check_local(trait_ref)? {
+          return Some(ImplRestriction::Orphan);
+        }
+
+        let overlaps = self.infcx.overlapping_impls(trait_ref);
+        (!overlaps.is_empty()).then_some(ImplRestriction::Overlap)
+      })
+  }
+
+  /// Encode `ty` in the tree's type table, the analysis is serialized after
+  /// the inference context is gone.
+  fn encode_ty(&self, ty: ty::Ty<'tcx>) -> ser::Encoded {
+    #[derive(Serialize)]
+    struct Wrapper<'tcx>(#[serde(with = "ser::ty::TyDef")] ty::Ty<'tcx>);
+
+    let ty = self.infcx.resolve_vars_if_possible(ty);
+    tls::unsafe_access_interner(|ty_interner| {
+      ser::to_value_expect(self.infcx, ty_interner, &Wrapper(ty))
+    })
+  }
+
+  /// A projection that only fails because its bound lifetimes are too
+  /// general, e.g., a closure whose return type is inferred for a single
+  /// lifetime where `for<'a> Fn(&'a u8) -> &'a u8` is expected. Returns the
+  /// normalized and the expected type.
+  fn higher_ranked_projection(
+    &self,
+    p: ty::ProjectionPredicate<'tcx>,
+  ) -> Option<(ty::Ty<'tcx>, ty::Ty<'tcx>)> {
+    let binder = self.predicate().kind();
+    if !binder
+      .bound_vars()
+      .iter()
+      .any(|v| matches!(v, ty::BoundVariableKind::Region(..)))
+    {
+      return None;
+    }
+
+    let tcx = self.infcx.tcx;
+    let expected = p.term.ty()?;
+    self.infcx.probe(|_| {
+      let p = self.infcx.instantiate_binder_with_fresh_vars(
+        DUMMY_SP,
+        BoundRegionConversionTime::HigherRankedType,
+        binder.rebind(p),
+      );
+      let alias = p.projection_term.to_term(tcx).ty()?;
+      let cause = ObligationCause::dummy();
+      let at = self.infcx.at(&cause, self.goal.param_env);
+      let normalized = deeply_normalize(at, alias).ok()?;
+      let normalized =
+        tcx.erase_regions(self.infcx.resolve_vars_if_possible(normalized));
+      let term = tcx.erase_regions(self.infcx.resolve_vars_if_possible(p.term));
+      (term.ty() == Some(normalized)).then_some((normalized, expected))
+    })
+  }
+
   fn analyze(&self) -> Heuristic {
     use std::cmp::Ordering;
 
@@ -178,7 +444,19 @@ impl<'a, 'tcx> Goal<'a, 'tcx> {
 
     let tcx = self.infcx.tcx;
 
+    let lang_items = tcx.lang_items();
     let kind = match self.predicate().kind().skip_binder() {
+      ty::PredicateKind::Clause(ty::ClauseKind::Trait(t))
+        if t.polarity == ty::PredicatePolarity::Positive
+          && [lang_items.unsize_trait(), lang_items.coerce_unsized_trait()]
+            .contains(&Some(t.def_id())) =>
+      {
+        GoalKind::Unsize {
+          source: self.encode_ty(t.self_ty()),
+          target: self.encode_ty(t.trait_ref.args.type_at(1)),
+        }
+      }
+
       ty::PredicateKind::Clause(ty::ClauseKind::Trait(t))
         if t.polarity == ty::PredicatePolarity::Positive
           && tcx.is_fn_trait(t.def_id())
@@ -215,14 +493,8 @@ impl<'a, 'tcx> Goal<'a, 'tcx> {
         if t.polarity == ty::PredicatePolarity::Positive
           && let Some(fn_arity) = tcx.function_arity(t.self_ty()) =>
       {
-        let def_id = t.def_id();
-        let location = if def_id.is_local() {
-          Location::Local
-        } else {
-          Location::External
-        };
         GoalKind::FnToTrait {
-          _trait: location,
+          _trait: Location::from_local(t.def_id().is_local()),
           arity: fn_arity,
         }
       }
@@ -235,26 +507,12 @@ impl<'a, 'tcx> Goal<'a, 'tcx> {
         let ty = t.self_ty();
         let def_id = t.def_id();
 
-        let def_id_local = def_id.is_local();
-        let ty_local = ty.is_local();
-
-        match (ty_local, def_id_local) {
-          (true, true) => GoalKind::Trait {
-            _self: Location::Local,
-            _trait: Location::Local,
-          },
-          (true, false) => GoalKind::Trait {
-            _self: Location::Local,
-            _trait: Location::External,
-          },
-          (false, true) => GoalKind::Trait {
-            _self: Location::External,
-            _trait: Location::Local,
-          },
-          (false, false) => GoalKind::Trait {
-            _self: Location::External,
-            _trait: Location::External,
+        match self.impl_restriction(t) {
+          None => GoalKind::Trait {
+            _self: Location::from_local(ty.is_local()),
+            _trait: Location::from_local(def_id.is_local()),
           },
+          Some(rule) => GoalKind::ForbiddenImpl(ForbiddenImpl::new(rule)),
         }
       }
 
@@ -263,23 +521,33 @@ impl<'a, 'tcx> Goal<'a, 'tcx> {
         GoalKind::Misc
       }
 
-      ty::PredicateKind::Clause(ty::ClauseKind::Projection(_)) => {
-        GoalKind::TyChange
+      ty::PredicateKind::Clause(ty::ClauseKind::Projection(p)) => {
+        match self.higher_ranked_projection(p) {
+          Some((normalized, expected)) => GoalKind::HigherRankedSubtype {
+            sub: self.encode_ty(normalized),
+            sup: self.encode_ty(expected),
+          },
+          None => GoalKind::TyChange,
+        }
       }
 
+      // Outlives goals always hold during typeck, they're region constraints
+      // reported by borrowck. Subtype, coerce and const equate goals only
+      // relate unresolved variables and stay ambiguous rather than fail.
       ty::PredicateKind::Clause(..)
-      | ty::PredicateKind::NormalizesTo(..)
-      | ty::PredicateKind::AliasRelate(..)
-      | ty::PredicateKind::ObjectSafe(..)
       | ty::PredicateKind::Subtype(..)
       | ty::PredicateKind::Coerce(..)
       | ty::PredicateKind::ConstEquate(..)
+      | ty::PredicateKind::NormalizesTo(..)
+      | ty::PredicateKind::AliasRelate(..)
+      | ty::PredicateKind::ObjectSafe(..)
       | ty::PredicateKind::Ambiguous => GoalKind::Misc,
     };
 
     Heuristic {
       idx: self.idx,
       kind,
+      do_not_recommend: self.is_under_do_not_recommend(),
     }
   }
 }
@@ -320,6 +588,7 @@ pub enum N<'tcx> {
     kind: ProbeKind<TyCtxt<'tcx>>,
     result: EvaluationResult,
     retain: bool,
+    do_not_recommend: bool,
   },
   R {
     infcx: InferCtxt<'tcx>,
@@ -334,6 +603,8 @@ pub struct T<'a, 'tcx: 'a> {
   pub topology: &'a TreeTopology,
   pub maybe_ambiguous: bool,
   dnf: RefCell<Option<Dnf<I>>>,
+  impl_restrictions:
+    RefCell<HashMap<ty::PolyTraitRef<'tcx>, Option<ImplRestriction>>>,
 }
 
 impl<'a, 'tcx: 'a> T<'a, 'tcx> {
@@ -349,6 +620,7 @@ impl<'a, 'tcx: 'a> T<'a, 'tcx> {
       topology,
       maybe_ambiguous,
       dnf: RefCell::new(None),
+      impl_restrictions: RefCell::default(),
     }
   }
 
@@ -375,12 +647,19 @@ impl<'a, 'tcx: 'a> T<'a, 'tcx> {
     }
   }
 
+  /// The goal predicate as rustc would print it, e.g. `Rc<()>: Send`.
+  pub fn render_goal(&self, i: I) -> String {
+    let goal = self.goal(i).expect("goal");
+    ty::print::with_forced_trimmed_paths!(goal.predicate().to_string())
+  }
+
   pub fn candidate(&self, i: I) -> Option<Candidate<'_, 'tcx>> {
     match &self.ns[i] {
       N::C {
         kind,
         result,
         retain,
+        ..
       } => Some(Candidate {
         idx: i,
         retain: *retain,
@@ -454,21 +733,27 @@ impl<'a, 'tcx: 'a> T<'a, 'tcx> {
   /// Non-intrusive changes:
   ///
   /// A local type failing to implement a trait (local/external).
-  /// NOTE that `T: C` where `T` is an external type is considered impossible
-  /// to change, if this is the only option a relaxed rule might suggest
-  /// creating a wrapper for the type.
+  /// When coherence forbids the impl (orphan rule or overlap) the predicate
+  /// is weighted by its cheapest alternative: a newtype wrapper, a local
+  /// trait, or changing the type.
+  ///
+  /// Unsize goals and projections that fail only for their bound lifetimes
+  /// are usually fixed by an annotation, a cast or boxing the value.
   ///
   /// Intrusive changes
   ///
   /// Changing types. That could either be changing a type to match an
   /// alias-relate, deleting function parameters or tuple elements.
+  ///
+  /// Goals introduced by an impl marked `#[diagnostic::do_not_recommend]`
+  /// carry an extra penalty, the library author asked us not to show them.
   pub fn weight(&self, and: &And<I>) -> SetHeuristic {
     let goals = and
       .iter()
       .map(|&idx| self.goal(idx).expect("goal").analyze())
       .collect::<Vec<_>>();
 
-    let momentum = goals.iter().fold(0, |acc, g| acc + g.kind.weight());
+    let momentum = goals.iter().fold(0, |acc, g| acc + g.weight());
     let velocity = and
       .iter()
       .map(|&idx| self.topology.depth(idx))
@@ -493,6 +778,7 @@ impl std::fmt::Debug for N<'_> {
         kind,
         result,
         retain,
+        ..
       } => write!(f, "C {{ {retain} {result:?} {kind:?} }}"),
       N::R { goal, result, .. } => {
         write!(f, "R {{ result: {result:?}, goal: {:?} }}", goal.predicate)
@@ -2,11 +2,11 @@
 //! rleationships between large structures.
 
 use anyhow::{anyhow, bail, Result};
-use argus_ext::ty::EvaluationResultExt;
+use argus_ext::ty::{EvaluationResultExt, PredicateExt, TyCtxtExt};
 use fluid_let::fluid_let;
 use rustc_hir::BodyId;
 use rustc_infer::{infer::InferCtxt, traits::PredicateObligation};
-use rustc_middle::ty::{TyCtxt, TypeckResults};
+use rustc_middle::ty::{TyCtxt, TypeVisitableExt, TypeckResults};
 use rustc_trait_selection::traits::solve::Goal;
 
 use crate::{
@@ -17,11 +17,17 @@ use crate::{
   proof_tree::{serialize::try_serialize, SerializedTree},
   tls,
   types::{
-    intermediate::{ErrorAssemblyCtx, Forgettable, FullData},
-    ObligationHash, ObligationNecessity, ObligationsInBody,
+    intermediate::{ErrorAssemblyCtx, Forgettable, FullData, Provenance},
+    Obligation, ObligationHash, ObligationsInBody,
   },
 };
 
+// Each ambiguity is only compared against the next `DRAIN_WINDOW` stored
+// obligations, checking `is_refined_by` on every later pair is quadratic in
+// the size of the body. An ambiguity resolved further away, e.g., by a use
+// of the variable at the end of a long function, is kept as a stale `maybe`.
+const DRAIN_WINDOW: usize = 100;
+
 fluid_let! {
   pub static INSPECTING: bool;
   pub static BODY_ID: BodyId;
@@ -62,27 +68,18 @@ pub fn process_obligation<'tcx>(
     // and we want to present it as such to the user.
     let obl = &infcx.resolve_vars_if_possible(obl.clone());
 
-    // HACK: Remove ambiguous obligations if a "stronger" result was found and
-    // the predicate implies the  previous. This is necessary because we
-    // can't (currently) distinguish between a subsequent solving attempt
-    // of a previous obligation.
-    // if result.is_yes() || result.is_no() {
-    //   tls::drain_implied_ambiguities(infcx, obl);
-    // }
-
     if !INCLUDE_SUCCESSES.copied().unwrap_or(false) && result.is_yes() {
       log::debug!("Skipping successful obligation {obl:?}");
       return;
     }
 
     let necessity = infcx.obligation_necessity(obl);
-    let dataid = if matches!(necessity, ObligationNecessity::Yes)
-      || (matches!(necessity, ObligationNecessity::OnError) && result.is_no())
-    {
-      Some(tls::unsafe_store_data(infcx, obl, result))
-    } else {
-      None
-    };
+    // Ambiguities over inference variables are also stored, these may be
+    // drained after typeck if a later result implies them, see
+    // `drain_implied_ambiguities`. Without variables nothing can refine them.
+    let drainable = result.is_maybe() && obl.predicate.has_non_region_infer();
+    let dataid = (necessity.is_necessary(result) || drainable)
+      .then(|| tls::unsafe_store_data(infcx, obl, result));
 
     let obligation =
       transform::compute_provenance(body_id, infcx, obl, result, dataid);
@@ -212,20 +209,21 @@ pub(in crate::analysis) fn build_obligations_in_body<'tcx>(
   let mut obligations = tls::take_obligations();
   let obligation_data = tls::unsafe_take_data();
   let obligation_data = FullData::new(obligation_data);
+  drain_implied_ambiguities(&mut obligations, &obligation_data);
 
   // XXX: it's possible that Argus reports false negatives. Meaning that
   // in a body that type checks, failing obligations are reported.
   // This happens as a result of how predicates are extracted from rustc (ask gavin)
   // so as a first heuristic, if the body isn't tainted by errors, we'll just remove
   // all non-successful obligations.
-  if typeck_results.tainted_by_errors.is_none() {
+  if tcx.body_has_errors(typeck_results) {
+    log::debug!("Body tainted! {:?}", typeck_results.hir_owner);
+  } else {
     log::debug!(
       "Removing failures: Body not-tainted {:?}",
       typeck_results.hir_owner
     );
     obligations.retain(|prov| prov.it.result.is_yes());
-  } else {
-    log::debug!("Body tainted! {:?}", typeck_results.hir_owner);
   }
 
   let ctx = ErrorAssemblyCtx {
@@ -250,3 +248,52 @@ pub(in crate::analysis) fn build_obligations_in_body<'tcx>(
 
   (Forgettable::new(obligation_data), oib)
 }
+
+/// Remove ambiguous obligations implied by a later definite result.
+///
+/// We can't distinguish a subsequent solving attempt of an obligation from a
+/// new one, so ambiguities that rustc later resolved would otherwise show up
+/// as stale `maybe`s. This runs after typeck on the stored (forked) inference
+/// contexts, the live `InferCtxt` is never touched.
+fn drain_implied_ambiguities(
+  obligations: &mut Vec<Provenance<Obligation>>,
+  obligation_data: &FullData,
+) {
+  let data = |prov: &Provenance<Obligation>| {
+    prov.full_data.map(|idx| obligation_data.get(idx))
+  };
+
+  let implied = obligations
+    .iter()
+    .enumerate()
+    .map(|(i, prov)| {
+      let Some(ambiguous) = data(prov).filter(|_| prov.result.is_maybe())
+      else {
+        return false;
+      };
+
+      obligations[i + 1 ..]
+        .iter()
+        .take(DRAIN_WINDOW)
+        .filter(|later| !later.result.is_maybe())
+        .filter_map(data)
+        .any(|definite| {
+          // Use the later context, it was forked from the same tables after
+          // the ambiguous one and therefore knows all of its variables.
+          ambiguous
+            .obligation
+            .predicate
+            .is_refined_by(&definite.infcx, &definite.obligation.predicate)
+        })
+    })
+    .collect::<Vec<_>>();
+
+  let mut implied = implied.into_iter();
+  obligations.retain(|prov| {
+    let drain = implied.next().unwrap_or(false);
+    if drain {
+      log::debug!("Draining implied ambiguity {:?}", prov.it);
+    }
+    !drain
+  });
+}
@@ -1,7 +1,8 @@
 use argus_ext::ty::TyCtxtExt;
 use rustc_data_structures::fx::FxHashMap as HashMap;
 use rustc_hir::{
-  self as hir, intravisit::Visitor as HirVisitor, BodyId, HirId,
+  self as hir, def_id::LocalDefId, intravisit::Visitor as HirVisitor, BodyId,
+  HirId,
 };
 use rustc_middle::{hir::nested_filter, ty::TyCtxt};
 use rustc_span::Span;
@@ -17,6 +18,42 @@ pub fn associate_obligations_nodes(ctx: &ErrorAssemblyCtx) -> Vec<Bin> {
   bin_expressions(ctx, grouped)
 }
 
+/// Bin the obligations of an impl block, trait or type definition, `hir_ids`
+/// are the most enclosing nodes of each obligation, see `walk_item`.
+pub fn associate_item_obligations(
+  tcx: TyCtxt,
+  def_id: LocalDefId,
+  hir_ids: &[HirId],
+) -> Vec<Bin> {
+  let mut map: HashMap<_, Vec<_>> = HashMap::default();
+  for (i, hir_id) in hir_ids.iter().enumerate() {
+    map.entry(*hir_id).or_default().push(i);
+  }
+
+  let mut binner = BinCreator {
+    tcx,
+    map: &mut map,
+    bins: vec![],
+  };
+
+  for ty in assoc_tys(tcx, def_id) {
+    binner.visit_ty(ty);
+    binner.drain_nested(ty.hir_id, BinKind::AssocBinding);
+  }
+  binner.visit_signature(def_id);
+
+  let mut bins = binner.bins;
+  for (hir_id, obligations) in map {
+    bins.push(Bin {
+      hir_id,
+      obligations,
+      kind: BinKind::Misc,
+    });
+  }
+
+  bins
+}
+
 // Given a map from [ HirId -> Vec< usize > ]
 //
 // we categorize obligations even further:
@@ -33,21 +70,26 @@ pub fn associate_obligations_nodes(ctx: &ErrorAssemblyCtx) -> Vec<Bin> {
 // -- obligations corresponding call args `arg1, arg2, ...`, respectively.
 // -- obligations corresponding to `obj . frobnicate(arg1, arg2, ...)`
 //
-// TODO: for now, we will leave everything else *untouched*. We may want
-// to expand this in the future, for example, when creating a system of
-// traits it is important to debug why an impl block is invalid. (Either
-// ill-formed, violates orphan rule, overlapping, etc...)
+// * for the signature of the body owner, see `walk_signature`
+//
+// -- obligations corresponding to associated type bindings `Item = T`
+// -- obligations corresponding to `where` clauses (and inline bounds)
+// -- obligations corresponding to fn signature and field types
+// -- obligations corresponding to the enclosing impl header
+//
+// Everything else is left *untouched* as a miscellaneous bin.
 fn bin_expressions(
   ctx: &ErrorAssemblyCtx,
   mut map: HashMap<HirId, Vec<usize>>,
 ) -> Vec<Bin> {
   let mut binner = BinCreator {
-    ctx,
+    tcx: ctx.tcx,
     map: &mut map,
     bins: vec![],
   };
 
   binner.visit_body(ctx.tcx.hir().body(ctx.body_id));
+  binner.visit_signature(ctx.tcx.hir().body_owner_def_id(ctx.body_id));
 
   // Add remaining miscellaneous unbinned obligations
   let mut bins = binner.bins;
@@ -67,6 +109,12 @@ pub enum BinKind {
   CallableExpr,
   CallArg,
   Call,
+  FnSigTy,
+  WhereClause,
+  FieldTy,
+  ImplHeader,
+  AssocBinding,
+  Closure,
   Misc,
 }
 
@@ -80,15 +128,23 @@ pub struct Bin {
 }
 
 struct BinCreator<'a, 'tcx: 'a> {
-  ctx: &'a ErrorAssemblyCtx<'a, 'tcx>,
+  tcx: TyCtxt<'tcx>,
   map: &'a mut HashMap<HirId, Vec<usize>>,
   bins: Vec<Bin>,
 }
 
 impl BinCreator<'_, '_> {
   fn drain_nested(&mut self, target: HirId, kind: BinKind) {
-    let is_nested = |id: HirId| self.ctx.tcx.is_parent_of(target, id);
+    let tcx = self.tcx;
+    self.drain_where(target, kind, |id| tcx.is_parent_of(target, id));
+  }
 
+  fn drain_where(
+    &mut self,
+    target: HirId,
+    kind: BinKind,
+    is_nested: impl Fn(HirId) -> bool,
+  ) {
     let obligations = self
       .map
       .extract_if(|&id, _| is_nested(id))
@@ -98,7 +154,7 @@ impl BinCreator<'_, '_> {
     if !obligations.is_empty() {
       log::debug!(
         "Associating obligations with {kind:?} {:?}\n{:#?}",
-        self.ctx.tcx.hir().node_to_string(target),
+        self.tcx.hir().node_to_string(target),
         obligations
       );
 
@@ -109,25 +165,59 @@ impl BinCreator<'_, '_> {
       });
     }
   }
+
+  fn visit_signature(&mut self, owner: LocalDefId) {
+    walk_signature(self.tcx, owner, self);
+
+    // The impl header is drained last, bounds and bindings within it are
+    // more specific.
+    if let Some(impl_) = enclosing_impl(self.tcx, owner) {
+      if let Some(trait_ref) = &impl_.of_trait {
+        self.drain_nested(trait_ref.hir_ref_id, BinKind::ImplHeader);
+      }
+      self.drain_nested(impl_.self_ty.hir_id, BinKind::ImplHeader);
+    }
+  }
 }
 
 impl<'a, 'tcx: 'a> HirVisitor<'tcx> for BinCreator<'a, 'tcx> {
   type NestedFilter = nested_filter::All;
 
   fn nested_visit_map(&mut self) -> Self::Map {
-    self.ctx.tcx.hir()
+    self.tcx.hir()
   }
 
   // FIXME: after updating to nightly-2024-05-20 this binning logic broke slightly.
   // Obligations associated with parameters are now being assigned to the overall call,
   // this makes more things use a method call table than necessary.
   fn visit_expr(&mut self, ex: &'tcx hir::Expr) {
+    // Closures and async blocks are type-checked with their parent, all
+    // obligations within them are reported on the closure expression. The
+    // closure itself is left for the enclosing call, that's where the
+    // `Fn*` and `Future` requirements are placed. (The coroutine of an
+    // `async fn` is its whole body, that one is binned as usual.)
+    if let hir::ExprKind::Closure(closure) = ex.kind
+      && !matches!(
+        closure.kind,
+        hir::ClosureKind::Coroutine(hir::CoroutineKind::Desugared(
+          _,
+          hir::CoroutineSource::Fn
+        ))
+      )
+    {
+      let tcx = self.tcx;
+      self.drain_where(ex.hir_id, BinKind::Closure, |id| {
+        id != ex.hir_id && tcx.is_parent_of(ex.hir_id, id)
+      });
+      return;
+    }
+
     // Drain nested obligations first to match the most specific node possible.
     hir::intravisit::walk_expr(self, ex);
 
     log::debug!(
       "Visiting expression: {}",
-      self.ctx.tcx.hir().node_to_string(ex.hir_id)
+      self.tcx.hir().node_to_string(ex.hir_id)
     );
 
     match ex.kind {
@@ -151,10 +241,198 @@ impl<'a, 'tcx: 'a> HirVisitor<'tcx> for BinCreator<'a, 'tcx> {
       _ => {}
     }
   }
+
+  fn visit_fn_decl(&mut self, decl: &'tcx hir::FnDecl) {
+    hir::intravisit::walk_fn_decl(self, decl);
+    for ty in decl.inputs {
+      self.drain_nested(ty.hir_id, BinKind::FnSigTy);
+    }
+    if let hir::FnRetTy::Return(ty) = decl.output {
+      self.drain_nested(ty.hir_id, BinKind::FnSigTy);
+    }
+  }
+
+  fn visit_where_predicate(&mut self, predicate: &'tcx hir::WherePredicate) {
+    hir::intravisit::walk_where_predicate(self, predicate);
+    // NOTE: region predicates don't have a `HirId`, but they're
+    // also never the source of a trait obligation.
+    if let hir::WherePredicate::BoundPredicate(bound) = predicate {
+      self.drain_nested(bound.hir_id, BinKind::WhereClause);
+    }
+  }
+
+  fn visit_field_def(&mut self, field: &'tcx hir::FieldDef) {
+    hir::intravisit::walk_field_def(self, field);
+    self.drain_nested(field.ty.hir_id, BinKind::FieldTy);
+    self.drain_nested(field.hir_id, BinKind::FieldTy);
+  }
+
+  fn visit_assoc_type_binding(&mut self, binding: &'tcx hir::TypeBinding) {
+    hir::intravisit::walk_assoc_type_binding(self, binding);
+    self.drain_nested(binding.hir_id, BinKind::AssocBinding);
+  }
+}
+
+// ------------------------------------------------
+
+/// The impl block of `def_id`, either the item itself or its parent.
+fn enclosing_impl(tcx: TyCtxt, def_id: LocalDefId) -> Option<&hir::Impl> {
+  let as_impl = |def_id| match tcx.hir_node_by_def_id(def_id) {
+    hir::Node::Item(hir::Item {
+      kind: hir::ItemKind::Impl(impl_),
+      ..
+    }) => Some(*impl_),
+    _ => None,
+  };
+
+  as_impl(def_id).or_else(|| match tcx.hir_node_by_def_id(def_id) {
+    hir::Node::ImplItem(..) => as_impl(tcx.local_parent(def_id)),
+    _ => None,
+  })
+}
+
+/// Visit the parts of the HIR owner `def_id` outside of its body that
+/// obligations can point into. That is, generics and `where` clauses,
+/// fn signature types, field types and, for associated items, the header
+/// of the enclosing impl or trait.
+pub(crate) fn walk_signature<'tcx, V: HirVisitor<'tcx, Result = ()>>(
+  tcx: TyCtxt<'tcx>,
+  def_id: LocalDefId,
+  v: &mut V,
+) {
+  match tcx.hir_node_by_def_id(def_id) {
+    hir::Node::Item(item) => match item.kind {
+      hir::ItemKind::Fn(sig, generics, _) => {
+        v.visit_generics(generics);
+        v.visit_fn_decl(sig.decl);
+      }
+      hir::ItemKind::Const(ty, generics, _) => {
+        v.visit_generics(generics);
+        v.visit_ty(ty);
+      }
+      hir::ItemKind::Static(ty, ..) => {
+        v.visit_ty(ty);
+      }
+      hir::ItemKind::Struct(ref data, generics)
+      | hir::ItemKind::Union(ref data, generics) => {
+        v.visit_generics(generics);
+        for field in data.fields() {
+          v.visit_field_def(field);
+        }
+      }
+      hir::ItemKind::Enum(ref def, generics) => {
+        v.visit_generics(generics);
+        for field in def.variants.iter().flat_map(|var| var.data.fields()) {
+          v.visit_field_def(field);
+        }
+      }
+      hir::ItemKind::Trait(_, _, generics, bounds, _) => {
+        v.visit_generics(generics);
+        for bound in bounds {
+          v.visit_param_bound(bound);
+        }
+      }
+      hir::ItemKind::Impl(impl_) => {
+        v.visit_generics(impl_.generics);
+        if let Some(trait_ref) = &impl_.of_trait {
+          v.visit_trait_ref(trait_ref);
+        }
+        v.visit_ty(impl_.self_ty);
+      }
+      _ => {}
+    },
+    hir::Node::ImplItem(item) => {
+      v.visit_generics(item.generics);
+      match item.kind {
+        hir::ImplItemKind::Fn(sig, _) => v.visit_fn_decl(sig.decl),
+        hir::ImplItemKind::Const(ty, _) | hir::ImplItemKind::Type(ty) => {
+          v.visit_ty(ty);
+        }
+      }
+      walk_signature(tcx, tcx.local_parent(def_id), v);
+    }
+    hir::Node::TraitItem(item) => {
+      v.visit_generics(item.generics);
+      match item.kind {
+        hir::TraitItemKind::Fn(sig, _) => v.visit_fn_decl(sig.decl),
+        hir::TraitItemKind::Const(ty, _) => v.visit_ty(ty),
+        hir::TraitItemKind::Type(bounds, ty) => {
+          for bound in bounds {
+            v.visit_param_bound(bound);
+          }
+          if let Some(ty) = ty {
+            v.visit_ty(ty);
+          }
+        }
+      }
+      walk_signature(tcx, tcx.local_parent(def_id), v);
+    }
+    _ => {}
+  }
+}
+
+/// The associated types defined by an impl block, or defaulted by a trait.
+/// Their bounds are checked together with the item.
+fn assoc_tys(
+  tcx: TyCtxt,
+  def_id: LocalDefId,
+) -> impl Iterator<Item = &hir::Ty> {
+  let hir = tcx.hir();
+  let (impl_items, trait_items) = match tcx.hir_node_by_def_id(def_id) {
+    hir::Node::Item(hir::Item {
+      kind: hir::ItemKind::Impl(impl_),
+      ..
+    }) => (impl_.items, &[][..]),
+    hir::Node::Item(hir::Item {
+      kind: hir::ItemKind::Trait(.., items),
+      ..
+    }) => (&[][..], *items),
+    _ => (&[][..], &[][..]),
+  };
+
+  let impl_tys = impl_items.iter().filter_map(move |item_ref| {
+    match hir.impl_item(item_ref.id).kind {
+      hir::ImplItemKind::Type(ty) => Some(ty),
+      _ => None,
+    }
+  });
+  let trait_tys = trait_items.iter().filter_map(move |item_ref| {
+    match hir.trait_item(item_ref.id).kind {
+      hir::TraitItemKind::Type(_, ty) => ty,
+      _ => None,
+    }
+  });
+
+  impl_tys.chain(trait_tys)
+}
+
+/// Visit the signature of an impl block, trait or type definition, see
+/// `walk_signature`, along with its associated types.
+pub(crate) fn walk_item<'tcx, V: HirVisitor<'tcx, Result = ()>>(
+  tcx: TyCtxt<'tcx>,
+  def_id: LocalDefId,
+  v: &mut V,
+) {
+  walk_signature(tcx, def_id, v);
+  for ty in assoc_tys(tcx, def_id) {
+    v.visit_ty(ty);
+  }
 }
 
 // ------------------------------------------------
 
+/// Find the `HirId` of the node that is the "most enclosing" of the span
+/// within an impl block, trait or type definition, see `walk_item`.
+pub fn find_most_enclosing_item_node(
+  tcx: TyCtxt,
+  def_id: LocalDefId,
+  span: Span,
+) -> Option<HirId> {
+  let mut node_finder = FindNodeBySpan::new(tcx, span);
+  walk_item(tcx, def_id, &mut node_finder);
+  node_finder.result.map(|t| t.0)
+}
+
 /// Find the `HirId` of the node that is the "most enclosing" of the span.
 pub fn find_most_enclosing_node(
   tcx: TyCtxt,
@@ -171,6 +449,7 @@ pub fn find_most_enclosing_node(
   );
 
   node_finder.visit_body(hir.body(body_id));
+  walk_signature(tcx, hir.body_owner_def_id(body_id), &mut node_finder);
   node_finder
     .result
     // NOTE: there should always be an enclosing body somewhere, this could be an expect
@@ -247,5 +526,23 @@ impl<'tcx> HirVisitor<'tcx> for FindNodeBySpan<'tcx> {
     [visit_expr_field, walk_expr_field, hir::ExprField],
     [visit_ty, walk_ty, hir::Ty],
     [visit_generic_param, walk_generic_param, hir::GenericParam],
+    [visit_field_def, walk_field_def, hir::FieldDef],
+    [visit_assoc_type_binding, walk_assoc_type_binding, hir::TypeBinding],
+  }
+
+  fn visit_where_predicate(&mut self, predicate: &'tcx hir::WherePredicate) {
+    hir::intravisit::walk_where_predicate(self, predicate);
+    if let hir::WherePredicate::BoundPredicate(bound) = predicate
+      && self.is_better_match(bound.span)
+    {
+      self.result = Some((bound.hir_id, bound.span));
+    }
+  }
+
+  fn visit_trait_ref(&mut self, trait_ref: &'tcx hir::TraitRef) {
+    hir::intravisit::walk_trait_ref(self, trait_ref);
+    if self.is_better_match(trait_ref.path.span) {
+      self.result = Some((trait_ref.hir_ref_id, trait_ref.path.span));
+    }
   }
 }
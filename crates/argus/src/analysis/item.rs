@@ -0,0 +1,500 @@
+//! Analysis of impl blocks, trait definitions and type definitions.
+//!
+//! Obligations checked by `check_well_formed` and coherence never go through
+//! `inspect_typeck`, an impl rejected because a supertrait or associated type
+//! bound doesn't hold has no body to inspect. Instead we rebuild the
+//! obligations as rustc's `wfcheck` does and evaluate them ourselves.
+
+use anyhow::{bail, Result};
+use argus_ext::{
+  infer::InferCtxtExt as ArgusInferCtxtExt,
+  ty::{retain_error_sources, EvaluationResultExt},
+  utils::SpanExt as ArgusSpanExt,
+};
+use argus_ser as ser;
+use index_vec::IndexVec;
+use indexmap::IndexSet;
+use rustc_hir::{self as hir, def::DefKind, def_id::LocalDefId, LangItem};
+use rustc_infer::{
+  infer::{InferCtxt, TyCtxtInferExt},
+  traits::{
+    Obligation as RustcObligation, ObligationCause, PredicateObligation,
+  },
+};
+use rustc_middle::ty::{self, fold::BottomUpFolder, TyCtxt, TypeFoldable};
+use rustc_span::Span;
+use rustc_trait_selection::traits::{solve::Goal, wf};
+use rustc_utils::source_map::{range::CharRange, span::SpanExt};
+
+use super::{
+  hir::{self as item_hir, Bin},
+  transform::kind_of_bin,
+  INCLUDE_SUCCESSES,
+};
+use crate::{
+  ext::InferCtxtExt,
+  proof_tree::{serialize::try_serialize, SerializedTree},
+  tls::{self, FullObligationData},
+  types::{
+    intermediate::{Forgettable, FullData},
+    AmbiguityError, CoherenceError, CoherenceErrorKind, Expr, ExprIdx,
+    Obligation, ObligationHash, ObligationIdx, ObligationsInItem, TraitError,
+  },
+};
+
+pub(crate) fn item_data(
+  tcx: TyCtxt,
+  def_id: LocalDefId,
+) -> Result<(Forgettable<FullData>, ObligationsInItem)> {
+  let hir::Node::Item(item) = tcx.hir_node_by_def_id(def_id) else {
+    bail!("{def_id:?} is not an item");
+  };
+  tls::reset_interned_tys();
+
+  let infcx = tcx.infer_ctxt().with_next_trait_solver(true).build();
+  let obligations = match item.kind {
+    hir::ItemKind::Impl(impl_) => impl_obligations(&infcx, item, impl_),
+    hir::ItemKind::Trait(..) => where_clause_obligations(&infcx, def_id),
+    hir::ItemKind::Struct(..)
+    | hir::ItemKind::Enum(..)
+    | hir::ItemKind::Union(..) => adt_obligations(&infcx, def_id),
+    _ => bail!("only impl blocks, traits and ADTs are analyzed as items"),
+  };
+  let obligations = obligations
+    .into_iter()
+    .flat_map(|obligation| expand_well_formed(&infcx, obligation));
+
+  let include_successes = INCLUDE_SUCCESSES.copied().unwrap_or(false);
+  let full_data = obligations
+    .into_iter()
+    .map(|obligation| {
+      let result = infcx.evaluate_obligation(&obligation);
+      let hash = infcx.bless_fulfilled(&obligation, result).hash;
+      FullObligationData {
+        infcx: infcx.fork(),
+        hash,
+        obligation,
+        result,
+      }
+    })
+    .filter(|fdata| include_successes || !fdata.result.is_yes())
+    .collect::<IndexVec<_, _>>();
+  let full_data = FullData::new(full_data);
+
+  let item_span = local_item_span(tcx, def_id);
+  let to_local = |span: Span| span.as_local(item_span).unwrap_or(span);
+
+  let mut obligations = IndexVec::<ObligationIdx, _>::default();
+  let mut hir_ids = vec![];
+  for fdata in full_data.iter() {
+    let cause_span = to_local(fdata.obligation.cause.span);
+    let cause_span = if cause_span.is_dummy() {
+      item_span
+    } else {
+      cause_span
+    };
+    let range = CharRange::from_span(cause_span, tcx.sess.source_map())
+      .or_else(|_| CharRange::from_span(item_span, tcx.sess.source_map()))
+      .expect("couldn't get item range");
+
+    let hir_id =
+      item_hir::find_most_enclosing_item_node(tcx, def_id, cause_span)
+        .unwrap_or_else(|| tcx.local_def_id_to_hir_id(def_id));
+    hir_ids.push(hir_id);
+
+    let blessed = fdata.infcx.bless_fulfilled(&fdata.obligation, fdata.result);
+    obligations.push(fdata.infcx.erase_non_local_data(range, hir_id, blessed));
+  }
+
+  let bins = item_hir::associate_item_obligations(tcx, def_id, &hir_ids);
+  let (exprs, trait_errors, ambiguity_errors) =
+    sort_bins(tcx, item_span, bins, &full_data, &obligations);
+
+  let coherence_errors = coherence_errors(tcx, def_id, item_span);
+
+  let oii = ObligationsInItem::new(
+    (&infcx, def_id.to_def_id()),
+    CharRange::from_span(item_span, tcx.sess.source_map())
+      .expect("couldn't get item range"),
+    coherence_errors,
+    ambiguity_errors,
+    trait_errors,
+    obligations,
+    exprs,
+  );
+
+  Ok((Forgettable::new(full_data), oii))
+}
+
+/// Turn bins into expressions, an expression with failing obligations is a
+/// trait error. Unlike bodies, rustc doesn't report these errors to us.
+fn sort_bins<'tcx>(
+  tcx: TyCtxt<'tcx>,
+  item_span: Span,
+  bins: Vec<Bin>,
+  full_data: &FullData<'tcx>,
+  obligations: &IndexVec<ObligationIdx, Obligation>,
+) -> (
+  IndexVec<ExprIdx, Expr>,
+  Vec<TraitError>,
+  IndexSet<AmbiguityError>,
+) {
+  let to_local = |span: Span| span.as_local(item_span).unwrap_or(span);
+
+  let mut exprs = IndexVec::default();
+  let mut trait_errors = vec![];
+  let mut ambiguity_errors = IndexSet::default();
+  for bin in bins {
+    let mut idxs = bin.obligations;
+    retain_error_sources(
+      &mut idxs,
+      |&i| full_data.get(i.into()).result,
+      |&i| full_data.get(i.into()).obligation.predicate,
+      |_| tcx,
+    );
+
+    let span = to_local(tcx.hir().span(bin.hir_id));
+    let Ok(range) = CharRange::from_span(span, tcx.sess.source_map()) else {
+      continue;
+    };
+    let idxs = idxs
+      .into_iter()
+      .map(ObligationIdx::from)
+      .collect::<Vec<_>>();
+
+    let failures = idxs
+      .iter()
+      .filter(|&&idx| {
+        let obl = &obligations[idx];
+        obl.result.is_err() && obl.is_necessary
+      })
+      .map(|&idx| obligations[idx].hash)
+      .collect::<Vec<_>>();
+    let is_ambiguous =
+      idxs.iter().any(|&idx| obligations[idx].result.is_maybe());
+
+    let idx = exprs.push(Expr {
+      range,
+      snippet: span.sanitized_snippet(tcx.sess.source_map()),
+      obligations: idxs,
+      kind: kind_of_bin(&bin.kind),
+      is_body: false,
+    });
+
+    if !failures.is_empty() {
+      trait_errors.push(TraitError {
+        idx,
+        range,
+        hashes: failures,
+      });
+    } else if is_ambiguous {
+      ambiguity_errors.insert(AmbiguityError { idx, range });
+    }
+  }
+
+  (exprs, trait_errors, ambiguity_errors)
+}
+
+pub(crate) fn pick_tree(
+  def_id: LocalDefId,
+  hash: ObligationHash,
+  data: &FullData,
+) -> Result<SerializedTree> {
+  let Some(fdata) = data.iter().find(|fdata| fdata.hash == hash) else {
+    bail!("could not find tree for {hash:?}");
+  };
+
+  let goal = Goal {
+    predicate: fdata.obligation.predicate,
+    param_env: fdata.obligation.param_env,
+  };
+
+  try_serialize(
+    goal,
+    fdata.result,
+    fdata.obligation.cause.span,
+    &fdata.infcx,
+    def_id.to_def_id(),
+  )
+}
+
+/// The span of the item in the local crate, derived impls point to the
+/// derive attribute.
+fn local_item_span(tcx: TyCtxt, def_id: LocalDefId) -> Span {
+  let mut span = tcx.hir().span_with_body(tcx.local_def_id_to_hir_id(def_id));
+  while span.from_expansion() {
+    span = span.source_callsite();
+  }
+  span
+}
+
+// --------------------------------
+// Obligation collection, mirroring `rustc_hir_analysis::check::wfcheck`
+
+fn impl_obligations<'tcx>(
+  infcx: &InferCtxt<'tcx>,
+  item: &'tcx hir::Item<'tcx>,
+  impl_: &'tcx hir::Impl<'tcx>,
+) -> Vec<PredicateObligation<'tcx>> {
+  let tcx = infcx.tcx;
+  let def_id = item.owner_id.def_id;
+  let param_env = tcx.param_env(def_id);
+
+  let mut obligations = if let Some(hir_trait_ref) = &impl_.of_trait {
+    let trait_ref = tcx.impl_trait_ref(def_id).unwrap().instantiate_identity();
+    let trait_span = hir_trait_ref.path.span;
+    let trait_pred = ty::TraitPredicate {
+      trait_ref,
+      polarity: ty::PredicatePolarity::Positive,
+    };
+
+    let mut obligations = wf::trait_obligations(
+      infcx, param_env, def_id, trait_pred, trait_span, item,
+    );
+
+    // Predicates on the self type are better pointed to by the self type.
+    for obligation in &mut obligations {
+      let self_ty = obligation
+        .predicate
+        .as_trait_clause()
+        .map(|p| p.skip_binder().self_ty())
+        .or_else(|| {
+          obligation
+            .predicate
+            .as_projection_clause()
+            .map(|p| p.skip_binder().self_ty())
+        });
+
+      if obligation.cause.span == trait_span
+        && self_ty == Some(trait_ref.self_ty())
+      {
+        obligation.cause.span = impl_.self_ty.span;
+      }
+    }
+
+    obligations.extend(assoc_type_obligations(infcx, def_id, trait_ref));
+    obligations
+  } else {
+    let self_ty = tcx.type_of(def_id).instantiate_identity();
+    vec![RustcObligation::new(
+      tcx,
+      ObligationCause::misc(impl_.self_ty.span, def_id),
+      param_env,
+      ty::ClauseKind::WellFormed(self_ty.into()),
+    )]
+  };
+
+  obligations.extend(where_clause_obligations(infcx, def_id));
+  obligations
+}
+
+fn where_clause_obligations<'tcx>(
+  infcx: &InferCtxt<'tcx>,
+  def_id: LocalDefId,
+) -> Vec<PredicateObligation<'tcx>> {
+  let tcx = infcx.tcx;
+  let param_env = tcx.param_env(def_id);
+  tcx
+    .predicates_of(def_id)
+    .instantiate_identity(tcx)
+    .into_iter()
+    .flat_map(|(clause, span)| {
+      wf::clause_obligations(infcx, param_env, def_id, clause, span)
+    })
+    .collect()
+}
+
+/// The bounds required by a `WellFormed` obligation, e.g., `Local: Display`
+/// for `WF(Shown<Local>)`. Unlike the `WellFormed` goal these are trait
+/// predicates worth showing, pointing to the type as written.
+fn expand_well_formed<'tcx>(
+  infcx: &InferCtxt<'tcx>,
+  obligation: PredicateObligation<'tcx>,
+) -> Vec<PredicateObligation<'tcx>> {
+  let Some(ty::ClauseKind::WellFormed(arg)) = obligation
+    .predicate
+    .as_clause()
+    .map(|clause| clause.kind().skip_binder())
+  else {
+    return vec![obligation];
+  };
+
+  wf::obligations(
+    infcx,
+    obligation.param_env,
+    obligation.cause.body_id,
+    obligation.recursion_depth,
+    arg,
+    obligation.cause.span,
+  )
+  .unwrap_or_else(|| vec![obligation])
+}
+
+/// Field types must be well-formed, and sized unless it's the last field of
+/// a struct. See `wfcheck::check_type_defn`.
+fn adt_obligations<'tcx>(
+  infcx: &InferCtxt<'tcx>,
+  def_id: LocalDefId,
+) -> Vec<PredicateObligation<'tcx>> {
+  let tcx = infcx.tcx;
+  let param_env = tcx.param_env(def_id);
+  let adt = tcx.adt_def(def_id);
+  let sized = tcx.require_lang_item(LangItem::Sized, None);
+
+  let mut obligations = vec![];
+  for variant in adt.variants() {
+    let unsized_tail = adt
+      .is_struct()
+      .then(|| variant.fields.len().checked_sub(1))
+      .flatten();
+    for (i, field) in variant.fields.iter().enumerate() {
+      let hir::Node::Field(field_def) =
+        tcx.hir_node_by_def_id(field.did.expect_local())
+      else {
+        continue;
+      };
+
+      let ty = tcx.type_of(field.did).instantiate_identity();
+      let cause = ObligationCause::misc(field_def.ty.span, def_id);
+      obligations.push(RustcObligation::new(
+        tcx,
+        cause.clone(),
+        param_env,
+        ty::ClauseKind::WellFormed(ty.into()),
+      ));
+      if unsized_tail != Some(i) {
+        obligations.push(RustcObligation::new(
+          tcx,
+          cause,
+          param_env,
+          ty::TraitRef::new(tcx, sized, [ty]),
+        ));
+      }
+    }
+  }
+
+  obligations.extend(where_clause_obligations(infcx, def_id));
+  obligations
+}
+
+/// The bounds on each associated type of the trait, instantiated with the
+/// type given by the impl. See `compare_impl_item::check_type_bounds`.
+fn assoc_type_obligations<'tcx>(
+  infcx: &InferCtxt<'tcx>,
+  impl_def_id: LocalDefId,
+  trait_ref: ty::TraitRef<'tcx>,
+) -> Vec<PredicateObligation<'tcx>> {
+  let tcx = infcx.tcx;
+  let mut obligations = vec![];
+  for &impl_item_id in tcx.associated_item_def_ids(impl_def_id) {
+    let assoc = tcx.associated_item(impl_item_id);
+    let Some(trait_item_id) = assoc.trait_item_def_id else {
+      continue;
+    };
+
+    // Synthesized items, e.g., from return position `impl Trait`, have no HIR.
+    let hir::Node::ImplItem(hir::ImplItem {
+      kind: hir::ImplItemKind::Type(hir_ty),
+      ..
+    }) = tcx.hir_node_by_def_id(impl_item_id.expect_local())
+    else {
+      continue;
+    };
+
+    let args = ty::GenericArgs::identity_for_item(tcx, impl_item_id)
+      .rebase_onto(tcx, impl_def_id.to_def_id(), trait_ref.args);
+    let alias = ty::Ty::new_projection(tcx, trait_item_id, args);
+    let impl_ty = tcx.type_of(impl_item_id).instantiate_identity();
+    let param_env = tcx.param_env(impl_item_id);
+
+    // Show the bounds on the impl's type rather than the projection.
+    let mut folder = BottomUpFolder {
+      tcx,
+      ty_op: |ty| if ty == alias { impl_ty } else { ty },
+      lt_op: |lt| lt,
+      ct_op: |ct| ct,
+    };
+
+    for (bound, _) in tcx
+      .explicit_item_bounds(trait_item_id)
+      .iter_instantiated_copied(tcx, args)
+    {
+      obligations.push(RustcObligation::new(
+        tcx,
+        ObligationCause::misc(hir_ty.span, impl_def_id),
+        param_env,
+        bound.fold_with(&mut folder),
+      ));
+    }
+  }
+
+  obligations
+}
+
+// --------------------------------
+// Coherence
+
+fn coherence_errors(
+  tcx: TyCtxt,
+  def_id: LocalDefId,
+  item_span: Span,
+) -> Vec<CoherenceError> {
+  if !matches!(tcx.def_kind(def_id), DefKind::Impl { .. }) {
+    return vec![];
+  }
+
+  let Some(trait_ref) = tcx.impl_trait_ref(def_id) else {
+    return vec![];
+  };
+
+  // Coherence is checked for all impls of a trait at once.
+  if tcx
+    .ensure()
+    .coherent_trait(trait_ref.skip_binder().def_id)
+    .is_ok()
+  {
+    return vec![];
+  }
+
+  let Ok(range) = CharRange::from_span(
+    tcx
+      .def_span(def_id)
+      .as_local(item_span)
+      .unwrap_or(item_span),
+    tcx.sess.source_map(),
+  ) else {
+    return vec![];
+  };
+
+  if tcx.ensure().orphan_check_impl(def_id).is_err() {
+    return vec![CoherenceError {
+      kind: CoherenceErrorKind::Orphan,
+      range,
+      conflicting_impls: vec![],
+    }];
+  }
+
+  let infcx = tcx.infer_ctxt().with_next_trait_solver(true).build();
+  let trait_ref = ty::Binder::dummy(trait_ref.instantiate_identity());
+  let conflicting_impls = infcx
+    .overlapping_impls(trait_ref)
+    .into_iter()
+    .filter(|&other| other != def_id.to_def_id())
+    .filter_map(|other| ser::get_opt_impl_header(tcx, other))
+    .map(|header| {
+      tls::unsafe_access_interner(|ty_interner| {
+        ser::to_value_expect(&infcx, ty_interner, &header)
+      })
+    })
+    .collect::<Vec<_>>();
+
+  if conflicting_impls.is_empty() {
+    return vec![];
+  }
+
+  vec![CoherenceError {
+    kind: CoherenceErrorKind::Overlap,
+    range,
+    conflicting_impls,
+  }]
+}
@@ -0,0 +1,533 @@
+//! Explanations of method resolution.
+//!
+//! A failed method call is reported as "no method found" without saying
+//! which receiver types were tried or why the methods of a given name
+//! didn't apply. Builder APIs (typestate) make this especially painful,
+//! the method exists but its impl bounds don't hold in the current state.
+//!
+//! We replay a simplified form of rustc's method probe: autoderef the
+//! receiver, try each step by value and autoref'd against the inherent
+//! methods and the methods of traits in scope, then evaluate the bounds
+//! of the impls that would provide them. Traits not in scope are only
+//! reported when they would apply to the receiver.
+
+use argus_ext::{
+  infer::InferCtxtExt as ArgusInferCtxtExt, ty::EvaluationResultExt,
+};
+use argus_ser as ser;
+use indexmap::IndexSet;
+use rustc_data_structures::fx::FxHashSet as HashSet;
+use rustc_hir::{
+  self as hir,
+  def_id::DefId,
+  intravisit::{self, Visitor as HirVisitor},
+  BodyId, HirId,
+};
+use rustc_infer::{
+  infer::{
+    canonical::{OriginalQueryValues, QueryResponse},
+    BoundRegionConversionTime, InferCtxt, TyCtxtInferExt,
+  },
+  traits::{Obligation, ObligationCause},
+};
+use rustc_middle::{
+  hir::nested_filter,
+  ty::{
+    self,
+    fast_reject::{simplify_type, TreatParams},
+    GenericArgs, Ty, TyCtxt, TypeVisitableExt, TypeckResults,
+  },
+};
+use rustc_span::{symbol::Ident, Span};
+use rustc_trait_selection::traits::ObligationCtxt;
+use rustc_utils::source_map::range::CharRange;
+use serde::Serialize;
+
+use crate::{
+  tls,
+  types::{
+    Autoref, EvaluationResult, ExtensionCandidates, MethodCandidate,
+    MethodCandidateKind, MethodLookup, ReceiverMatch, ReceiverStep,
+  },
+};
+
+const AUTOREFS: [Autoref; 3] =
+  [Autoref::ByValue, Autoref::Ref, Autoref::RefMut];
+
+/// Explain method resolution at the method calls of a body that either
+/// failed to resolve, or were binned together with errors (`erroneous`).
+pub(crate) fn method_lookups<'tcx>(
+  tcx: TyCtxt<'tcx>,
+  body_id: BodyId,
+  typeck_results: &'tcx TypeckResults<'tcx>,
+  erroneous: &HashSet<HirId>,
+) -> Vec<MethodLookup> {
+  let mut finder = MethodCallFinder {
+    tcx,
+    calls: Vec::default(),
+  };
+  finder.visit_body(tcx.hir().body(body_id));
+
+  let source_map = tcx.sess.source_map();
+  let param_env = tcx.param_env(tcx.hir().body_owner_def_id(body_id));
+  finder
+    .calls
+    .into_iter()
+    .filter_map(|(call, segment, receiver, span)| {
+      let resolved = typeck_results.type_dependent_def_id(call);
+      if resolved.is_some()
+        && !erroneous.contains(&call)
+        && !erroneous.contains(&segment.hir_id)
+      {
+        return None;
+      }
+
+      let receiver_ty = typeck_results.expr_ty_opt(receiver)?;
+      if receiver_ty.references_error() {
+        return None;
+      }
+
+      let span = argus_ext::ty::TyCtxtExt::to_local(&tcx, body_id, span);
+      let range = CharRange::from_span(span, source_map).ok()?;
+      let infcx = tcx.infer_ctxt().with_next_trait_solver(true).build();
+      let probe = Probe::new(&infcx, param_env, span, receiver_ty);
+      Some(probe.lookup(call, segment.ident, resolved, range))
+    })
+    .collect()
+}
+
+struct MethodCallFinder<'tcx> {
+  tcx: TyCtxt<'tcx>,
+  calls: Vec<(
+    HirId,
+    &'tcx hir::PathSegment<'tcx>,
+    &'tcx hir::Expr<'tcx>,
+    Span,
+  )>,
+}
+
+impl<'tcx> HirVisitor<'tcx> for MethodCallFinder<'tcx> {
+  type NestedFilter = nested_filter::OnlyBodies;
+
+  fn nested_visit_map(&mut self) -> Self::Map {
+    self.tcx.hir()
+  }
+
+  // Closures share the typeck results of their parent, other nested
+  // bodies are analyzed on their own.
+  fn visit_nested_body(&mut self, id: BodyId) {
+    let owner = self.tcx.hir().body_owner_def_id(id);
+    if self.tcx.is_typeck_child(owner.to_def_id()) {
+      self.visit_body(self.tcx.hir().body(id));
+    }
+  }
+
+  fn visit_expr(&mut self, ex: &'tcx hir::Expr<'tcx>) {
+    if let hir::ExprKind::MethodCall(segment, receiver, _, span) = ex.kind {
+      self.calls.push((ex.hir_id, segment, receiver, span));
+    }
+    intravisit::walk_expr(self, ex);
+  }
+}
+
+struct Probe<'a, 'tcx> {
+  infcx: &'a InferCtxt<'tcx>,
+  param_env: ty::ParamEnv<'tcx>,
+  span: Span,
+  steps: Vec<(Ty<'tcx>, ReceiverStep)>,
+}
+
+impl<'a, 'tcx: 'a> Probe<'a, 'tcx> {
+  fn new(
+    infcx: &'a InferCtxt<'tcx>,
+    param_env: ty::ParamEnv<'tcx>,
+    span: Span,
+    receiver_ty: Ty<'tcx>,
+  ) -> Self {
+    let tcx = infcx.tcx;
+    let goal = infcx.canonicalize_query(
+      param_env.and(receiver_ty),
+      &mut OriginalQueryValues::default(),
+    );
+    let steps = tcx
+      .method_autoderef_steps(goal)
+      .steps
+      .iter()
+      .map(|step| {
+        let (QueryResponse { value: ty, .. }, _) =
+          infcx.instantiate_canonical(span, &step.self_ty);
+        let desc = ReceiverStep {
+          ty: serialize(infcx, &TyWrapper(ty)),
+          autoderefs: step.autoderefs,
+          from_unsafe_deref: step.from_unsafe_deref,
+          unsize: step.unsize,
+        };
+        (ty, desc)
+      })
+      .collect();
+
+    Probe {
+      infcx,
+      param_env,
+      span,
+      steps,
+    }
+  }
+
+  fn lookup(
+    self,
+    call: HirId,
+    name: Ident,
+    resolved: Option<DefId>,
+    range: CharRange,
+  ) -> MethodLookup {
+    let tcx = self.infcx.tcx;
+    let in_scope = tcx
+      .in_scope_traits(call)
+      .into_iter()
+      .flatten()
+      .map(|candidate| candidate.def_id)
+      .collect::<HashSet<_>>();
+
+    let inherent_impls = self
+      .steps
+      .iter()
+      .flat_map(|(ty, _)| inherent_impls(tcx, *ty))
+      .collect::<IndexSet<_>>();
+
+    let candidates = inherent_impls
+      .into_iter()
+      .map(|impl_| (MethodCandidateKind::Inherent, impl_))
+      .chain(
+        in_scope
+          .iter()
+          .map(|&trait_| (MethodCandidateKind::Trait, trait_)),
+      )
+      .flat_map(|(kind, container)| {
+        methods_named(tcx, container, name).map(move |m| (kind, m))
+      })
+      .map(|(kind, method)| self.candidate(kind, method))
+      .collect();
+
+    let module = tcx.parent_module(call).to_def_id();
+    let extension_candidates = tcx
+      .all_traits()
+      .filter(|trait_| {
+        !in_scope.contains(trait_) && is_nameable_from(tcx, *trait_, module)
+      })
+      .filter_map(|trait_| methods_named(tcx, trait_, name).next())
+      .filter_map(|method| self.applicable_trait_ref(method))
+      .collect();
+
+    let resolved =
+      resolved.map(|def_id| serialize(self.infcx, &ser::PathDefNoArgs(def_id)));
+    let steps = self.steps.into_iter().map(|(_, desc)| desc).collect();
+
+    MethodLookup {
+      range,
+      name: name.to_string(),
+      resolved,
+      steps,
+      candidates,
+      extension_candidates: ExtensionCandidates::new(
+        self.infcx,
+        extension_candidates,
+      ),
+    }
+  }
+
+  /// Rustc keeps probing when the bounds of a candidate don't hold at a
+  /// step, report the first step where the candidate applies, otherwise
+  /// the first step its receiver accepts with the bounds left unmet.
+  fn candidate(
+    &self,
+    kind: MethodCandidateKind,
+    method: DefId,
+  ) -> MethodCandidate {
+    let tcx = self.infcx.tcx;
+    let container = tcx.parent(method);
+    let (receiver, unmet_bounds) = self.infcx.probe(|_| {
+      let (receiver_ty, args) = self.instantiate_method(method);
+      let args = args.truncate_to(tcx, tcx.generics_of(container));
+      let mut first_match = None;
+      for m in self.receiver_matches(receiver_ty) {
+        let unmet_bounds = self.infcx.probe(|_| {
+          self.relate_receiver(receiver_ty, self.adjusted(m));
+          match kind {
+            MethodCandidateKind::Inherent => {
+              self.unmet_impl_bounds(container, args)
+            }
+            MethodCandidateKind::Trait => {
+              self.unmet_trait_bounds(ty::TraitRef::new(tcx, container, args))
+            }
+          }
+        });
+
+        if unmet_bounds.is_empty() {
+          return (Some(m), unmet_bounds);
+        }
+        first_match.get_or_insert((m, unmet_bounds));
+      }
+      first_match.map_or((None, vec![]), |(m, unmet)| (Some(m), unmet))
+    });
+
+    MethodCandidate {
+      kind,
+      method: serialize(self.infcx, &ser::PathDefNoArgs(method)),
+      receiver,
+      unmet_bounds,
+    }
+  }
+
+  /// The trait of `method`, with `Self` as the receiver, if the trait
+  /// may be implemented for the receiver at some step.
+  fn applicable_trait_ref(&self, method: DefId) -> Option<ty::TraitRef<'tcx>> {
+    let tcx = self.infcx.tcx;
+    let trait_ = tcx.parent(method);
+    let (receiver_ty, args) = self.instantiate_method(method);
+    self
+      .receiver_matches(receiver_ty)
+      .into_iter()
+      .find_map(|m| {
+        self.infcx.probe(|_| {
+          self.relate_receiver(receiver_ty, self.adjusted(m));
+          let trait_ref = ty::TraitRef::from_method(tcx, trait_, args);
+          if self.evaluate(trait_ref).is_no() {
+            return None;
+          }
+
+          // Inference variables don't survive the probe, keep only the
+          // receiver and print the trait with its own parameters.
+          let self_ty =
+            self.infcx.resolve_vars_if_possible(trait_ref.self_ty());
+          let self_ty = if self_ty.has_infer() {
+            self.steps[m.step].0
+          } else {
+            self_ty
+          };
+          let identity = GenericArgs::identity_for_item(tcx, trait_);
+          Some(ty::TraitRef::new(
+            tcx,
+            trait_,
+            std::iter::once(self_ty.into()).chain(identity.iter().skip(1)),
+          ))
+        })
+      })
+  }
+
+  /// The receiver type of `method`, `self`, with fresh variables for all
+  /// generics of the method and its container.
+  fn instantiate_method(
+    &self,
+    method: DefId,
+  ) -> (Ty<'tcx>, ty::GenericArgsRef<'tcx>) {
+    let tcx = self.infcx.tcx;
+    let args = self.infcx.fresh_args_for_item(self.span, method);
+    let sig = self.infcx.instantiate_binder_with_fresh_vars(
+      self.span,
+      BoundRegionConversionTime::FnCall,
+      tcx.fn_sig(method).instantiate(tcx, args),
+    );
+    (sig.inputs()[0], args)
+  }
+
+  fn adjusted(&self, m: ReceiverMatch) -> Ty<'tcx> {
+    let tcx = self.infcx.tcx;
+    let ty = self.steps[m.step].0;
+    let region = tcx.lifetimes.re_erased;
+    match m.autoref {
+      Autoref::ByValue => ty,
+      Autoref::Ref => Ty::new_imm_ref(tcx, region, ty),
+      Autoref::RefMut => Ty::new_mut_ref(tcx, region, ty),
+    }
+  }
+
+  /// Steps, and adjustments of them, accepted by `receiver_ty` in
+  /// probing order.
+  fn receiver_matches(&self, receiver_ty: Ty<'tcx>) -> Vec<ReceiverMatch> {
+    (0 .. self.steps.len())
+      .flat_map(|step| {
+        AUTOREFS
+          .into_iter()
+          .map(move |autoref| ReceiverMatch { step, autoref })
+      })
+      .filter(move |&m| {
+        self
+          .infcx
+          .probe(|_| self.relate_receiver(receiver_ty, self.adjusted(m)))
+      })
+      .collect()
+  }
+
+  fn relate_receiver(&self, receiver_ty: Ty<'tcx>, self_ty: Ty<'tcx>) -> bool {
+    let ocx = ObligationCtxt::new(self.infcx);
+    ocx
+      .sup(
+        &ObligationCause::dummy(),
+        self.param_env,
+        receiver_ty,
+        self_ty,
+      )
+      .is_ok()
+      && ocx.select_where_possible().is_empty()
+  }
+
+  fn evaluate(
+    &self,
+    predicate: impl ty::Upcast<TyCtxt<'tcx>, ty::Predicate<'tcx>>,
+  ) -> EvaluationResult {
+    let obligation = Obligation::new(
+      self.infcx.tcx,
+      ObligationCause::dummy(),
+      self.param_env,
+      predicate,
+    );
+    self.infcx.evaluate_obligation(&obligation)
+  }
+
+  /// Where clauses of the impl that don't hold, serialized as the
+  /// variables of `args` only live within the current probe.
+  fn unmet_impl_bounds(
+    &self,
+    impl_def_id: DefId,
+    args: ty::GenericArgsRef<'tcx>,
+  ) -> Vec<ser::Encoded> {
+    let tcx = self.infcx.tcx;
+    tcx
+      .predicates_of(impl_def_id)
+      .instantiate(tcx, args)
+      .predicates
+      .into_iter()
+      .filter(|clause| self.evaluate(*clause).is_no())
+      .map(|clause| {
+        let predicate = self.infcx.resolve_vars_if_possible(clause);
+        serialize(self.infcx, &PredicateWrapper(predicate.as_predicate()))
+      })
+      .collect()
+  }
+
+  /// Where clauses of the impls that could provide `trait_ref`, if it
+  /// doesn't hold. When no impl header matches the bound itself is unmet.
+  fn unmet_trait_bounds(
+    &self,
+    trait_ref: ty::TraitRef<'tcx>,
+  ) -> Vec<ser::Encoded> {
+    let tcx = self.infcx.tcx;
+    if !self.evaluate(trait_ref).is_no() {
+      return vec![];
+    }
+
+    let impls = self
+      .infcx
+      .unifying_impls(self.param_env, ty::Binder::dummy(trait_ref));
+    if impls.is_empty() {
+      let predicate = self.infcx.resolve_vars_if_possible(trait_ref);
+      let predicate: ty::Predicate<'tcx> = ty::Upcast::upcast(predicate, tcx);
+      return vec![serialize(self.infcx, &PredicateWrapper(predicate))];
+    }
+
+    impls
+      .into_iter()
+      .flat_map(|impl_def_id| {
+        self.infcx.probe(|_| {
+          let args = self.infcx.fresh_args_for_item(self.span, impl_def_id);
+          let impl_ref = tcx
+            .impl_trait_ref(impl_def_id)
+            .expect("impl of a trait")
+            .instantiate(tcx, args);
+          let ocx = ObligationCtxt::new(self.infcx);
+          if ocx
+            .eq(
+              &ObligationCause::dummy(),
+              self.param_env,
+              trait_ref,
+              impl_ref,
+            )
+            .is_err()
+          {
+            return vec![];
+          }
+          self.unmet_impl_bounds(impl_def_id, args)
+        })
+      })
+      .collect()
+  }
+}
+
+#[derive(Serialize)]
+struct TyWrapper<'tcx>(#[serde(with = "ser::ty::TyDef")] Ty<'tcx>);
+
+#[derive(Serialize)]
+struct PredicateWrapper<'tcx>(
+  #[serde(with = "ser::ty::PredicateDef")] ty::Predicate<'tcx>,
+);
+
+fn serialize<T: Serialize>(infcx: &InferCtxt, value: &T) -> ser::Encoded {
+  tls::unsafe_access_interner(|ty_interner| {
+    ser::to_value_expect(infcx, ty_interner, value)
+  })
+}
+
+/// Inherent impls probed for a receiver step, see
+/// `ProbeContext::assemble_probe` in rustc.
+fn inherent_impls<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> Vec<DefId> {
+  let for_type =
+    |def_id| tcx.inherent_impls(def_id).into_iter().flatten().copied();
+  match *ty.kind() {
+    ty::Adt(def, _) => for_type(def.did()).collect(),
+    ty::Foreign(def_id) => for_type(def_id).collect(),
+    ty::Dynamic(data, ..) => data
+      .principal_def_id()
+      .map_or_else(Vec::new, |def_id| for_type(def_id).collect()),
+    ty::Bool
+    | ty::Char
+    | ty::Int(_)
+    | ty::Uint(_)
+    | ty::Float(_)
+    | ty::Str
+    | ty::Array(..)
+    | ty::Slice(_)
+    | ty::RawPtr(..)
+    | ty::Ref(..)
+    | ty::Never
+    | ty::Tuple(..) => simplify_type(tcx, ty, TreatParams::AsCandidateKey)
+      .map_or_else(Vec::new, |simp| {
+        tcx
+          .incoherent_impls(simp)
+          .into_iter()
+          .flatten()
+          .copied()
+          .collect()
+      }),
+    _ => vec![],
+  }
+}
+
+/// Can `def_id` be imported in `module`? Local items need the item and
+/// every module on its path accessible, items of other crates a public path
+/// from a dependency the user can name, see `suggest_traits_to_import` in
+/// rustc.
+fn is_nameable_from(tcx: TyCtxt, def_id: DefId, module: DefId) -> bool {
+  if def_id.is_local() {
+    std::iter::successors(Some(def_id), |&d| tcx.opt_parent(d))
+      .take_while(|d| !d.is_crate_root())
+      .all(|d| tcx.visibility(d).is_accessible_from(module, tcx))
+  } else {
+    tcx.visibility(def_id).is_public()
+      && tcx.is_user_visible_dep(def_id.krate)
+      && tcx.visible_parent_map(()).contains_key(&def_id)
+  }
+}
+
+/// Methods with a `self` parameter called `name` in an impl or trait.
+fn methods_named(
+  tcx: TyCtxt,
+  container: DefId,
+  name: Ident,
+) -> impl Iterator<Item = DefId> + '_ {
+  tcx
+    .associated_items(container)
+    .filter_by_name_unhygienic(name.name)
+    .filter(|item| item.kind == ty::AssocKind::Fn && item.fn_has_self_parameter)
+    .map(|item| item.def_id)
+}
@@ -1,34 +1,46 @@
 pub(crate) mod entry;
 mod hir;
+mod item;
+mod method;
 mod transform;
 
 use std::collections::HashMap;
 
 use anyhow::Result;
 use argus_ext::ty::TyCtxtExt;
+/// Options for the paths of analysis outputs, apply them around a call to
+/// any analysis, e.g., `options.apply(|| analysis::bundle(tcx, body_id))`.
+pub use argus_ser::{PathStyle, SerializeOptions};
 use fluid_let::fluid_let;
-use rustc_hir::BodyId;
-use rustc_middle::ty::TyCtxt;
+use rustc_hir::{def_id::LocalDefId, BodyId};
+use rustc_middle::ty::{self, TyCtxt};
 
 pub(crate) use crate::types::intermediate::{
   EvaluationResult, FulfillmentData,
 };
 use crate::{
+  evaluation::{self, ErrorRank},
   proof_tree::SerializedTree,
+  summary::{ErrorLocation, TraitErrorSite},
+  tls,
   types::{
     intermediate::{Forgettable, FullData},
-    BodyBundle, ObligationNecessity, ObligationsInBody, Target,
+    BodyBundle, ItemBundle, ObligationsInBody, ObligationsInItem, Target,
   },
 };
 
 fluid_let! {
   pub static OBLIGATION_TARGET: Target;
   pub static INCLUDE_SUCCESSES: bool;
+  /// Treat `OnError` obligations, predicates on lang-item traits and
+  /// non-trait predicates, as always necessary.
+  pub static FORCE_ON_ERROR: bool;
 }
 
 /// Generate the set of evaluated obligations within a single body.
 pub fn obligations(tcx: TyCtxt, body_id: BodyId) -> Result<ObligationsInBody> {
   fluid_let::fluid_set!(entry::BODY_ID, body_id);
+  tls::reset_interned_tys();
 
   let typeck_results = tcx.inspect_typeck(body_id, entry::process_obligation);
 
@@ -46,6 +58,7 @@ pub fn tree(tcx: TyCtxt, body_id: BodyId) -> Result<SerializedTree> {
   fluid_let::fluid_set!(entry::BODY_ID, body_id);
 
   log::trace!("tree {body_id:?}");
+  tls::reset_interned_tys();
 
   let typeck_results =
     tcx.inspect_typeck(body_id, entry::process_obligation_for_tree);
@@ -69,9 +82,7 @@ pub fn bundle(tcx: TyCtxt, body_id: BodyId) -> Result<BodyBundle> {
 
   let mut trees = HashMap::new();
   for obl in &t.1.obligations {
-    if obl.necessity == ObligationNecessity::Yes
-      || (obl.necessity == ObligationNecessity::OnError && obl.result.is_err())
-    {
+    if obl.is_necessary {
       if let Ok(stree) = entry::pick_tree(obl.hash, thunk) {
         trees.insert(obl.hash, stree);
       }
@@ -91,10 +102,215 @@ pub fn bundle(tcx: TyCtxt, body_id: BodyId) -> Result<BodyBundle> {
   })
 }
 
+/// Like `bundle`, but types, paths and impl headers are written to tables
+/// shared by every body, see `SharedBundle`.
+pub fn shared_bundle(tcx: TyCtxt, body_id: BodyId) -> Result<BodyBundle> {
+  tls::with_shared_tables(|| bundle(tcx, body_id))
+}
+
+/// Rank the expected root causes annotated in the body, see the
+/// `evaluation` module for the annotation format.
+pub fn evaluate(tcx: TyCtxt, body_id: BodyId) -> Result<Vec<ErrorRank>> {
+  fluid_let::fluid_set!(entry::BODY_ID, body_id);
+
+  log::trace!("evaluate {body_id:?}");
+
+  let body_span = tcx.hir().body(body_id).value.span;
+  let source_file = tcx.sess.source_map().lookup_source_file(body_span.lo());
+  let Some(source) = source_file.src.as_ref() else {
+    return Ok(vec![]);
+  };
+
+  let (full_data, obligations_in_body) = body_data(tcx, body_id);
+  let t = (&*full_data, &obligations_in_body);
+  let thunk = || t;
+
+  let body_lines = t.1.range.start.line ..= t.1.range.end.line;
+  let expected = evaluation::parse_annotations(source)
+    .into_iter()
+    .filter(|e| body_lines.contains(&e.line));
+
+  let filename = tcx
+    .body_filename(body_id)
+    .prefer_local()
+    .to_string_lossy()
+    .to_string();
+
+  let mut trees = HashMap::new();
+  let ranks = expected
+    .map(|expected| {
+      let mut rank = None;
+      let mut num_goals = 0;
+      let mut num_tree_nodes = 0;
+
+      let obligations = t.1.obligations.iter().filter(|obl| {
+        obl.is_necessary
+          && (obl.range.start.line ..= obl.range.end.line)
+            .contains(&expected.line)
+      });
+
+      for obl in obligations {
+        let Some(stree) = trees
+          .entry(obl.hash)
+          .or_insert_with(|| entry::pick_tree(obl.hash, thunk).ok())
+        else {
+          continue;
+        };
+
+        let goals = stree.analysis.ranked_goals();
+        let found = goals.iter().position(|&idx| {
+          stree
+            .analysis
+            .rendered(idx)
+            .is_some_and(|s| s.contains(&expected.message))
+        });
+
+        rank = match (rank, found) {
+          (Some(r), Some(f)) => Some(std::cmp::min(r, f)),
+          (r, f) => r.or(f),
+        };
+        num_goals = num_goals.max(goals.len());
+        num_tree_nodes = num_tree_nodes.max(stree.nodes.len());
+      }
+
+      ErrorRank {
+        filename: filename.clone(),
+        expected,
+        rank,
+        num_goals,
+        num_tree_nodes,
+      }
+    })
+    .collect();
+
+  Ok(ranks)
+}
+
+/// Key the trait errors of a body for aggregation across the workspace, see
+/// `summary::Summary`.
+pub fn trait_error_sites(
+  tcx: TyCtxt,
+  body_id: BodyId,
+) -> Result<Vec<TraitErrorSite>> {
+  fluid_let::fluid_set!(entry::BODY_ID, body_id);
+
+  log::trace!("trait_error_sites {body_id:?}");
+
+  let (full_data, obligations_in_body) = body_data(tcx, body_id);
+  let t = (&*full_data, &obligations_in_body);
+  let thunk = || t;
+
+  let filename = tcx
+    .body_filename(body_id)
+    .prefer_local()
+    .to_string_lossy()
+    .to_string();
+
+  let sites = t
+    .1
+    .trait_errors
+    .iter()
+    .map(|error| {
+      let failing = error.hashes.iter().find_map(|&hash| {
+        t.0
+          .iter()
+          .find(|fdata| fdata.hash == hash && fdata.result.is_err())
+      });
+
+      let (failing_trait, self_ty) = failing
+        .and_then(|fdata| {
+          let predicate = fdata
+            .infcx
+            .resolve_vars_if_possible(fdata.obligation.predicate);
+          let (trait_def_id, self_ty) = match predicate.kind().skip_binder() {
+            ty::PredicateKind::Clause(ty::ClauseKind::Trait(p)) => {
+              (p.def_id(), p.self_ty())
+            }
+            ty::PredicateKind::Clause(ty::ClauseKind::Projection(p)) => {
+              (p.trait_def_id(tcx), p.self_ty())
+            }
+            _ => return None,
+          };
+          Some((tcx.def_path_str(trait_def_id), self_ty.to_string()))
+        })
+        .unzip();
+
+      let root_cause = failing.and_then(|fdata| {
+        let stree = entry::pick_tree(fdata.hash, thunk).ok()?;
+        let first = *stree.analysis.ranked_goals().first()?;
+        stree.analysis.rendered(first).map(ToString::to_string)
+      });
+
+      TraitErrorSite {
+        location: ErrorLocation {
+          filename: filename.clone(),
+          line: error.range.start.line,
+          column: error.range.start.column,
+        },
+        failing_trait,
+        self_ty,
+        root_cause,
+      }
+    })
+    .collect();
+
+  Ok(sites)
+}
+
+/// Generate the set of evaluated obligations of an impl block, trait or type
+/// definition. These are checked outside of any body, by well-formedness
+/// and coherence checking, see `find_bodies::find_items`.
+pub fn item_obligations(
+  tcx: TyCtxt,
+  def_id: LocalDefId,
+) -> Result<ObligationsInItem> {
+  log::trace!("item_obligations {def_id:?}");
+
+  let (_, obligations_in_item) = item::item_data(tcx, def_id)?;
+  Ok(obligations_in_item)
+}
+
+/// Analyze an impl block, trait or type definition and pre-generate the proof
+/// trees of its necessary obligations, see `bundle`.
+pub fn item_bundle(tcx: TyCtxt, def_id: LocalDefId) -> Result<ItemBundle> {
+  log::trace!("item_bundle {def_id:?}");
+
+  let (full_data, obligations_in_item) = item::item_data(tcx, def_id)?;
+
+  let mut trees = HashMap::new();
+  for obl in &obligations_in_item.obligations {
+    if obl.is_necessary {
+      match item::pick_tree(def_id, obl.hash, &full_data) {
+        Ok(stree) => {
+          trees.insert(obl.hash, stree);
+        }
+        Err(e) => log::error!("tree not generated {e:?}"),
+      }
+    }
+  }
+
+  let filename = tcx
+    .sess
+    .source_map()
+    .span_to_filename(tcx.def_span(def_id))
+    .prefer_local()
+    .to_string_lossy()
+    .to_string();
+
+  Ok(ItemBundle {
+    filename,
+    item: obligations_in_item,
+    trees,
+  })
+}
+
 pub(crate) fn body_data(
   tcx: TyCtxt,
   body_id: BodyId,
 ) -> (Forgettable<FullData>, ObligationsInBody) {
+  fluid_let::fluid_set!(entry::BODY_ID, body_id);
+  tls::reset_interned_tys();
+
   let typeck_results = tcx.inspect_typeck(body_id, entry::process_obligation);
   entry::build_obligations_in_body(tcx, body_id, typeck_results)
 }
@@ -1,22 +1,25 @@
 use argus_ext::{
   infer::InferCtxtExt,
   ty::{
-    retain_error_sources, retain_method_calls, TyCtxtExt, TypeckResultsExt,
+    retain_error_sources, retain_method_calls, EvaluationResultExt,
+    PredicateObligationExt, TyCtxtExt, TypeckResultsExt,
   },
   utils::SpanExt as ArgusSpanExt,
 };
 use index_vec::IndexVec;
 use indexmap::IndexSet;
 use rustc_data_structures::fx::{FxHashMap as HashMap, FxIndexMap};
-use rustc_hir::{self as hir, intravisit::Map, BodyId, HirId};
+use rustc_hir::{
+  self as hir, def_id::LocalDefId, intravisit::Map, BodyId, HirId,
+};
 use rustc_infer::{infer::InferCtxt, traits::PredicateObligation};
-use rustc_middle::ty::{TyCtxt, TypeckResults};
+use rustc_middle::ty::{self, TyCtxt, TypeckResults};
 use rustc_span::Span;
 use rustc_utils::source_map::{range::CharRange, span::SpanExt};
 
 use super::{
   hir::{self as hier_hir, Bin, BinKind},
-  EvaluationResult,
+  method, EvaluationResult,
 };
 use crate::{
   ext::InferCtxtExt as LocalInferCtxtExt,
@@ -54,20 +57,20 @@ pub fn compute_provenance<'tcx>(
   let hir_id =
     hier_hir::find_most_enclosing_node(infcx.tcx, body_id, callsite_cause_span)
       .unwrap_or_else(|| hir.body_owner(body_id));
+  let range = fdata.obligation.range(&infcx.tcx, body_id);
 
   Provenance {
     hir_id,
     full_data: dataid,
-    it: infcx.erase_non_local_data(body_id, fdata),
+    it: infcx.erase_non_local_data(range, hir_id, fdata),
   }
 }
 
 /// Transform a set of obligations into categorized trait errors.
 ///
-/// `bins` coarsely associates obligations with HIR *expressions*. In
-/// the future we should be even more precise and associate them with
-/// arbitrary nodes. This is important because predicates like `WellFormed`
-/// associate with signature types.
+/// `bins` associates obligations with HIR expressions and, for predicates
+/// like `WellFormed` or where-clause bounds, with the signature types,
+/// `where` clauses and impl headers of the body owner.
 ///
 /// 1. Take the map of 'HIR ids -> obligations' and sort these into
 ///    expressions. This leaves us with a map of 'expressions -> hirids'.
@@ -133,12 +136,25 @@ pub fn transform<'a, 'tcx: 'a>(
   // where found but type-checking failed.
   if builder.trait_errors.is_empty()
     && builder.ambiguity_errors.is_empty()
-    && builder.typeck_results.tainted_by_errors.is_some()
+    && tcx.body_has_errors(typeck_results)
   {
     builder.relate_unreported_errors();
     property_is_ok!(builder.is_valid(), "builder is invalid");
   }
 
+  // Method calls binned with an error, the method may be resolved
+  // but rustc could have picked a different one.
+  let erroneous = builder
+    .trait_errors
+    .iter()
+    .map(|e| e.idx)
+    .chain(builder.ambiguity_errors.iter().map(|e| e.idx))
+    .filter_map(|idx| builder.exprs_to_hir_id.get(&idx).copied())
+    .collect();
+  let method_lookups =
+    method::method_lookups(tcx, body_id, typeck_results, &erroneous);
+  let closures = builder.relate_closures();
+
   let name = obligation_data.iter().next().map(|fdata| {
     (
       &fdata.infcx,
@@ -148,14 +164,34 @@ pub fn transform<'a, 'tcx: 'a>(
 
   ObligationsInBody::new(
     name,
+    BodyHash::new(tcx, body_id),
     body_range,
     builder.ambiguity_errors,
     builder.trait_errors,
     builder.raw_obligations,
     builder.exprs,
+    method_lookups,
+    closures,
   )
 }
 
+pub(super) fn kind_of_bin(kind: &BinKind) -> ExprKind {
+  use ExprKind as EK;
+
+  match kind {
+    BinKind::Misc => EK::Misc,
+    BinKind::CallableExpr => EK::CallableExpr,
+    BinKind::CallArg => EK::CallArg,
+    BinKind::Call => EK::Call,
+    BinKind::FnSigTy => EK::FnSigTy,
+    BinKind::WhereClause => EK::WhereClause,
+    BinKind::FieldTy => EK::FieldTy,
+    BinKind::ImplHeader => EK::ImplHeader,
+    BinKind::AssocBinding => EK::AssocBinding,
+    BinKind::Closure => EK::Closure,
+  }
+}
+
 struct ObligationsBuilder<'a, 'tcx: 'a> {
   tcx: TyCtxt<'tcx>,
   body_id: BodyId,
@@ -196,8 +232,6 @@ impl<'a, 'tcx: 'a> ObligationsBuilder<'a, 'tcx> {
   }
 
   fn sort_bins(&mut self, bins: Vec<Bin>) {
-    use ExprKind as EK;
-
     let hir = self.tcx.hir();
     let source_map = self.tcx.sess.source_map();
     for bin in bins {
@@ -221,12 +255,7 @@ impl<'a, 'tcx: 'a> ObligationsBuilder<'a, 'tcx> {
           hir.span_with_body(hir_id),
           hir.span_with_body(hir_id).from_expansion()
         );
-      let kind = match kind {
-        BinKind::Misc => EK::Misc,
-        BinKind::CallableExpr => EK::CallableExpr,
-        BinKind::CallArg => EK::CallArg,
-        BinKind::Call => EK::Call,
-      };
+      let kind = kind_of_bin(&kind);
 
       // We can only filter obligations that have known provenance data, so just split the
       // others off and add them back in later.
@@ -281,14 +310,79 @@ impl<'a, 'tcx: 'a> ObligationsBuilder<'a, 'tcx> {
     }
   }
 
+  /// Link closure expressions to the obligations placed on the closure
+  /// type, usually by the callee it's passed to, and to the failures
+  /// within the closure body.
+  fn relate_closures(&self) -> Vec<ClosureLink> {
+    let closure_of = |hir_id| match self.tcx.hir_node(hir_id) {
+      hir::Node::Expr(hir::Expr {
+        kind: hir::ExprKind::Closure(closure),
+        ..
+      }) => Some(closure.def_id),
+      _ => None,
+    };
+
+    self
+      .exprs
+      .iter_enumerated()
+      .filter(|(_, expr)| matches!(expr.kind, ExprKind::Closure))
+      .filter_map(|(idx, expr)| {
+        let def_id = closure_of(*self.exprs_to_hir_id.get(&idx)?)?;
+        let requirements = self
+          .obligations
+          .iter()
+          .filter(|prov| {
+            prov.full_data.is_some_and(|uoidx| {
+              let predicate = self.full_data.get(uoidx).obligation.predicate;
+              closure_self_ty(predicate) == Some(def_id)
+            })
+          })
+          .map(|prov| prov.it)
+          .collect::<Vec<_>>();
+        let failures = expr
+          .obligations
+          .iter()
+          .copied()
+          .filter(|&i| {
+            let obl = &self.raw_obligations[i];
+            obl.result.is_no() && obl.is_necessary
+          })
+          .collect::<Vec<_>>();
+
+        (!requirements.is_empty() || !failures.is_empty()).then_some(
+          ClosureLink {
+            closure: idx,
+            requirements,
+            failures,
+          },
+        )
+      })
+      .collect()
+  }
+
   fn exact_predicate_search(
     &self,
     needle: ObligationHash,
+    error_span: Span,
   ) -> Option<ObligationIdx> {
-    self
+    let source_map = self.tcx.sess.source_map();
+    let error_span = self.tcx.to_local(self.body_id, error_span);
+    let error_range = CharRange::from_span(error_span, source_map).ok();
+
+    let mut candidates = self
       .raw_obligations
       .iter_enumerated()
-      .find_map(|(obl_id, obl)| (obl.hash == needle).then_some(obl_id))
+      .filter(|(_, obl)| obl.predicate_hash == needle)
+      .peekable();
+    let (first, _) = *candidates.peek()?;
+
+    // Equal predicates can be required at different call sites,
+    // prefer the obligation that overlaps the reported error.
+    let overlapping = candidates.find(|(_, obl)| {
+      error_range
+        .is_some_and(|r| r.start <= obl.range.end && obl.range.start <= r.end)
+    });
+    Some(overlapping.map_or(first, |(obl_id, _)| obl_id))
   }
 
   fn shallow_tree_predicate_search(
@@ -314,9 +408,9 @@ impl<'a, 'tcx: 'a> ObligationsBuilder<'a, 'tcx> {
         .iter()
         .filter_map(|&p| {
           self
-            .exact_predicate_search(p)
+            .exact_predicate_search(p, *error_span)
             .or_else(|| self.shallow_tree_predicate_search(p))
-            .map(|h| (h, p))
+            .map(|idx| (idx, self.raw_obligations[idx].hash))
         })
         .collect::<Vec<_>>();
 
@@ -426,10 +520,7 @@ impl<'a, 'tcx: 'a> ObligationsBuilder<'a, 'tcx> {
   #[cfg(any(feature = "testing", debug_assertions))]
   fn is_valid(&self) -> anyhow::Result<()> {
     for obl in &self.raw_obligations {
-      if matches!(obl.necessity, ObligationNecessity::Yes)
-        || (matches!(obl.necessity, ObligationNecessity::OnError)
-          && obl.result.is_err())
-      {
+      if obl.is_necessary {
         let exists = self.full_data.iter().any(|fdata| fdata.hash == obl.hash);
 
         anyhow::ensure!(exists, "full data not found for {:?}", obl);
@@ -440,6 +531,26 @@ impl<'a, 'tcx: 'a> ObligationsBuilder<'a, 'tcx> {
   }
 }
 
+/// The closure, or coroutine, a trait or projection predicate is about.
+fn closure_self_ty(predicate: ty::Predicate) -> Option<LocalDefId> {
+  let self_ty = match predicate.kind().skip_binder() {
+    ty::PredicateKind::Clause(ty::ClauseKind::Trait(trait_pred)) => {
+      trait_pred.self_ty()
+    }
+    ty::PredicateKind::Clause(ty::ClauseKind::Projection(proj)) => {
+      proj.self_ty()
+    }
+    _ => return None,
+  };
+
+  match self_ty.kind() {
+    ty::Closure(def_id, _)
+    | ty::Coroutine(def_id, _)
+    | ty::CoroutineClosure(def_id, _) => def_id.as_local(),
+    _ => None,
+  }
+}
+
 mod tree_search {
   use std::ops::ControlFlow;
 
@@ -0,0 +1,152 @@
+//! Evaluate the root-cause ranking against annotated source files.
+//!
+//! An expected root cause is written as a comment on the line of the
+//! failing expression, following the compiletest convention
+//!
+//! ```text
+//! needs_send(rc); //~ ROOT_CAUSE: Rc<i32>: Send
+//! //~^ ROOT_CAUSE: Rc<i32>: Send
+//! ```
+//!
+//! where each `^` refers one line further up. The rank of an error is the
+//! position of the first goal, in the order shown to the user, whose rendered
+//! predicate contains the expected text.
+use serde::Serialize;
+
+const ANNOTATION: &str = "ROOT_CAUSE:";
+
+/// Expected root cause parsed from a `//~ ROOT_CAUSE:` annotation.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpectedCause {
+  /// Zero-indexed line of the annotated error.
+  pub line: usize,
+  pub message: String,
+}
+
+/// Rank of the expected root cause for a single annotated error.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorRank {
+  pub filename: String,
+  pub expected: ExpectedCause,
+  /// Position of the expected cause, `None` if Argus didn't list it.
+  pub rank: Option<usize>,
+  /// Number of goals listed for the error.
+  pub num_goals: usize,
+  /// Size of the largest proof tree for the error.
+  pub num_tree_nodes: usize,
+}
+
+/// Aggregate metrics over a set of ranked errors.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluationSummary {
+  pub errors: usize,
+  pub found: usize,
+  pub top1: usize,
+  pub top3: usize,
+  pub top5: usize,
+  /// Mean rank of the found causes.
+  pub mean_rank: Option<f64>,
+  /// Mean reciprocal rank, missing causes count as zero.
+  pub mean_reciprocal_rank: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Evaluation {
+  pub ranks: Vec<ErrorRank>,
+  pub summary: EvaluationSummary,
+}
+
+impl From<Vec<ErrorRank>> for Evaluation {
+  fn from(ranks: Vec<ErrorRank>) -> Self {
+    let summary = EvaluationSummary::new(&ranks);
+    Evaluation { ranks, summary }
+  }
+}
+
+impl EvaluationSummary {
+  #[allow(clippy::cast_precision_loss)]
+  pub fn new(ranks: &[ErrorRank]) -> Self {
+    let found = ranks.iter().filter_map(|r| r.rank).collect::<Vec<_>>();
+    let within = |n| found.iter().filter(|&&r| r < n).count();
+    let mean_rank = (!found.is_empty())
+      .then(|| found.iter().sum::<usize>() as f64 / found.len() as f64);
+    let mean_reciprocal_rank = if ranks.is_empty() {
+      0.0
+    } else {
+      found.iter().map(|&r| 1.0 / (r + 1) as f64).sum::<f64>()
+        / ranks.len() as f64
+    };
+
+    EvaluationSummary {
+      errors: ranks.len(),
+      found: found.len(),
+      top1: within(1),
+      top3: within(3),
+      top5: within(5),
+      mean_rank,
+      mean_reciprocal_rank,
+    }
+  }
+}
+
+/// Parse all root-cause annotations in `source`.
+pub fn parse_annotations(source: &str) -> Vec<ExpectedCause> {
+  source
+    .lines()
+    .enumerate()
+    .filter_map(|(line, text)| {
+      let (_, rest) = text.split_once("//~")?;
+      let up = rest.chars().take_while(|&c| c == '^').count();
+      let message = rest[up ..].trim_start().strip_prefix(ANNOTATION)?;
+      Some(ExpectedCause {
+        line: line.checked_sub(up)?,
+        message: message.trim().to_string(),
+      })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn annotations_same_and_previous_line() {
+    let source = "fn main() {\n  f(x); //~ ROOT_CAUSE: X: Send\n  //~^^ ROOT_CAUSE: Y: Sync\n}";
+    assert_eq!(parse_annotations(source), vec![
+      ExpectedCause {
+        line: 1,
+        message: "X: Send".into()
+      },
+      ExpectedCause {
+        line: 0,
+        message: "Y: Sync".into()
+      }
+    ]);
+  }
+
+  #[test]
+  fn summary_counts_missing_causes() {
+    let rank = |rank| ErrorRank {
+      filename: String::new(),
+      expected: ExpectedCause {
+        line: 0,
+        message: String::new(),
+      },
+      rank,
+      num_goals: 0,
+      num_tree_nodes: 0,
+    };
+    let summary =
+      EvaluationSummary::new(&[rank(Some(0)), rank(Some(3)), rank(None)]);
+    assert_eq!(summary.found, 2);
+    assert_eq!(summary.top1, 1);
+    assert_eq!(summary.top5, 2);
+    assert_eq!(summary.mean_rank, Some(1.5));
+    assert!((summary.mean_reciprocal_rank - 1.25 / 3.0).abs() < f64::EPSILON);
+  }
+}
@@ -1,16 +1,14 @@
-use argus_ext::{
-  infer::InferCtxtExt as ArgusInferCtxtExt,
-  ty::{PredicateExt, PredicateObligationExt},
-};
+use argus_ext::{infer::InferCtxtExt as ArgusInferCtxtExt, ty::PredicateExt};
 use argus_ser as ser;
-use rustc_hir::BodyId;
+use rustc_hir::HirId;
 use rustc_infer::{infer::InferCtxt, traits::PredicateObligation};
 use rustc_middle::ty::{self, Predicate};
+use rustc_utils::source_map::range::CharRange;
 use serde::Serialize;
 
 use crate::{
-  analysis::{EvaluationResult, FulfillmentData},
-  types::{Obligation, ObligationNecessity},
+  analysis::{EvaluationResult, FulfillmentData, FORCE_ON_ERROR},
+  types::{MacroFrame, Obligation, ObligationNecessity},
 };
 
 pub trait InferCtxtExt<'tcx> {
@@ -20,9 +18,12 @@ pub trait InferCtxtExt<'tcx> {
     result: EvaluationResult,
   ) -> FulfillmentData<'a, 'tcx>;
 
+  /// Serialize the obligation, `range` is its location in the local crate
+  /// and `hir_id` the most enclosing node of its cause.
   fn erase_non_local_data(
     &self,
-    body_id: BodyId,
+    range: CharRange,
+    hir_id: HirId,
     fdata: FulfillmentData<'_, 'tcx>,
   ) -> Obligation;
 
@@ -67,8 +68,9 @@ impl<'tcx> InferCtxtExt<'tcx> for InferCtxt<'tcx> {
 
     if !is_writeable() || p.is_lhs_unit() {
       ON::No
-    } else if (p.is_trait_predicate() && is_rhs_lang_item())
-      || !p.is_trait_predicate()
+    } else if ((p.is_trait_predicate() && is_rhs_lang_item())
+      || !p.is_trait_predicate())
+      && !FORCE_ON_ERROR.copied().unwrap_or(false)
     {
       ON::OnError
     } else {
@@ -109,7 +111,8 @@ impl<'tcx> InferCtxtExt<'tcx> for InferCtxt<'tcx> {
     result: EvaluationResult,
   ) -> FulfillmentData<'a, 'tcx> {
     FulfillmentData {
-      hash: self.predicate_hash(&obligation.predicate).into(),
+      hash: self.obligation_hash(obligation).into(),
+      predicate_hash: self.predicate_hash(&obligation.predicate).into(),
       obligation,
       result,
     }
@@ -117,7 +120,8 @@ impl<'tcx> InferCtxtExt<'tcx> for InferCtxt<'tcx> {
 
   fn erase_non_local_data(
     &self,
-    body_id: BodyId,
+    range: CharRange,
+    hir_id: HirId,
     fdata: FulfillmentData<'_, 'tcx>,
   ) -> Obligation {
     #[derive(Serialize)]
@@ -128,19 +132,26 @@ impl<'tcx> InferCtxtExt<'tcx> for InferCtxt<'tcx> {
     );
 
     let obl = &fdata.obligation;
-    let range = obl.range(&self.tcx, body_id);
     let necessity = self.obligation_necessity(obl);
-    let obligation = crate::tls::unsafe_access_interner(|ty_intern| {
-      ser::to_value_expect(self, ty_intern, &Wrapper(obl))
+    let is_necessary = necessity.is_necessary(fdata.result);
+    let expansion = MacroFrame::backtrace(self.tcx, obl.cause.span);
+    let (obligation, hir) = crate::tls::unsafe_access_interner(|ty_intern| {
+      let hir = ser::hir::NodeDef::new(self.tcx.hir_node(hir_id))
+        .map(|node| ser::to_value_expect(self, ty_intern, &node));
+      (ser::to_value_expect(self, ty_intern, &Wrapper(obl)), hir)
     });
 
     Obligation {
       obligation,
       hash: fdata.hash,
+      predicate_hash: fdata.predicate_hash,
       range,
       kind: fdata.kind(),
       necessity,
       result: fdata.result,
+      is_necessary,
+      expansion,
+      hir,
     }
   }
 }
@@ -1,7 +1,7 @@
 //! This is a copy of the `BodyFinder` from `rustc_utils` but it
 //! does *not* skip const/static items. Funny enough, these items
 //! often have important trait constraints evaluated (think derive macros).
-use rustc_hir::{intravisit::Visitor, BodyId};
+use rustc_hir::{def_id::LocalDefId, intravisit::Visitor, BodyId, ItemKind};
 use rustc_middle::{hir::nested_filter::OnlyBodies, ty::TyCtxt};
 use rustc_span::Span;
 use rustc_utils::{block_timer, SpanExt};
@@ -33,6 +33,15 @@ impl<'tcx> Visitor<'tcx> for BodyFinder<'tcx> {
     let body = hir.body(id);
     self.visit_body(body);
 
+    // Closures and async blocks are type-checked together with their
+    // enclosing body, their obligations are reported there.
+    if self
+      .tcx
+      .is_typeck_child(hir.body_owner_def_id(id).to_def_id())
+    {
+      return;
+    }
+
     let hir = self.tcx.hir();
     let span = hir.span_with_body(hir.body_owner(id));
     log::trace!(
@@ -68,3 +77,25 @@ pub fn find_enclosing_bodies(
   bodies.sort_by_key(|(span, _)| span.size());
   bodies.into_iter().map(|(_, id)| id)
 }
+
+/// Finds all impl blocks, trait definitions and type definitions in the
+/// current crate, these have obligations that are checked outside of any body.
+pub fn find_items(tcx: TyCtxt) -> Vec<(Span, LocalDefId)> {
+  block_timer!("find_items");
+  let hir = tcx.hir();
+  hir
+    .items()
+    .map(|item_id| hir.item(item_id))
+    .filter(|item| {
+      matches!(
+        item.kind,
+        ItemKind::Impl(..)
+          | ItemKind::Trait(..)
+          | ItemKind::Struct(..)
+          | ItemKind::Enum(..)
+          | ItemKind::Union(..)
+      )
+    })
+    .map(|item| (item.span, item.owner_id.def_id))
+    .collect()
+}
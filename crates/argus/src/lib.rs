@@ -37,9 +37,11 @@ extern crate rustc_trait_selection;
 
 mod aadebug;
 pub mod analysis;
+pub mod evaluation;
 pub mod ext;
 pub mod find_bodies; // TODO: remove when upstreamed to rustc-plugin
 mod proof_tree;
+pub mod summary;
 #[cfg(feature = "testing")]
 pub mod test_utils;
 mod tls;
@@ -4,7 +4,9 @@ use std::{
 };
 
 use argus_ext::{
-  infer::InferCtxtExt, ty::VarCounterExt, utils::SpanExt as ArgusSpanExt,
+  infer::InferCtxtExt,
+  ty::{EvaluationResultExt, VarCounterExt},
+  utils::SpanExt as ArgusSpanExt,
 };
 use argus_ser as ser;
 use argus_ser::interner::Interner;
@@ -48,6 +50,16 @@ impl Interners {
     }
   }
 
+  /// Log the size of each interner, called once the tree is built.
+  pub fn report_stats(&self) {
+    log::info!(
+      "Tree interners: goals {}, candidates {}, results {}",
+      self.goals.stats(),
+      self.candidates.stats(),
+      self.results.stats()
+    );
+  }
+
   pub fn take(
     self,
   ) -> (
@@ -80,9 +92,9 @@ impl Interners {
 
   pub fn mk_goal_node(&mut self, goal: &InspectGoal) -> Node {
     let infcx = goal.infcx();
-    let result_idx = self.intern_result(goal.result());
+    let result = goal.result();
     let goal = goal.goal();
-    let goal_idx = self.intern_goal(infcx, &goal, result_idx);
+    let goal_idx = self.intern_goal(infcx, &goal, result);
     Node::Goal(goal_idx)
   }
 
@@ -137,8 +149,9 @@ impl Interners {
     &mut self,
     infcx: &InferCtxt<'tcx>,
     goal: &solve::Goal<'tcx, ty::Predicate<'tcx>>,
-    result_idx: ResultIdx,
+    result: EvaluationResult,
   ) -> GoalIdx {
+    let result_idx = self.intern_result(result);
     let goal = infcx.resolve_vars_if_possible(*goal);
     let hash = infcx.predicate_hash(&goal.predicate);
     let hash = (hash, result_idx);
@@ -149,6 +162,10 @@ impl Interners {
     let necessity = infcx.guess_predicate_necessity(&goal.predicate);
     let num_vars = goal.predicate.count_vars(infcx.tcx);
     let is_main_tv = goal.predicate.is_main_ty_var();
+    let diagnostic = result
+      .is_no()
+      .then(|| DiagnosticNote::new(infcx, &goal))
+      .flatten();
     let goal_value = tls::unsafe_access_interner(|ty_interner| {
       ser::to_value_expect(infcx, ty_interner, &ser::GoalPredicateDef(goal))
     });
@@ -159,6 +176,7 @@ impl Interners {
       num_vars,
       is_main_tv,
       result: result_idx,
+      diagnostic,
 
       #[cfg(debug_assertions)]
       debug_comparison: format!("{:?}", goal.predicate.kind().skip_binder()),
@@ -198,6 +216,7 @@ impl Interners {
         CanKey::Impl(def_id),
         CandidateData::new_impl_header(
           infcx,
+          def_id,
           &header,
           infcx.tcx.is_user_visible_dep(def_id.krate),
         ),
@@ -7,11 +7,15 @@ pub mod topology;
 
 use std::collections::HashMap;
 
-use argus_ext::ty::PredicateExt;
-use argus_ser::{self as ser, interner::TyIdx};
+use argus_ext::{infer::InferCtxtExt, ty::PredicateExt};
+use argus_ser::{
+  self as ser,
+  interner::{TyAbbreviations, TyIdx},
+};
 use index_vec::IndexVec;
+use rustc_hir::def_id::DefId;
 use rustc_infer::infer::InferCtxt;
-use rustc_middle::ty;
+use rustc_middle::{traits::solve, ty};
 use serde::Serialize;
 pub use topology::*;
 #[cfg(feature = "testing")]
@@ -48,7 +52,7 @@ pub enum Node {
 #[cfg_attr(feature = "testing", ts(export))]
 pub struct GoalData {
   #[cfg_attr(feature = "testing", ts(type = "GoalPredicate"))]
-  value: serde_json::Value,
+  value: ser::Encoded,
 
   necessity: ObligationNecessity,
   num_vars: usize,
@@ -58,18 +62,35 @@ pub struct GoalData {
   is_main_tv: bool,
   result: ResultIdx,
 
+  /// Library-authored message from `#[diagnostic::on_unimplemented]`.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  diagnostic: Option<DiagnosticNote>,
+
   #[cfg(debug_assertions)]
   #[cfg_attr(feature = "testing", ts(type = "string | undefined"))]
   debug_comparison: String,
 }
 
+/// Custom message, label and notes of a failing trait goal, formatted
+/// with the goal's generic arguments.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
+pub struct DiagnosticNote {
+  message: Option<String>,
+  label: Option<String>,
+  notes: Vec<String>,
+}
+
 #[derive(Serialize, Clone, Debug)]
 #[cfg_attr(feature = "testing", derive(TS))]
 #[cfg_attr(feature = "testing", ts(export))]
 pub enum CandidateData {
   Impl {
+    /// The header, or its index in the shared tables of a bundle.
     #[cfg_attr(feature = "testing", ts(type = "ImplHeader"))]
-    hd: serde_json::Value,
+    hd: ser::Encoded,
     is_user_visible: bool,
   },
   ParamEnv(usize),
@@ -106,12 +127,28 @@ pub struct SerializedTree {
   pub results: IndexVec<ResultIdx, ResultData>,
 
   #[cfg_attr(feature = "testing", ts(type = "TyVal[]"))]
-  pub tys: IndexVec<TyIdx, serde_json::Value>,
+  pub tys: IndexVec<TyIdx, ser::Encoded>,
+
+  #[serde(skip_serializing_if = "TyAbbreviations::is_empty")]
+  #[cfg_attr(
+    feature = "testing",
+    ts(type = "Record<TyIdx, TyIdx[]> | undefined")
+  )]
+  pub ty_abbreviations: TyAbbreviations,
 
   pub projection_values: HashMap<TyIdx, TyIdx>,
 
   pub all_impl_candidates: HashMap<ProofNodeIdx, Vec<CandidateIdx>>,
 
+  /// Where trait goals differ from their impl candidates, keyed by the
+  /// goal's node. Impls that match a goal exactly aren't listed.
+  #[serde(skip_serializing_if = "HashMap::is_empty")]
+  #[cfg_attr(
+    feature = "testing",
+    ts(type = "Record<ProofNodeIdx, ImplDiff[]> | undefined")
+  )]
+  pub impl_diffs: HashMap<ProofNodeIdx, Vec<ImplDiff>>,
+
   pub topology: TreeTopology,
 
   #[serde(skip_serializing_if = "Option::is_none")]
@@ -125,26 +162,68 @@ pub struct SerializedTree {
 #[cfg_attr(feature = "testing", ts(export))]
 pub struct ProofCycle(Vec<ProofNodeIdx>);
 
+/// The subterms of a goal that differ from those of an impl candidate.
+#[derive(Serialize, Debug, Clone)]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
+pub struct ImplDiff {
+  pub candidate: CandidateIdx,
+
+  #[cfg_attr(feature = "testing", ts(type = "ArgDiff[]"))]
+  pub args: ser::Encoded,
+}
+
 // ----------------------------------------
 // impls
 
 impl CandidateData {
   fn new_impl_header<'tcx>(
     infcx: &InferCtxt<'tcx>,
+    def_id: DefId,
     impl_: &ser::ImplHeader<'tcx>,
     is_user_visible: bool,
   ) -> Self {
-    let impl_ = tls::unsafe_access_interner(|ty_interner| {
-      ser::to_value_expect(infcx, ty_interner, impl_)
-    });
+    let encode = || {
+      tls::unsafe_access_interner(|ty_interner| {
+        ser::to_value_expect(infcx, ty_interner, impl_)
+      })
+    };
+
+    // Headers are written once to the shared tables and referred to by index.
+    let hd = if ser::has_shared_tables() {
+      let idx = tls::intern_impl_header(def_id, encode);
+      ser::Encoded::new(&idx).expect("failed to serialize index")
+    } else {
+      encode()
+    };
 
     Self::Impl {
-      hd: impl_,
+      hd,
       is_user_visible,
     }
   }
 }
 
+impl DiagnosticNote {
+  fn new<'tcx>(
+    infcx: &InferCtxt<'tcx>,
+    goal: &solve::Goal<'tcx, ty::Predicate<'tcx>>,
+  ) -> Option<Self> {
+    let trait_pred = goal.predicate.as_trait_clause()?;
+    if trait_pred.polarity() != ty::PredicatePolarity::Positive {
+      return None;
+    }
+
+    let note = infcx.on_unimplemented_note(goal.param_env, trait_pred);
+    (note.message.is_some() || note.label.is_some() || !note.notes.is_empty())
+      .then_some(DiagnosticNote {
+        message: note.message,
+        label: note.label,
+        notes: note.notes,
+      })
+  }
+}
+
 impl From<&'static str> for CandidateData {
   fn from(value: &'static str) -> Self {
     value.to_string().into()
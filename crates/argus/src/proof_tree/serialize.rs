@@ -26,6 +26,7 @@ pub struct SerializedTreeVisitor<'tcx> {
   pub cycle: Option<ProofCycle>,
   pub projection_values: HashMap<TyIdx, TyIdx>,
   pub all_impl_candidates: HashMap<ProofNodeIdx, Vec<CandidateIdx>>,
+  pub impl_diffs: HashMap<ProofNodeIdx, Vec<ImplDiff>>,
 
   deferred_leafs: Vec<(ProofNodeIdx, EvaluationResult)>,
   interners: Interners,
@@ -42,6 +43,7 @@ impl SerializedTreeVisitor<'_> {
       cycle: None,
       projection_values: HashMap::default(),
       all_impl_candidates: HashMap::default(),
+      impl_diffs: HashMap::default(),
 
       deferred_leafs: Vec::default(),
       interners: Interners::default(),
@@ -82,28 +84,29 @@ impl SerializedTreeVisitor<'_> {
   }
 
   #[cfg(debug_assertions)]
-  fn is_valid(&self) -> Result<()> {
-    for (pidx, node) in self.nodes.iter_enumerated() {
+  fn is_valid(
+    nodes: &IndexVec<ProofNodeIdx, Node>,
+    topology: &TreeTopology,
+    interners: &Interners,
+  ) -> Result<()> {
+    for (pidx, node) in nodes.iter_enumerated() {
       match node {
         Node::Goal(g) => {
           anyhow::ensure!(
-            !self.topology.is_leaf(pidx),
+            !topology.is_leaf(pidx),
             "non-leaf node (goal) has no children {:?}",
-            self.interners.goal(*g)
+            interners.goal(*g)
           );
         }
         Node::Candidate(c) => {
           anyhow::ensure!(
-            !self.topology.is_leaf(pidx),
+            !topology.is_leaf(pidx),
             "non-leaf node (candidate) has no children {:?}",
-            self.interners.candidate(*c)
+            interners.candidate(*c)
           );
         }
         Node::Result(..) => {
-          anyhow::ensure!(
-            self.topology.is_leaf(pidx),
-            "result node is not a leaf"
-          );
+          anyhow::ensure!(topology.is_leaf(pidx), "result node is not a leaf");
         }
       }
     }
@@ -111,9 +114,6 @@ impl SerializedTreeVisitor<'_> {
   }
 
   pub fn into_tree(self) -> Result<SerializedTree> {
-    #[cfg(debug_assertions)]
-    self.is_valid()?;
-
     let SerializedTreeVisitor {
       root: Some(root),
       mut nodes,
@@ -124,6 +124,7 @@ impl SerializedTreeVisitor<'_> {
       aadebug,
       deferred_leafs,
       all_impl_candidates,
+      impl_diffs,
       ..
     } = self
     else {
@@ -139,8 +140,14 @@ impl SerializedTreeVisitor<'_> {
       topology.add(parent, leaf_idx);
     }
 
+    // Validate after the deferred leafs are added, these are
+    // the only children of unsuccessful leaf goals.
+    #[cfg(debug_assertions)]
+    Self::is_valid(&nodes, &topology, &interners)?;
+
+    interners.report_stats();
     let (goals, candidates, results) = interners.take();
-    let tys = crate::tls::take_interned_tys();
+    let (tys, ty_abbreviations) = crate::tls::take_interned_tys();
 
     Ok(SerializedTree {
       root,
@@ -149,8 +156,10 @@ impl SerializedTreeVisitor<'_> {
       candidates,
       results,
       tys,
+      ty_abbreviations,
       projection_values,
       all_impl_candidates,
+      impl_diffs,
       topology,
       cycle,
       analysis,
@@ -250,9 +259,46 @@ impl<'tcx> SerializedTreeVisitor<'tcx> {
           .entry(idx)
           .or_default()
           .push(can_idx);
+        self.record_impl_diff(
+          infcx,
+          idx,
+          goal.goal().param_env,
+          tp,
+          can.impl_def_id,
+          can_idx,
+        );
       }
     }
   }
+
+  /// Record where the goal at `idx` fails to unify with the trait reference
+  /// of impl `def_id`. The goal's inference variables are resolved first, as
+  /// far as they can be.
+  fn record_impl_diff(
+    &mut self,
+    infcx: &InferCtxt<'tcx>,
+    idx: ProofNodeIdx,
+    param_env: ty::ParamEnv<'tcx>,
+    tp: ty::PolyTraitPredicate<'tcx>,
+    def_id: DefId,
+    candidate: CandidateIdx,
+  ) {
+    let trait_ref =
+      infcx.resolve_vars_if_possible(tp.map_bound(|tp| tp.trait_ref));
+    let diffs = ser::ArgDiff::between(infcx, param_env, trait_ref, def_id);
+    if diffs.is_empty() {
+      return;
+    }
+
+    let args = tls::unsafe_access_interner(|ty_interner| {
+      ser::to_value_expect(infcx, ty_interner, &diffs)
+    });
+    self
+      .impl_diffs
+      .entry(idx)
+      .or_default()
+      .push(ImplDiff { candidate, args });
+  }
 }
 
 impl<'tcx> ProofTreeVisitor<'tcx> for SerializedTreeVisitor<'tcx> {
@@ -310,7 +356,12 @@ impl<'tcx> ProofTreeVisitor<'tcx> for SerializedTreeVisitor<'tcx> {
       self.topology.add(here_idx, candidate_idx);
       self.previous = Some(candidate_idx);
 
-      self.visit_nested_roots(goal.infcx().tcx, candidate_idx, &c);
+      // rustc ICEs instantiating the nested goals of a goal over an effect
+      // variable, e.g., the ambiguous `{integer}: Add<{integer}>`. Those
+      // goals stop at their candidates.
+      if !has_effect_var(goal.goal().predicate) {
+        self.visit_nested_roots(goal.infcx().tcx, candidate_idx, &c);
+      }
 
       // FIXME: is this necessary now that we store all nodes?
       add_result_if_empty(self, candidate_idx);
@@ -321,6 +372,14 @@ impl<'tcx> ProofTreeVisitor<'tcx> for SerializedTreeVisitor<'tcx> {
   }
 }
 
+fn has_effect_var(predicate: ty::Predicate) -> bool {
+  predicate.as_trait_clause().is_some_and(|p| {
+    p.skip_binder().trait_ref.args.consts().any(|c| {
+      matches!(c.kind(), ty::ConstKind::Infer(ty::InferConst::EffectVar(_)))
+    })
+  })
+}
+
 pub fn try_serialize<'tcx>(
   goal: solve::Goal<'tcx, ty::Predicate<'tcx>>,
   result: EvaluationResult,
@@ -0,0 +1,148 @@
+//! Aggregate trait errors across a workspace.
+//!
+//! A single missing impl can surface as dozens of trait errors, e.g., after
+//! bumping a dependency. Each error is keyed by its failing trait, its self
+//! type and the first root cause Argus would show, errors sharing a key are
+//! grouped so that the few underlying problems stand out.
+//!
+//! Sites are collected per crate and merged afterwards, so everything here
+//! is plain data that round-trips through JSON.
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Number of locations kept for each group.
+const REPRESENTATIVES: usize = 3;
+
+/// Source location of a trait error, zero-indexed.
+#[derive(
+  Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorLocation {
+  pub filename: String,
+  pub line: usize,
+  pub column: usize,
+}
+
+/// A trait error reported in a body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TraitErrorSite {
+  pub location: ErrorLocation,
+  /// Path of the trait that failed to hold, absent for non-trait predicates.
+  pub failing_trait: Option<String>,
+  pub self_ty: Option<String>,
+  /// Rendered leaf goal Argus ranks first for this error, e.g.,
+  /// `MyType: Component`.
+  pub root_cause: Option<String>,
+}
+
+/// Errors sharing a key, most frequent first.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorGroup {
+  pub key: String,
+  pub count: usize,
+  pub locations: Vec<ErrorLocation>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Summary {
+  pub errors: usize,
+  pub by_trait: Vec<ErrorGroup>,
+  pub by_self_ty: Vec<ErrorGroup>,
+  pub by_root_cause: Vec<ErrorGroup>,
+}
+
+impl From<Vec<TraitErrorSite>> for Summary {
+  fn from(sites: Vec<TraitErrorSite>) -> Self {
+    Summary {
+      errors: sites.len(),
+      by_trait: group_by(&sites, |s| s.failing_trait.as_deref()),
+      by_self_ty: group_by(&sites, |s| s.self_ty.as_deref()),
+      by_root_cause: group_by(&sites, |s| s.root_cause.as_deref()),
+    }
+  }
+}
+
+fn group_by<'a>(
+  sites: &'a [TraitErrorSite],
+  key: impl Fn(&'a TraitErrorSite) -> Option<&'a str>,
+) -> Vec<ErrorGroup> {
+  let mut groups = HashMap::<_, Vec<_>>::default();
+  for site in sites {
+    if let Some(k) = key(site) {
+      groups.entry(k).or_default().push(&site.location);
+    }
+  }
+
+  let mut groups = groups
+    .into_iter()
+    .map(|(key, mut locations)| {
+      let count = locations.len();
+      locations.sort();
+      locations.dedup();
+      ErrorGroup {
+        key: key.to_string(),
+        count,
+        locations: locations
+          .into_iter()
+          .take(REPRESENTATIVES)
+          .cloned()
+          .collect(),
+      }
+    })
+    .collect::<Vec<_>>();
+  groups.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+  groups
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn groups_by_shared_root_cause() {
+    let site = |line, failing_trait: &str, root_cause: &str| TraitErrorSite {
+      location: ErrorLocation {
+        filename: "main.rs".into(),
+        line,
+        column: 0,
+      },
+      failing_trait: Some(failing_trait.into()),
+      self_ty: None,
+      root_cause: Some(root_cause.into()),
+    };
+
+    let summary = Summary::from(vec![
+      site(4, "IntoSystemConfigs", "Timer: Resource"),
+      site(3, "IntoSystemConfigs", "Timer: Resource"),
+      site(2, "IntoSystemConfigs", "Timer: Resource"),
+      site(1, "IntoSystemConfigs", "Timer: Resource"),
+      site(0, "Bundle", "Player: Component"),
+    ]);
+
+    assert_eq!(summary.errors, 5);
+    assert!(summary.by_self_ty.is_empty());
+    let keys = |groups: &[ErrorGroup]| {
+      groups
+        .iter()
+        .map(|g| (g.key.clone(), g.count))
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(keys(&summary.by_root_cause), vec![
+      ("Timer: Resource".to_string(), 4),
+      ("Player: Component".to_string(), 1),
+    ]);
+    assert_eq!(keys(&summary.by_trait)[0], ("IntoSystemConfigs".into(), 4));
+
+    let lines = summary.by_root_cause[0]
+      .locations
+      .iter()
+      .map(|l| l.line)
+      .collect::<Vec<_>>();
+    assert_eq!(lines, vec![1, 2, 3]);
+  }
+}
@@ -1,4 +1,10 @@
-use std::{env, fs, io, panic, path::Path, process::Command, sync::Arc};
+use std::{
+  collections::HashMap,
+  env, fs, io, panic,
+  path::{Path, PathBuf},
+  process::Command,
+  sync::Arc,
+};
 
 use anyhow::{Context, Result};
 use rustc_hir::BodyId;
@@ -6,16 +12,20 @@ use rustc_middle::ty::TyCtxt;
 use rustc_span::source_map::FileLoader;
 use rustc_utils::source_map::{
   filename::{Filename, FilenameIndex},
-  find_bodies::{find_bodies, find_enclosing_bodies},
   range::{CharRange, ToSpan},
 };
+use serde::{de::DeserializeOwned, Serialize};
 
 use crate::{
-  analysis,
+  analysis::{self, SerializeOptions},
+  evaluation::ErrorRank,
+  find_bodies::{find_bodies, find_enclosing_bodies, find_items},
   proof_tree::SerializedTree,
+  summary::TraitErrorSite,
   types::{
     intermediate::{Forgettable, FullData},
-    ObligationHash, ObligationsInBody, Target,
+    BodyBundle, ItemBundle, ObligationHash, ObligationsInBody, SharedBundle,
+    Target,
   },
 };
 
@@ -74,26 +84,199 @@ pub(crate) fn load_test_from_file(
   Ok((source, cfg))
 }
 
+/// Compile the test at `path` and run `analyze` on every body, the fixture
+/// shared by the helpers below.
+///
+/// # Panics
+///
+/// Panics if the test file could not be loaded.
+pub fn test_each_body(
+  path: &Path,
+  mut analyze: impl FnMut(TyCtxt<'_>, BodyId) + Send,
+) {
+  let (source, _cfg) = load_test_from_file(path).unwrap();
+  compile_normal(source, |tcx| {
+    for_each_body(tcx, |body_id, tcx| analyze(tcx, body_id));
+  });
+}
+
+/// Compile the test at `path` and run `assert_pass` on the obligations of
+/// every body.
+///
+/// # Panics
+///
+/// Panics if the test file could not be loaded.
 pub fn test_obligations_no_crash(
   path: &Path,
   mut assert_pass: impl for<'tcx> FnMut(Forgettable<FullData<'tcx>>, ObligationsInBody)
     + Send
     + Sync,
 ) {
-  let inner = || -> Result<()> {
-    let (source, _cfg) = load_test_from_file(path)?;
-    compile_normal(source, move |tcx| {
-      for_each_body(tcx, |body_id, tcx| {
-        let (full_data, obligations_in_body) =
-          analysis::body_data(tcx, body_id);
+  test_each_body(path, |tcx, body_id| {
+    let (full_data, obligations_in_body) = analysis::body_data(tcx, body_id);
 
-        assert_pass(full_data, obligations_in_body);
-      })
+    // Trees are generated lazily by `assert_pass`, within the same body.
+    analysis::entry::BODY_ID.set(body_id, || {
+      assert_pass(full_data, obligations_in_body);
     });
-    Ok(())
-  };
+  });
+}
 
-  inner().unwrap()
+/// Compile the test at `path` and run `assert_pass` on the bundle of every
+/// impl block, trait and type definition, see [`analysis::item_bundle`].
+///
+/// # Panics
+///
+/// Panics if the test file could not be loaded or an item failed to analyze.
+pub fn test_item_bundles(
+  path: &Path,
+  mut assert_pass: impl FnMut(ItemBundle) + Send + Sync,
+) {
+  let (source, _cfg) = load_test_from_file(path).unwrap();
+  compile_normal(source, move |tcx| {
+    for (_, def_id) in find_items(tcx) {
+      let bundle =
+        analysis::item_bundle(tcx, def_id).expect("failed to analyze item");
+      assert_pass(bundle);
+    }
+  });
+}
+
+/// Compile the test at `path` and run `assert_pass` on the bundle of every
+/// body, see [`analysis::bundle`].
+///
+/// # Panics
+///
+/// Panics if the test file could not be loaded or a body failed to analyze.
+pub fn test_body_bundles(
+  path: &Path,
+  assert_pass: impl FnMut(BodyBundle) + Send + Sync,
+) {
+  test_body_bundles_with(path, SerializeOptions::default(), assert_pass);
+}
+
+/// Like [`test_body_bundles`], with paths serialized following `options`.
+///
+/// # Panics
+///
+/// Panics if the test file could not be loaded or a body failed to analyze.
+pub fn test_body_bundles_with(
+  path: &Path,
+  options: SerializeOptions,
+  mut assert_pass: impl FnMut(BodyBundle) + Send + Sync,
+) {
+  test_each_body(path, |tcx, body_id| {
+    let bundle = options
+      .apply(|| analysis::bundle(tcx, body_id))
+      .expect("failed to analyze body");
+    assert_pass(bundle);
+  });
+}
+
+/// Compile the test at `path` and run `assert_pass` on every output of each
+/// body: its bundle, its obligations and the trees of its necessary
+/// obligations, each built by [`analysis::tree`]. All of them are computed
+/// with [`analysis::FORCE_ON_ERROR`] set to `force_on_error`.
+///
+/// # Panics
+///
+/// Panics if the test file could not be loaded or a body failed to analyze.
+pub fn test_body_outputs(
+  path: &Path,
+  force_on_error: bool,
+  mut assert_pass: impl FnMut(
+      BodyBundle,
+      ObligationsInBody,
+      HashMap<ObligationHash, SerializedTree>,
+    ) + Send
+    + Sync,
+) {
+  test_each_body(path, |tcx, body_id| {
+    analysis::FORCE_ON_ERROR.set(force_on_error, || {
+      let bundle =
+        analysis::bundle(tcx, body_id).expect("failed to analyze body");
+      let obligations =
+        analysis::obligations(tcx, body_id).expect("failed to analyze body");
+
+      let span = tcx.hir().body(body_id).value.span;
+      let trees = obligations
+        .obligations
+        .iter()
+        .filter(|obl| obl.is_necessary)
+        .filter_map(|obl| {
+          let target = Target {
+            hash: obl.hash,
+            span,
+          };
+          let tree = analysis::OBLIGATION_TARGET
+            .set(target, || analysis::tree(tcx, body_id));
+          tree.ok().map(|tree| (obl.hash, tree))
+        })
+        .collect();
+
+      assert_pass(bundle, obligations, trees);
+    });
+  });
+}
+
+/// Path of the test `name` in `tests/<dir>`.
+pub fn test_path(dir: &str, name: &str) -> PathBuf {
+  Path::new(env!("CARGO_MANIFEST_DIR"))
+    .join("tests")
+    .join(dir)
+    .join(name)
+}
+
+/// `value` read back as its typed mirror, see [`argus_schema`].
+///
+/// # Panics
+///
+/// Panics if `value` doesn't deserialize as `T`.
+pub fn mirror<T: DeserializeOwned>(value: &impl Serialize) -> T {
+  serde_json::from_value(serde_json::to_value(value).unwrap()).unwrap()
+}
+
+/// Mirrored bundles of every body in `tests/contained/<name>`.
+///
+/// # Panics
+///
+/// Panics if the test file could not be loaded or a body failed to analyze.
+pub fn contained_bundles(name: &str) -> Vec<argus_schema::types::BodyBundle> {
+  let mut bundles = vec![];
+  test_body_bundles(&test_path("contained", name), |bundle| {
+    bundles.push(mirror(&bundle));
+  });
+  bundles
+}
+
+/// Compile the test at `path` and run `assert_pass` on the bundle of every
+/// body, and on the bundles of all bodies with shared tables, see
+/// [`analysis::shared_bundle`].
+///
+/// # Panics
+///
+/// Panics if the test file could not be loaded or a body failed to analyze.
+pub fn test_shared_bundle(
+  path: &Path,
+  assert_pass: impl FnOnce(Vec<BodyBundle>, SharedBundle) + Send,
+) {
+  let (source, _cfg) = load_test_from_file(path).unwrap();
+  compile_normal(source, |tcx| {
+    // Bundling without shared tables resets the type interner, the shared
+    // bundles are built in a second pass rather than alongside.
+    let mut bundles = vec![];
+    for_each_body(tcx, |body_id, tcx| {
+      bundles
+        .push(analysis::bundle(tcx, body_id).expect("failed to analyze body"));
+    });
+    let mut shared = vec![];
+    for_each_body(tcx, |body_id, tcx| {
+      shared.push(
+        analysis::shared_bundle(tcx, body_id).expect("failed to analyze body"),
+      );
+    });
+    assert_pass(bundles, SharedBundle::new(shared));
+  });
 }
 
 pub fn test_locate_tree<'a, 'tcx: 'a>(
@@ -103,6 +286,13 @@ pub fn test_locate_tree<'a, 'tcx: 'a>(
   analysis::entry::pick_tree(hash, thunk)
 }
 
+/// Compile the test at `path` and build the tree of the obligation `hash`
+/// in the body at `range`.
+///
+/// # Panics
+///
+/// Panics if the test file could not be loaded or `range` doesn't match
+/// exactly one body.
 pub fn test_tree_for_target(
   path: &Path,
   mut range: CharRange,
@@ -120,8 +310,7 @@ pub fn test_tree_for_target(
       let bodies = find_enclosing_bodies(tcx, body_span).collect::<Vec<_>>();
       assert!(
         bodies.len() == 1,
-        "only one body must match a body range {:?}",
-        body_span
+        "only one body must match a body range {body_span:?}"
       );
 
       let body_id = bodies.first().unwrap();
@@ -137,9 +326,49 @@ pub fn test_tree_for_target(
     Ok(())
   };
 
-  inner().unwrap()
+  inner().unwrap();
+}
+
+/// Compile the test at `path` and rank the root causes annotated with
+/// `//~ ROOT_CAUSE:` comments, see [`crate::evaluation`].
+///
+/// # Panics
+///
+/// Panics if the test file could not be loaded.
+pub fn test_evaluate(path: &Path) -> Vec<ErrorRank> {
+  let mut ranks = vec![];
+  test_each_body(path, |tcx, body_id| {
+    match analysis::evaluate(tcx, body_id) {
+      Ok(body_ranks) => ranks.extend(body_ranks),
+      Err(e) => log::error!("Error evaluating body {body_id:?} {e:?}"),
+    }
+  });
+  ranks
 }
 
+/// Compile the test at `path` and key its trait errors, see
+/// [`crate::summary`].
+///
+/// # Panics
+///
+/// Panics if the test file could not be loaded.
+pub fn test_trait_error_sites(path: &Path) -> Vec<TraitErrorSite> {
+  let mut sites = vec![];
+  test_each_body(path, |tcx, body_id| {
+    match analysis::trait_error_sites(tcx, body_id) {
+      Ok(body_sites) => sites.extend(body_sites),
+      Err(e) => log::error!("Error summarizing body {body_id:?} {e:?}"),
+    }
+  });
+  sites
+}
+
+/// Run `test_fn` on every test file in `tests/<dir>`, filtered by the
+/// `ONLY` prefix if set.
+///
+/// # Panics
+///
+/// Panics if any test failed.
 pub fn run_in_dir(
   dir: impl AsRef<Path>,
   test_fn: impl Fn(&Path) + std::panic::RefUnwindSafe,
@@ -165,7 +394,7 @@ pub fn run_in_dir(
 
       let res = panic::catch_unwind(|| test_fn(&path));
 
-      if let Err(_) = res {
+      if res.is_err() {
         failed = true;
         eprintln!("\n\n\x1b[31m!! {test_name}\x1b[0m\n\n");
       } else {
@@ -189,10 +418,13 @@ pub fn run_in_dir(
   main().unwrap();
 }
 
+/// Visit the same bodies as the plugin. Unlike the `rustc_utils` finder this
+/// includes bodies from macro expansions, e.g., the `clone` method generated
+/// by `#[derive(Clone)]`, whose obligations carry expansion backtraces.
 pub fn for_each_body(tcx: TyCtxt, mut f: impl FnMut(BodyId, TyCtxt)) {
   find_bodies(tcx)
     .into_iter()
-    .for_each(|(_, body_id)| f(body_id, tcx))
+    .for_each(|(_, body_id)| f(body_id, tcx));
 }
 
 pub fn compile_normal(
@@ -203,7 +435,7 @@ pub fn compile_normal(
     input,
     &format!("--crate-type lib --sysroot {}", &*SYSROOT),
     callbacks,
-  )
+  );
 }
 
 #[allow(unused_must_use)]
@@ -218,7 +450,7 @@ pub fn compile(
   let args = format!(
     "rustc {DUMMY_FILE_NAME} --edition=2021 -Z next-solver -A warnings {args}",
   );
-  let args = args.split(' ').map(|s| s.to_string()).collect::<Vec<_>>();
+  let args = args.split(' ').map(ToString::to_string).collect::<Vec<_>>();
 
   // Explicitly ignore the unused return value. Many test cases are intended
   // to fail compilation, but the analysis results should still be sound.
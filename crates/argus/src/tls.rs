@@ -1,20 +1,20 @@
 //! Thread local storage for storing data processed in rustc.
 use std::cell::RefCell;
 
-use argus_ext::{
-  infer::InferCtxtExt,
-  ty::{EvaluationResultExt, PredicateExt},
-};
+use argus_ext::infer::InferCtxtExt;
 use argus_ser::{
   self as ser,
-  interner::{TyIdx, TyInterner},
+  interner::{
+    ImplHeaderIdx, PathIdx, SharedInterner, TyAbbreviations, TyIdx, TyInterner,
+  },
 };
 use index_vec::IndexVec;
 use rustc_data_structures::fx::FxIndexMap;
 use rustc_infer::{infer::InferCtxt, traits::PredicateObligation};
 use rustc_span::Span;
 pub use unsafe_tls::{
-  access_interner as unsafe_access_interner, store as unsafe_store_data,
+  access_interner as unsafe_access_interner,
+  reset_interned_values as reset_interned_tys, store as unsafe_store_data,
   take as unsafe_take_data, take_interned_values as take_interned_tys,
   FullObligationData, UODIdx,
 };
@@ -24,19 +24,19 @@ use crate::{
   types::{intermediate::Provenance, Obligation, ObligationHash},
 };
 
-const DRAIN_WINDOW: usize = 100;
-
 // NOTE: we use thread local storage to accumulate obligations
 // across call to the obligation inspector in `typeck_inspect`.
 // DO NOT set this directly, make sure to use the function `push_obligaion`.
 thread_local! {
-  static BODY_DEF_PATH: RefCell<Option<serde_json::Value>> = RefCell::default();
+  static BODY_DEF_PATH: RefCell<Option<ser::Encoded>> = RefCell::default();
 
   static OBLIGATIONS: RefCell<Vec<Provenance<Obligation>>> = RefCell::default();
 
   static TREE: RefCell<Option<SerializedTree>> = RefCell::default();
 
   static REPORTED_ERRORS: RefCell<FxIndexMap<Span, Vec<ObligationHash>>> = RefCell::default();
+
+  static SHARED_TABLES: SharedInterner = SharedInterner::default();
 }
 
 pub fn store_obligation(obl: Provenance<Obligation>) {
@@ -47,57 +47,6 @@ pub fn store_obligation(obl: Provenance<Obligation>) {
   });
 }
 
-// FIXME: using `infcx` for error implication panics, but using
-// stored contexts doesn't. Investigate why, as this certainly
-// isn't a "solution."
-//
-// NOTE this causes an internal compiler error that *should not happen*, it's setting
-// tainted by errors to be true when it shouldn't. Disabling for now.
-#[allow(unused)]
-pub fn drain_implied_ambiguities<'tcx>(
-  _infcx: &InferCtxt<'tcx>,
-  obligation: &PredicateObligation<'tcx>,
-) {
-  return;
-
-  OBLIGATIONS.with(|obls| {
-    let mut obls = obls.borrow_mut();
-
-    let mut set = Vec::new();
-
-    let lower_bound = obls.len().saturating_sub(DRAIN_WINDOW);
-    let upper_bound = obls.len();
-
-    for i in lower_bound .. upper_bound {
-      let provenance = &obls[i];
-
-      // Drain all elements that are:
-      // 1. Ambiguous and--
-      // 2. Implied by the passed obligation
-      let is_ambig = provenance.result.is_maybe();
-      let is_implied = provenance.full_data.map_or(false, |idx| {
-        unsafe_tls::borrow_in(idx, |fdata| {
-          let infcx = &fdata.infcx;
-          let previous_pred = &fdata.obligation.predicate;
-          let passing_pred = &obligation.predicate;
-          previous_pred.is_refined_by(infcx, passing_pred)
-        })
-      });
-
-      if is_ambig && is_implied {
-        set.push(i);
-      }
-    }
-
-    // TODO: we can make this faster by swapping elements to the end
-    // then truncating the vector. Except that shuffles the order, which
-    // we kind of rely on right now.
-    for i in set.into_iter().rev() {
-      obls.remove(i);
-    }
-  });
-}
-
 pub fn take_obligations() -> Vec<Provenance<Obligation>> {
   OBLIGATIONS.with(RefCell::take)
 }
@@ -143,10 +92,53 @@ pub fn take_tree() -> Option<SerializedTree> {
   TREE.with(RefCell::take)
 }
 
+/// Run `f` with types, paths and impl headers written to tables shared by
+/// every call, until they're taken with `take_shared_tables`.
+pub fn with_shared_tables<T>(f: impl FnOnce() -> T) -> T {
+  SHARED_TABLES.with(|tables| ser::with_shared_tables(tables, f))
+}
+
+/// Index of the header of impl `def_id` in the shared tables, `encode` is
+/// only called for headers not yet in the table.
+pub fn intern_impl_header(
+  def_id: rustc_hir::def_id::DefId,
+  encode: impl FnOnce() -> ser::Encoded,
+) -> ImplHeaderIdx {
+  SHARED_TABLES.with(|tables| {
+    if let Some(idx) = tables.borrow().impl_header_idx(def_id) {
+      return idx;
+    }
+    // Paths of the header are interned while encoding.
+    let header = encode();
+    tables.borrow_mut().insert_impl_header(def_id, header)
+  })
+}
+
+pub fn take_shared_tables() -> (
+  InternedTys,
+  IndexVec<PathIdx, ser::Encoded>,
+  IndexVec<ImplHeaderIdx, ser::Encoded>,
+) {
+  let tys = unsafe_tls::take_all_interned_values();
+  let tables = SHARED_TABLES.with(RefCell::take);
+  log::info!(
+    "Shared tables: paths {}, impl headers {}",
+    tables.paths_stats(),
+    tables.impl_headers_stats()
+  );
+  let (paths, impl_headers) = tables.consume();
+  (tys, paths, impl_headers)
+}
+
+/// Interned types and the placeholders of those abbreviated.
+pub type InternedTys = (IndexVec<TyIdx, ser::Encoded>, TyAbbreviations);
+
 // This is for complex obligations and their inference contexts.
 // We don't want to store the entire inference context and obligation for
 // every query, so we do it sparingly.
 mod unsafe_tls {
+  use rustc_middle::ty::TypeVisitableExt;
+
   use super::*;
   use crate::analysis::EvaluationResult;
 
@@ -186,7 +178,7 @@ mod unsafe_tls {
     OBLIGATION_DATA.with(|data| {
       let infcx = infer_ctxt.fork();
       let obl = obligation.clone();
-      let hash = infcx.predicate_hash(&obligation.predicate).into();
+      let hash = infcx.obligation_hash(obligation).into();
 
       let infcx: InferCtxt<'static> = unsafe { std::mem::transmute(infcx) };
       let obligation: PredicateObligation<'static> =
@@ -201,22 +193,6 @@ mod unsafe_tls {
     })
   }
 
-  // NOTE: ignore the 'tcx lifetime on the resulting reference. This data
-  // lives as long as the thread does, but the function can only be used
-  // from within this module so it shouldn't be an issue.
-  pub(super) fn borrow_in<'tcx, R>(
-    idx: UODIdx,
-    f: impl FnOnce(&'tcx FullObligationData<'tcx>) -> R,
-  ) -> R {
-    OBLIGATION_DATA.with(|data| {
-      let data = data.borrow();
-      let ud = data.get(idx);
-      let d: &'tcx FullObligationData<'tcx> =
-        unsafe { std::mem::transmute(ud) };
-      f(d)
-    })
-  }
-
   pub fn take<'tcx>() -> IndexVec<UODIdx, FullObligationData<'tcx>> {
     OBLIGATION_DATA.with(|data| {
       data
@@ -241,7 +217,39 @@ mod unsafe_tls {
     })
   }
 
-  pub fn take_interned_values() -> IndexVec<TyIdx, serde_json::Value> {
-    TY_INTERNER.with(RefCell::take).consume()
+  /// Types serialized since the last call, with shared tables these stay in
+  /// the shared table and only the inference variables of the caller's
+  /// context are forgotten.
+  pub fn take_interned_values() -> InternedTys {
+    if ser::has_shared_tables() {
+      TY_INTERNER.with(|interner| {
+        interner
+          .borrow_mut()
+          .retain_keys(|ty| !ty.has_infer() && !ty.has_placeholders());
+      });
+      return InternedTys::default();
+    }
+    take_all_interned_values()
+  }
+
+  pub fn take_all_interned_values() -> InternedTys {
+    let interner = TY_INTERNER.with(RefCell::take);
+    log::info!("Type interner: {}", interner.stats());
+    interner.consume_tys()
+  }
+
+  /// Drop the types left over from a previous body, e.g., one whose analysis
+  /// failed before they were taken. With shared tables the types are kept
+  /// for the next body.
+  pub fn reset_interned_values() {
+    TY_INTERNER.with(|interner| {
+      if ser::has_shared_tables() {
+        interner
+          .borrow_mut()
+          .retain_keys(|ty| !ty.has_infer() && !ty.has_placeholders());
+      } else {
+        interner.borrow_mut().reset();
+      }
+    });
   }
 }
@@ -1,10 +1,18 @@
 use std::{collections::HashMap, hash::Hash, ops::Deref, str::FromStr};
 
 use anyhow::Result;
-use argus_ser::{self as ser, interner::TyIdx};
+use argus_ext::ty::TyCtxtExt;
+use argus_ser::{
+  self as ser,
+  interner::{ImplHeaderIdx, PathIdx, TyAbbreviations, TyIdx},
+};
 use index_vec::IndexVec;
 use indexmap::IndexSet;
-use rustc_data_structures::stable_hasher::Hash64;
+use rustc_data_structures::{
+  fingerprint::Fingerprint,
+  stable_hasher::{Hash64, StableHasher},
+};
+use rustc_hir::BodyId;
 use rustc_infer::{infer::InferCtxt, traits::PredicateObligation};
 use rustc_middle::{
   traits::{
@@ -13,7 +21,10 @@ use rustc_middle::{
   },
   ty::{self, TyCtxt, TypeckResults},
 };
-use rustc_span::{def_id::DefId, Span};
+use rustc_span::{
+  def_id::{DefId, DefPathHash},
+  ExpnKind, Span,
+};
 use rustc_utils::source_map::range::{CharRange, ToSpan};
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "testing")]
@@ -40,13 +51,62 @@ pub struct BodyBundle {
   pub trees: HashMap<ObligationHash, SerializedTree>,
 }
 
+/// Bundles of every body in a crate, types, paths and impl headers are
+/// written once in tables shared by all of them. The `tys` of each body and
+/// tree are empty, their types index into the shared `tys`.
+///
+/// Tables aren't shared across crates, indices are only meaningful within
+/// the bundle they were written in.
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 #[cfg_attr(feature = "testing", derive(TS))]
 #[cfg_attr(feature = "testing", ts(export))]
+pub struct SharedBundle {
+  #[cfg_attr(feature = "testing", ts(type = "TyVal[]"))]
+  pub tys: IndexVec<TyIdx, ser::Encoded>,
+
+  #[serde(skip_serializing_if = "TyAbbreviations::is_empty")]
+  #[cfg_attr(
+    feature = "testing",
+    ts(type = "Record<TyIdx, TyIdx[]> | undefined")
+  )]
+  pub ty_abbreviations: TyAbbreviations,
+
+  #[cfg_attr(feature = "testing", ts(type = "DefinedPath[]"))]
+  pub paths: IndexVec<PathIdx, ser::Encoded>,
+
+  #[cfg_attr(feature = "testing", ts(type = "ImplHeader[]"))]
+  pub impl_headers: IndexVec<ImplHeaderIdx, ser::Encoded>,
+
+  pub bodies: Vec<BodyBundle>,
+}
+
+impl SharedBundle {
+  /// Take the shared tables the `bodies` were bundled with, see
+  /// `analysis::shared_bundle`.
+  pub fn new(bodies: Vec<BodyBundle>) -> Self {
+    let ((tys, ty_abbreviations), paths, impl_headers) =
+      tls::take_shared_tables();
+    SharedBundle {
+      tys,
+      ty_abbreviations,
+      paths,
+      impl_headers,
+      bodies,
+    }
+  }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
 pub struct ExtensionCandidates {
   #[cfg_attr(feature = "testing", ts(type = "TraitRefPrintOnlyTraitPath[]"))]
-  data: serde_json::Value,
+  data: ser::Encoded,
+
+  #[serde(skip)]
+  len: usize,
 }
 
 impl ExtensionCandidates {
@@ -54,6 +114,7 @@ impl ExtensionCandidates {
     infcx: &InferCtxt<'tcx>,
     traits: Vec<ty::TraitRef<'tcx>>,
   ) -> Self {
+    let len = traits.len();
     let wrapped = traits
       .into_iter()
       .map(ser::TraitRefPrintOnlyTraitPathDef)
@@ -61,8 +122,96 @@ impl ExtensionCandidates {
     let json = tls::unsafe_access_interner(|ty_interner| {
       ser::to_value_expect(infcx, ty_interner, &wrapped)
     });
-    ExtensionCandidates { data: json }
+    ExtensionCandidates { data: json, len }
   }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
+pub struct MethodLookup {
+  /// Range of the method name and arguments.
+  pub range: CharRange,
+  pub name: String,
+
+  /// The method picked by rustc, absent if resolution failed.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[cfg_attr(feature = "testing", ts(type = "PathDefNoArgs | undefined"))]
+  pub resolved: Option<ser::Encoded>,
+
+  /// Receiver types in the order they were probed, each is tried by
+  /// value, then autoref'd, then mutably autoref'd.
+  pub steps: Vec<ReceiverStep>,
+
+  /// Inherent methods and methods of traits in scope with this name.
+  pub candidates: Vec<MethodCandidate>,
+
+  /// Traits not in scope that provide the method for the receiver.
+  pub extension_candidates: ExtensionCandidates,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
+pub struct ReceiverStep {
+  #[cfg_attr(feature = "testing", ts(type = "Ty"))]
+  pub ty: ser::Encoded,
+  pub autoderefs: usize,
+  pub from_unsafe_deref: bool,
+  pub unsize: bool,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
+pub struct MethodCandidate {
+  pub kind: MethodCandidateKind,
+
+  #[cfg_attr(feature = "testing", ts(type = "PathDefNoArgs"))]
+  pub method: ser::Encoded,
+
+  /// First receiver step the method's `self` type accepts, absent
+  /// when the receiver never matches.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub receiver: Option<ReceiverMatch>,
+
+  /// Where clauses of the impls providing the method, or the trait
+  /// bound itself if no impl applies, that don't hold for the receiver.
+  #[cfg_attr(feature = "testing", ts(type = "Predicate[]"))]
+  pub unmet_bounds: Vec<ser::Encoded>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
+pub enum MethodCandidateKind {
+  Inherent,
+  Trait,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
+pub struct ReceiverMatch {
+  pub step: usize,
+  pub autoref: Autoref,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
+pub enum Autoref {
+  ByValue,
+  Ref,
+  RefMut,
 }
 
 #[derive(Serialize)]
@@ -78,7 +227,7 @@ pub struct Expr {
   pub is_body: bool,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "testing", derive(TS))]
 #[cfg_attr(feature = "testing", ts(export))]
 pub enum ExprKind {
@@ -86,6 +235,30 @@ pub enum ExprKind {
   CallableExpr,
   Call,
   CallArg,
+  FnSigTy,
+  WhereClause,
+  FieldTy,
+  ImplHeader,
+  AssocBinding,
+  Closure,
+}
+
+/// Relates a closure, or async block, to the requirements placed on it by
+/// the callee, e.g., `F: Fn(u32) -> String` or `F: Future + Send`, and
+/// to the failing obligations within its body that can explain them.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
+pub struct ClosureLink {
+  /// The closure expression, it holds the obligations of the body.
+  pub closure: ExprIdx,
+  /// Obligations whose self type is the closure.
+  #[cfg_attr(feature = "testing", ts(type = "ObligationIdx[]"))]
+  pub requirements: Vec<ObligationIdx>,
+  /// Necessary obligations within the closure body that failed.
+  #[cfg_attr(feature = "testing", ts(type = "ObligationIdx[]"))]
+  pub failures: Vec<ObligationIdx>,
 }
 
 #[derive(Serialize)]
@@ -127,9 +300,9 @@ pub struct TraitError {
 pub struct ObligationsInBody {
   #[serde(skip_serializing_if = "Option::is_none")]
   #[cfg_attr(feature = "testing", ts(type = "PathDefNoArgs | undefined"))]
-  name: Option<serde_json::Value>,
+  name: Option<ser::Encoded>,
 
-  hash: BodyHash,
+  pub hash: BodyHash,
 
   /// Range of the represented body.
   pub range: CharRange,
@@ -150,18 +323,41 @@ pub struct ObligationsInBody {
   #[cfg_attr(feature = "testing", ts(type = "Expr[]"))]
   pub exprs: IndexVec<ExprIdx, Expr>,
 
+  /// Method resolution at failed method calls, and at calls whose
+  /// receiver doesn't satisfy the bounds of the picked method.
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  #[cfg_attr(feature = "testing", ts(type = "MethodLookup[] | undefined"))]
+  pub method_lookups: Vec<MethodLookup>,
+
+  /// Closures and async blocks in the body with obligations on, or
+  /// within, them.
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  #[cfg_attr(feature = "testing", ts(type = "ClosureLink[] | undefined"))]
+  pub closures: Vec<ClosureLink>,
+
   #[cfg_attr(feature = "testing", ts(type = "TyVal[]"))]
-  pub tys: IndexVec<TyIdx, serde_json::Value>,
+  pub tys: IndexVec<TyIdx, ser::Encoded>,
+
+  #[serde(skip_serializing_if = "TyAbbreviations::is_empty")]
+  #[cfg_attr(
+    feature = "testing",
+    ts(type = "Record<TyIdx, TyIdx[]> | undefined")
+  )]
+  pub ty_abbreviations: TyAbbreviations,
 }
 
 impl ObligationsInBody {
+  #[allow(clippy::too_many_arguments)]
   pub fn new(
     id: Option<(&InferCtxt, DefId)>,
+    hash: BodyHash,
     range: CharRange,
     ambiguity_errors: IndexSet<AmbiguityError>,
     trait_errors: Vec<TraitError>,
     obligations: IndexVec<ObligationIdx, Obligation>,
     exprs: IndexVec<ExprIdx, Expr>,
+    method_lookups: Vec<MethodLookup>,
+    closures: Vec<ClosureLink>,
   ) -> Self {
     let json_name = id.map(|(infcx, id)| {
       tls::unsafe_access_interner(|ty_interner| {
@@ -169,30 +365,144 @@ impl ObligationsInBody {
       })
     });
 
-    let tys = tls::take_interned_tys();
+    let (tys, ty_abbreviations) = tls::take_interned_tys();
     ObligationsInBody {
       name: json_name,
-      hash: BodyHash::new(),
+      hash,
+      range,
+      ambiguity_errors,
+      trait_errors,
+      obligations,
+      exprs,
+      method_lookups,
+      closures,
+      tys,
+      ty_abbreviations,
+    }
+  }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
+pub struct ItemBundle {
+  pub filename: String,
+  pub item: ObligationsInItem,
+  pub trees: HashMap<ObligationHash, SerializedTree>,
+}
+
+/// Obligations of an impl block, trait or type definition that rustc checks
+/// outside of any body, i.e., during well-formedness and coherence checking.
+/// Expressions are the parts of the item signature, see `hir::walk_item`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
+pub struct ObligationsInItem {
+  #[cfg_attr(feature = "testing", ts(type = "PathDefNoArgs"))]
+  name: ser::Encoded,
+
+  /// Range of the represented item.
+  pub range: CharRange,
+
+  /// Orphan rule and overlap violations of an impl block.
+  pub coherence_errors: Vec<CoherenceError>,
+
+  pub ambiguity_errors: IndexSet<AmbiguityError>,
+
+  pub trait_errors: Vec<TraitError>,
+
+  #[cfg_attr(feature = "testing", ts(type = "Obligation[]"))]
+  pub obligations: IndexVec<ObligationIdx, Obligation>,
+
+  #[cfg_attr(feature = "testing", ts(type = "Expr[]"))]
+  pub exprs: IndexVec<ExprIdx, Expr>,
+
+  #[cfg_attr(feature = "testing", ts(type = "TyVal[]"))]
+  pub tys: IndexVec<TyIdx, ser::Encoded>,
+
+  #[serde(skip_serializing_if = "TyAbbreviations::is_empty")]
+  #[cfg_attr(
+    feature = "testing",
+    ts(type = "Record<TyIdx, TyIdx[]> | undefined")
+  )]
+  pub ty_abbreviations: TyAbbreviations,
+}
+
+impl ObligationsInItem {
+  pub fn new(
+    id: (&InferCtxt, DefId),
+    range: CharRange,
+    coherence_errors: Vec<CoherenceError>,
+    ambiguity_errors: IndexSet<AmbiguityError>,
+    trait_errors: Vec<TraitError>,
+    obligations: IndexVec<ObligationIdx, Obligation>,
+    exprs: IndexVec<ExprIdx, Expr>,
+  ) -> Self {
+    let (infcx, id) = id;
+    let json_name = tls::unsafe_access_interner(|ty_interner| {
+      ser::to_value_expect(infcx, ty_interner, &ser::PathDefNoArgs(id))
+    });
+
+    let (tys, ty_abbreviations) = tls::take_interned_tys();
+    ObligationsInItem {
+      name: json_name,
       range,
+      coherence_errors,
       ambiguity_errors,
       trait_errors,
       obligations,
       exprs,
       tys,
+      ty_abbreviations,
     }
   }
 }
 
-#[derive(Serialize, PartialEq, Eq, Hash)]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
+pub struct CoherenceError {
+  pub kind: CoherenceErrorKind,
+  pub range: CharRange,
+  /// Headers of the other impls that overlap with this one.
+  #[cfg_attr(feature = "testing", ts(type = "ImplHeader[]"))]
+  pub conflicting_impls: Vec<ser::Encoded>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
+pub enum CoherenceErrorKind {
+  Orphan,
+  Overlap,
+}
+
+/// Identity of a body that is stable across compilations, the owner's
+/// `DefPathHash` combined with a fingerprint of the body's source.
+#[derive(Serialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "testing", derive(TS))]
 #[cfg_attr(feature = "testing", ts(export))]
 pub struct BodyHash(
-  #[cfg_attr(feature = "testing", ts(type = "string"))] uuid::Uuid,
+  #[serde(serialize_with = "string::serialize")]
+  #[cfg_attr(feature = "testing", ts(type = "string"))]
+  Fingerprint,
 );
 
 impl BodyHash {
-  fn new() -> Self {
-    Self(uuid::Uuid::new_v4())
+  pub fn new(tcx: TyCtxt, body_id: BodyId) -> Self {
+    let owner = tcx.hir().body_owner_def_id(body_id);
+    let DefPathHash(owner_hash) = tcx.def_path_hash(owner.to_def_id());
+
+    let body_span = tcx.to_local(body_id, tcx.hir().body(body_id).value.span);
+    let mut hasher = StableHasher::new();
+    if let Ok(snippet) = tcx.sess.source_map().span_to_snippet(body_span) {
+      snippet.hash(&mut hasher);
+    }
+
+    BodyHash(owner_hash.combine(hasher.finish()))
   }
 }
 
@@ -202,14 +512,74 @@ impl BodyHash {
 #[cfg_attr(feature = "testing", ts(export))]
 pub struct Obligation {
   #[cfg_attr(feature = "testing", ts(type = "PredicateObligation"))]
-  pub obligation: serde_json::Value,
+  pub obligation: ser::Encoded,
+  /// Identity of the obligation, see `InferCtxtExt::obligation_hash`.
   pub hash: ObligationHash,
+  /// Hash of the predicate alone, shared by equal predicates at different
+  /// locations. Use this for grouping.
+  pub predicate_hash: ObligationHash,
   pub range: CharRange,
   pub kind: ObligationKind,
   pub necessity: ObligationNecessity,
   #[serde(with = "EvaluationResultDef")]
   #[cfg_attr(feature = "testing", ts(type = "EvaluationResult"))]
   pub result: EvaluationResult,
+  /// Whether the obligation is shown to the user and has a proof tree, see
+  /// [`ObligationNecessity::is_necessary`].
+  pub is_necessary: bool,
+  /// Macro invocations the obligation was expanded from, innermost first.
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  #[cfg_attr(feature = "testing", ts(type = "MacroFrame[] | undefined"))]
+  pub expansion: Vec<MacroFrame>,
+  /// The type or bound the obligation points to as written by the user,
+  /// e.g., the field type or `where` clause it comes from.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[cfg_attr(feature = "testing", ts(type = "HirNode | undefined"))]
+  pub hir: Option<ser::Encoded>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "testing", derive(TS))]
+#[cfg_attr(feature = "testing", ts(export))]
+pub struct MacroFrame {
+  /// The invocation as written, e.g., `vec!` or `#[derive(Clone)]`.
+  pub name: String,
+  pub call_site: CharRange,
+  /// Location within the macro definition, only for macros by example
+  /// whose source is available.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub def_site: Option<CharRange>,
+}
+
+impl MacroFrame {
+  /// The macro backtrace of `span`, innermost expansion first. Compiler
+  /// desugarings are skipped.
+  pub fn backtrace(tcx: TyCtxt, mut span: Span) -> Vec<Self> {
+    let source_map = tcx.sess.source_map();
+    let mut frames = vec![];
+    while span.from_expansion() {
+      let expn_data = span.ctxt().outer_expn_data();
+      if let ExpnKind::Macro(..) = expn_data.kind {
+        let def_site = expn_data
+          .def_site
+          .contains(span)
+          .then(|| CharRange::from_span(span, source_map).ok())
+          .flatten();
+
+        match CharRange::from_span(expn_data.call_site, source_map) {
+          Ok(call_site) => frames.push(MacroFrame {
+            name: expn_data.kind.descr(),
+            call_site,
+            def_site,
+          }),
+          Err(e) => log::debug!("skipping macro frame {e:?}"),
+        }
+      }
+      span = expn_data.call_site;
+    }
+    frames
+  }
 }
 
 #[derive(Serialize, Clone, Debug, PartialEq, Eq)]
@@ -222,10 +592,12 @@ pub enum ObligationNecessity {
 }
 
 impl ObligationNecessity {
+  /// Should an obligation with this necessity and result be shown to the
+  /// user? Only these obligations get a proof tree.
   pub fn is_necessary(&self, res: EvaluationResult) -> bool {
     matches!(
       (self, res),
-      (ObligationNecessity::Yes, _) // TODO: | (OnError, Err(..))
+      (ObligationNecessity::Yes, _) | (ObligationNecessity::OnError, Err(..))
     )
   }
 }
@@ -446,6 +818,7 @@ pub(super) mod intermediate {
 
   pub struct FulfillmentData<'a, 'tcx: 'a> {
     pub hash: ObligationHash,
+    pub predicate_hash: ObligationHash,
     pub obligation: &'a PredicateObligation<'tcx>,
     pub result: EvaluationResult,
   }
@@ -0,0 +1,64 @@
+use std::sync::Mutex;
+
+use argus_lib::{evaluation::EvaluationSummary, test_utils as tu};
+
+/// Rank of every annotated root cause in `contained`, by file, zero-indexed
+/// line and expected message. Update these when the heuristics change.
+const EXPECTED: &[(&str, usize, &str, Option<usize>)] = &[
+  (
+    "bevy-system-simplified.test",
+    11,
+    "Timer: SystemParam",
+    Some(0),
+  ),
+  ("foreign-impl.test", 9, "Vec<u8>: Display", Some(0)),
+  ("foreign-impl.test", 10, "Local: Display", Some(0)),
+  ("held-across-await.test", 10, "Rc<i32>: Send", Some(0)),
+  ("into-string.test", 6, "&str: IntoString", Some(0)),
+  // Known miss: the error is reported on the method probe, its goals are
+  // the autoderef steps (`Vec<Elem>: Functionality<_>`, `[Elem]: Deref`),
+  // none of which reach the `T: Marker` where clause of the impl.
+  ("missing-marker.test", 21, "Elem: Marker", None),
+  ("on-unimplemented.test", 23, "Name: Display", Some(0)),
+  ("resolved-ambiguity.test", 8, "Elem: Marker", Some(0)),
+  ("same-predicate-call-sites.test", 7, "Elem: Marker", Some(0)),
+  ("same-predicate-call-sites.test", 8, "Elem: Marker", Some(0)),
+  ("spawned-future.test", 5, "Rc<i32>: Send", Some(0)),
+];
+
+#[test_log::test]
+fn root_causes() {
+  let ranks = Mutex::new(vec![]);
+  tu::run_in_dir("contained", |path| {
+    let name = path.file_name().unwrap().to_string_lossy().to_string();
+    let file_ranks = tu::test_evaluate(path);
+    ranks
+      .lock()
+      .unwrap()
+      .extend(file_ranks.into_iter().map(|r| (name.clone(), r)));
+  });
+
+  let ranks = ranks.into_inner().unwrap();
+  let summary = EvaluationSummary::new(
+    &ranks.iter().map(|(_, r)| r.clone()).collect::<Vec<_>>(),
+  );
+  log::info!("{summary:#?}");
+
+  let mut actual = ranks
+    .iter()
+    .map(|(name, r)| {
+      (
+        name.as_str(),
+        r.expected.line,
+        r.expected.message.as_str(),
+        r.rank,
+      )
+    })
+    .collect::<Vec<_>>();
+  actual.sort_unstable();
+
+  assert_eq!(
+    actual, EXPECTED,
+    "root cause ranking changed\n\n{summary:#?}"
+  );
+}
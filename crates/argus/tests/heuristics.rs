@@ -0,0 +1,309 @@
+use argus_lib::test_utils as tu;
+use argus_schema::{
+  print::{PrintOptions, Printer},
+  proof_tree::{
+    Alternative, DiagnosticNote, ForbiddenImpl, GoalKind, HeldAcrossAwait,
+    ImplRestriction, Location, Node,
+  },
+  types::EvaluationResult,
+  CharRange,
+};
+
+/// The two types carried by `kind`, printed.
+fn kind_tys(printer: &Printer, kind: &GoalKind) -> Option<(String, String)> {
+  match kind {
+    GoalKind::HigherRankedSubtype { sub, sup } => {
+      Some((printer.ty(*sub), printer.ty(*sup)))
+    }
+    GoalKind::Unsize { source, target } => {
+      Some((printer.ty(*source), printer.ty(*target)))
+    }
+    _ => None,
+  }
+}
+
+type Ranked = (String, GoalKind, Option<(String, String)>, usize);
+
+/// Ranked goals of every failing obligation in `tests/contained/<name>`,
+/// printed with their kind, its types and the momentum of the cheapest set
+/// they're in, keyed by the printed obligation.
+fn ranked_goals(name: &str) -> Vec<(String, Vec<Ranked>)> {
+  let mut ranked = vec![];
+  for bundle in tu::contained_bundles(name) {
+    let body_printer = Printer::new(&bundle.body.tys, PrintOptions::default());
+    for obligation in &bundle.body.obligations {
+      if obligation.result != EvaluationResult::No {
+        continue;
+      }
+      let Some(tree) = bundle.trees.get(&obligation.hash) else {
+        continue;
+      };
+      let root = body_printer.predicate(&obligation.obligation.predicate);
+      let printer = Printer::new(&tree.tys, PrintOptions::default());
+      let kinds = tree
+        .analysis
+        .problematic_sets
+        .iter()
+        .flat_map(|set| set.goals.iter().map(move |h| (set.momentum, h)))
+        .map(|(momentum, h)| (h.idx, h.kind.clone(), momentum))
+        .collect::<Vec<_>>();
+      let goals = tree
+        .analysis
+        .ranked_goals()
+        .into_iter()
+        .map(|idx| {
+          let Node::Goal(g) = tree.nodes[idx] else {
+            panic!("not a goal {idx}")
+          };
+          let (_, kind, _) = kinds.iter().find(|(i, ..)| *i == idx).unwrap();
+          let momentum = kinds
+            .iter()
+            .filter(|(i, ..)| *i == idx)
+            .map(|(.., m)| *m)
+            .min()
+            .unwrap();
+          let tys = kind_tys(&printer, kind);
+          let goal = printer.predicate(&tree.goals[g].value.predicate);
+          (goal, kind.clone(), tys, momentum)
+        })
+        .collect::<Vec<_>>();
+      ranked.push((root, goals));
+    }
+  }
+  ranked
+}
+
+/// Ranked goals for the failing obligation `root` in `name`, with the
+/// types of their kind and their momentum.
+fn ranked_tys_for(name: &str, root: &str) -> Vec<Ranked> {
+  let ranked = ranked_goals(name);
+  ranked
+    .iter()
+    .find(|(r, _)| r == root)
+    .unwrap_or_else(|| panic!("no failing `{root}` in {ranked:#?}"))
+    .1
+    .clone()
+}
+
+/// Ranked goals for the failing obligation `root` in `name`.
+fn ranked_for(name: &str, root: &str) -> Vec<(String, GoalKind)> {
+  ranked_tys_for(name, root)
+    .into_iter()
+    .map(|(goal, kind, ..)| (goal, kind))
+    .collect()
+}
+
+fn forbidden(rule: ImplRestriction) -> GoalKind {
+  let alternatives = match rule {
+    ImplRestriction::Orphan => {
+      vec![
+        Alternative::Newtype,
+        Alternative::LocalTrait,
+        Alternative::TyChange,
+      ]
+    }
+    ImplRestriction::Overlap => {
+      vec![Alternative::Newtype, Alternative::TyChange]
+    }
+  };
+  GoalKind::ForbiddenImpl(ForbiddenImpl { rule, alternatives })
+}
+
+#[test_log::test]
+fn forbidden_impls() {
+  // Neither `Vec` nor `Display` are local.
+  assert_eq!(ranked_for("foreign-impl.test", "Vec<u8>: Display"), [(
+    "Vec<u8>: Display".to_owned(),
+    forbidden(ImplRestriction::Orphan)
+  )]);
+
+  assert_eq!(
+    ranked_for("overlapping-impl.test", "(Marked, T): Describe"),
+    [(
+      "(Marked, T): Describe".to_owned(),
+      forbidden(ImplRestriction::Overlap)
+    )]
+  );
+
+  // The where clause of the existing impl can never hold.
+  assert_eq!(
+    ranked_for("overlapping-impl.test", "(Unmarked, T): Describe"),
+    [("(Unmarked, T): Describe".to_owned(), GoalKind::Trait {
+      self_: Location::External,
+      trait_: Location::Local,
+    })]
+  );
+}
+
+/// Values held across an await for every failing obligation in
+/// `tests/contained/<name>`, with the printed leaf goal and the printed
+/// owner of the field.
+fn held_across(name: &str) -> Vec<(HeldAcrossAwait, String, Option<String>)> {
+  let mut held = vec![];
+  for bundle in tu::contained_bundles(name) {
+    for tree in bundle.trees.values() {
+      let printer = Printer::new(&tree.tys, PrintOptions::default());
+      let goal = |idx| {
+        let Node::Goal(g) = tree.nodes[idx] else {
+          panic!("not a goal {idx}")
+        };
+        printer.predicate(&tree.goals[g].value.predicate)
+      };
+      for h in &tree.analysis.held_across_await {
+        let owner = h.field.as_ref().map(|f| goal(f.owner));
+        held.push((h.clone(), goal(h.leaf), owner));
+      }
+    }
+  }
+  held
+}
+
+/// Start and end of `range` as zero-based `(line, column)`.
+fn span(range: Option<CharRange>) -> Option<((usize, usize), (usize, usize))> {
+  range.map(|r| ((r.start.line, r.start.column), (r.end.line, r.end.column)))
+}
+
+#[test_log::test]
+fn held_across_await() {
+  // `w` in `let w = ...` and the `await` of `yield_now().await`.
+  let cases = [
+    (
+      "held-across-await.test",
+      ((4, 8), (4, 9)),
+      ((5, 16), (5, 21)),
+    ),
+    // The async block is defined in the analyzed body.
+    (
+      "spawned-future.test",
+      ((6, 12), (6, 13)),
+      ((7, 20), (7, 25)),
+    ),
+  ];
+
+  for (name, value, await_) in cases {
+    let held = held_across(name);
+    assert_eq!(held.len(), 1, "{name}: {held:#?}");
+    let (held, leaf, owner) = &held[0];
+    assert_eq!(leaf, "Rc<i32>: Send", "{name}");
+    assert_eq!(held.name.as_deref(), Some("w"), "{name}");
+    assert_eq!(span(held.value_range), Some(value), "{name}");
+    assert_eq!(span(held.await_range), Some(await_), "{name}");
+    assert_eq!(
+      held.field.as_ref().map(|f| f.field.as_str()),
+      Some("count"),
+      "{name}"
+    );
+    assert_eq!(owner.as_deref(), Some("Wrapper: Send"), "{name}");
+  }
+}
+
+/// Notes of the goals printed as `goal` in `tests/contained/<name>`.
+fn diagnostics(name: &str, goal: &str) -> Vec<Option<DiagnosticNote>> {
+  let mut notes = vec![];
+  for bundle in tu::contained_bundles(name) {
+    for tree in bundle.trees.values() {
+      let printer = Printer::new(&tree.tys, PrintOptions::default());
+      notes.extend(
+        tree
+          .goals
+          .iter()
+          .filter(|g| printer.predicate(&g.value.predicate) == goal)
+          .map(|g| g.diagnostic.clone()),
+      );
+    }
+  }
+  notes
+}
+
+#[test_log::test]
+fn on_unimplemented_note() {
+  let notes = diagnostics("on-unimplemented.test", "Name: Query<Users>");
+  assert!(!notes.is_empty());
+  for note in notes {
+    assert_eq!(
+      note,
+      Some(DiagnosticNote {
+        message: Some("`Name` is not a valid query for `Users`".to_owned()),
+        label: Some("invalid query".to_owned()),
+        notes: vec!["only columns of `Users` can be queried".to_owned()],
+      })
+    );
+  }
+
+  // Goals on traits without the attribute have no note.
+  for note in diagnostics("on-unimplemented.test", "Name: Column") {
+    assert_eq!(note, None);
+  }
+}
+
+#[test_log::test]
+fn do_not_recommend_penalty() {
+  // The set of the `do_not_recommend` impl weighs 0 against 1 for the
+  // other impl (`Display` is external), the penalty ranks it last.
+  assert_eq!(ranked_for("on-unimplemented.test", "Name: Query<Users>"), [
+    ("Name: Display".to_owned(), GoalKind::Trait {
+      self_: Location::Local,
+      trait_: Location::External,
+    }),
+    ("Name: Column".to_owned(), GoalKind::Trait {
+      self_: Location::Local,
+      trait_: Location::Local,
+    }),
+  ]);
+}
+
+#[test_log::test]
+fn higher_ranked_closure() {
+  // The closure returns its argument for the one lifetime it was first
+  // called with, `apply` needs it for all of them.
+  let root = "for<'a> <closure as FnOnce(&'a u8)>::Output == &'a u8";
+  let ranked = ranked_tys_for("higher-ranked-closure.test", root);
+  assert_eq!(ranked.len(), 1, "{ranked:#?}");
+  let (goal, kind, tys, momentum) = &ranked[0];
+  assert_eq!(goal, root);
+  assert!(
+    matches!(kind, GoalKind::HigherRankedSubtype { .. }),
+    "{kind:?}"
+  );
+  assert_eq!(tys, &Some(("&u8".to_owned(), "&'a u8".to_owned())));
+  assert_eq!(*momentum, 3);
+}
+
+#[test_log::test]
+fn unsize_bound() {
+  // The local trait is cheapest, an unsizing is fixed with a cast or a
+  // box, and the projection means changing the type.
+  let ranked = ranked_tys_for("unsize-bound.test", "[u8; 3]: Widen<M>");
+  let [marker, unsize, item] = &ranked[..] else {
+    panic!("{ranked:#?}")
+  };
+
+  assert_eq!(
+    marker,
+    &(
+      "[u8; 3]: Marker".to_owned(),
+      GoalKind::Trait {
+        self_: Location::External,
+        trait_: Location::Local,
+      },
+      None,
+      1
+    )
+  );
+
+  let (goal, kind, tys, momentum) = unsize;
+  assert_eq!(goal, "[u8; 3]: Unsize<[u16]>");
+  assert!(matches!(kind, GoalKind::Unsize { .. }), "{kind:?}");
+  assert_eq!(tys, &Some(("[u8; 3]".to_owned(), "[u16]".to_owned())));
+  assert_eq!(*momentum, 3);
+
+  assert_eq!(
+    item,
+    &(
+      "<[u8; 3] as IntoIterator>::Item == u16".to_owned(),
+      GoalKind::TyChange,
+      None,
+      4
+    )
+  );
+}
@@ -0,0 +1,225 @@
+use argus_lib::{test_utils as tu, types::CoherenceErrorKind};
+use argus_schema::{
+  hir::{
+    Dropped, GenericArg, GenericArgs, GenericArgsParentheses, GenericBound,
+    GenericParam, GenericParamKind, Ident, Lifetime, Node, ParamName, Path,
+    PathSegment, PolyTraitRef, QPath, Res, Term, TraitBoundModifier, TraitRef,
+    Ty, TyKind, TypeBinding, TypeBindingKind, WhereBoundPredicate,
+    WherePredicate,
+  },
+  print::{PrintOptions, Printer},
+  proof_tree::{CandidateData, SerializedTree},
+};
+use rustc_utils::source_map::range::CharRange;
+
+/// Every test in `items` has an ill-formed or incoherent impl, trait or
+/// type definition.
+#[test_log::test]
+fn items() {
+  tu::run_in_dir("items", |path| {
+    let mut found_error = false;
+    tu::test_item_bundles(path, |bundle| {
+      let item = &bundle.item;
+      found_error |=
+        !item.trait_errors.is_empty() || !item.coherence_errors.is_empty();
+
+      // Well-formedness errors point into the signature, these come with
+      // the types and bounds as written.
+      assert!(
+        item.trait_errors.is_empty()
+          || item.obligations.iter().any(|obl| obl.hir.is_some()),
+        "no user-written types or bounds in {path:?}"
+      );
+
+      let missing = item
+        .obligations
+        .iter()
+        .filter(|obl| obl.is_necessary && !bundle.trees.contains_key(&obl.hash))
+        .collect::<Vec<_>>();
+      assert!(missing.is_empty(), "missing trees {missing:#?}");
+    });
+    assert!(found_error, "no item errors in {path:?}");
+  });
+}
+
+/// Kind and range of each coherence error, the range is the impl header.
+#[test_log::test]
+fn coherence_errors() {
+  // Kind, range and the number of conflicting impls.
+  type Error = (CoherenceErrorKind, ((usize, usize), (usize, usize)), usize);
+  let expected: [(&str, &[Error]); 3] = [
+    ("orphan-impl.test", &[(
+      CoherenceErrorKind::Orphan,
+      ((2, 0), (2, 29)),
+      0,
+    )]),
+    ("overlapping-impls.test", &[
+      (CoherenceErrorKind::Overlap, ((4, 0), (4, 32)), 1),
+      (CoherenceErrorKind::Overlap, ((10, 0), (10, 21)), 1),
+    ]),
+    ("supertrait-bound.test", &[]),
+  ];
+
+  for (name, errors) in expected {
+    let path = tu::test_path("items", name);
+    let mut actual = vec![];
+    tu::test_item_bundles(&path, |bundle| {
+      actual.extend(bundle.item.coherence_errors.iter().map(|error| {
+        let CharRange { start, end, .. } = error.range;
+        (
+          error.kind,
+          ((start.line, start.column), (end.line, end.column)),
+          error.conflicting_impls.len(),
+        )
+      }));
+    });
+    assert_eq!(actual, errors, "{name}");
+  }
+}
+
+fn ident(name: &str) -> Ident {
+  Ident { name: name.into() }
+}
+
+fn segment(name: &str, args: Vec<GenericArg>) -> PathSegment {
+  PathSegment {
+    ident: ident(name),
+    res: Res::Def(Dropped()),
+    args: (!args.is_empty()).then_some(GenericArgs {
+      args,
+      bindings: vec![],
+      parenthesized: GenericArgsParentheses::No,
+    }),
+  }
+}
+
+fn path(segment: PathSegment) -> Path {
+  Path {
+    segments: vec![segment],
+  }
+}
+
+fn path_ty(name: &str, args: Vec<GenericArg>) -> Ty {
+  Ty {
+    kind: TyKind::Path(QPath::Resolved(None, path(segment(name, args)))),
+  }
+}
+
+/// `Bytes<'lifetime>`, an elided lifetime has an empty name.
+fn bytes(lifetime: &str) -> GenericArg {
+  GenericArg::Type(path_ty("Bytes", vec![GenericArg::Lifetime(Lifetime {
+    ident: ident(lifetime),
+  })]))
+}
+
+/// Types and bounds are serialized as written, with the type alias and its
+/// elided lifetimes, both where obligations point and in impl headers.
+#[test_log::test]
+fn hir_as_written() {
+  let fresh = GenericParam {
+    name: ParamName::Fresh,
+    kind: GenericParamKind::Lifetime {},
+  };
+
+  // `Shown<Bytes<'_>>`
+  let shown_bytes = path_ty("Shown", vec![bytes("'_")]);
+
+  // `From<Shown<Box<dyn Fn(Bytes)>>>`
+  let fn_bytes = PathSegment {
+    ident: ident("Fn"),
+    res: Res::Def(Dropped()),
+    args: Some(GenericArgs {
+      args: vec![GenericArg::Type(Ty {
+        kind: TyKind::Tup(vec![path_ty("Bytes", vec![GenericArg::Lifetime(
+          Lifetime { ident: ident("") },
+        )])]),
+      })],
+      bindings: vec![TypeBinding {
+        ident: ident("Output"),
+        gen_args: GenericArgs {
+          args: vec![],
+          bindings: vec![],
+          parenthesized: GenericArgsParentheses::No,
+        },
+        kind: TypeBindingKind::Equality {
+          term: Term::Ty(Ty {
+            kind: TyKind::Tup(vec![]),
+          }),
+        },
+      }],
+      parenthesized: GenericArgsParentheses::ParenSugar,
+    }),
+  };
+  let dyn_fn = Ty {
+    kind: TyKind::TraitObject(
+      vec![PolyTraitRef {
+        bound_generic_params: vec![fresh.clone()],
+        trait_ref: TraitRef {
+          path: path(fn_bytes),
+        },
+      }],
+      Lifetime { ident: ident("") },
+    ),
+  };
+  let from_shown = TraitRef {
+    path: path(segment("From", vec![GenericArg::Type(path_ty(
+      "Shown",
+      vec![GenericArg::Type(path_ty("Box", vec![GenericArg::Type(
+        dyn_fn,
+      )]))],
+    ))])),
+  };
+
+  let path = tu::test_path("items", "type-alias.test");
+  let mut nodes = vec![];
+  tu::test_item_bundles(&path, |bundle| {
+    nodes.extend(
+      bundle
+        .item
+        .obligations
+        .iter()
+        .map(|obl| tu::mirror::<Option<Node>>(&obl.hir)),
+    );
+  });
+  assert_eq!(nodes, [
+    Some(Node::Ty(shown_bytes.clone())),
+    Some(Node::TraitRef(from_shown.clone()))
+  ]);
+
+  // The candidates of `Shown<u8>: Render` are both local impls.
+  let mut impls = vec![];
+  tu::test_body_bundles(&path, |bundle| {
+    for tree in bundle.trees.values() {
+      let tree: SerializedTree = tu::mirror(tree);
+      let printer = Printer::new(&tree.tys, PrintOptions::default());
+      for candidate in &tree.candidates {
+        if let CandidateData::Impl { hd, .. } = candidate {
+          let hd = printer.resolve_impl_header(hd).unwrap();
+          let Some(hir) = &hd.hir else { continue };
+          if !impls.contains(hir) {
+            impls.push(hir.clone());
+          }
+        }
+      }
+    }
+  });
+  let [bytes_impl, vec_impl] = &impls[..] else {
+    panic!("expected two local impls, found {impls:#?}");
+  };
+
+  assert_eq!(bytes_impl.generics.params, [fresh]);
+  assert_eq!(bytes_impl.self_ty, shown_bytes);
+  assert_eq!(vec_impl.generics.predicates, [
+    WherePredicate::BoundPredicate(WhereBoundPredicate {
+      bound_generic_params: vec![],
+      bounded_ty: path_ty("T", vec![]),
+      bounds: vec![GenericBound::Trait(
+        PolyTraitRef {
+          bound_generic_params: vec![],
+          trait_ref: from_shown,
+        },
+        TraitBoundModifier::None
+      )],
+    })
+  ]);
+}
@@ -1,4 +1,18 @@
-use argus_lib::{test_utils as tu, types::ObligationNecessity};
+use std::sync::Mutex;
+
+use argus_lib::{
+  test_utils as tu,
+  types::{Expr, ExprKind, Obligation, ObligationIdx},
+};
+use argus_schema::{
+  print::{PrintOptions, Printer},
+  types::{
+    Autoref, BodyBundle, EvaluationResult, MethodCandidateKind,
+    ObligationNecessity, ObligationsInBody,
+  },
+};
+use index_vec::IndexVec;
+use rustc_utils::source_map::range::CharRange;
 
 #[test_log::test]
 fn obligations() {
@@ -8,10 +22,7 @@ fn obligations() {
       let t = (&*full_data, &obligations);
 
       for obl in t.1.obligations.iter() {
-        if obl.necessity == ObligationNecessity::Yes
-          || (obl.necessity == ObligationNecessity::OnError
-            && obl.result.is_err())
-        {
+        if obl.is_necessary {
           let res = tu::test_locate_tree(obl.hash, || t);
           if res.is_err() {
             missing.push((res, obl))
@@ -29,3 +40,353 @@ fn obligations() {
     });
   });
 }
+
+/// Equal predicates at two call sites are distinct obligations, each with
+/// its own tree, that share a predicate hash.
+#[test_log::test]
+fn same_predicate_call_sites() {
+  let path = tu::test_path("contained", "same-predicate-call-sites.test");
+  let failed = Mutex::new(vec![]);
+  tu::test_obligations_no_crash(&path, |full_data, obligations| {
+    // Suggestion probes, e.g. `&Elem: Marker`, point at the signature of
+    // `main`, only keep the failures at the two `needs_marker(Elem)` calls.
+    for obl in &obligations.obligations {
+      if !(obl.result.is_err()
+        && obl.is_necessary
+        && [7, 8].contains(&obl.range.start.line))
+      {
+        continue;
+      }
+
+      // Neither was dropped as a duplicate by `tls::store_obligation`.
+      let tree = tu::test_locate_tree(obl.hash, || (&*full_data, &obligations));
+      assert!(tree.is_ok(), "missing tree {obl:#?}");
+      failed.lock().unwrap().push((
+        obl.range.start.line,
+        obl.hash,
+        obl.predicate_hash,
+      ));
+    }
+  });
+
+  let failed = failed.into_inner().unwrap();
+  let lines = failed.iter().map(|(line, ..)| *line).collect::<Vec<_>>();
+  assert_eq!(lines, [7, 8], "{failed:#?}");
+
+  let [(_, first, first_predicate), (_, second, second_predicate)] = failed[..]
+  else {
+    unreachable!()
+  };
+  assert_ne!(first, second);
+  assert_eq!(first_predicate, second_predicate);
+}
+
+/// The ambiguous `T: Marker` of `needs_marker(elem)` is resolved once
+/// `elem` is known to be an `Elem`, only the failing goal is kept.
+#[test_log::test]
+fn resolved_ambiguity() {
+  let path = tu::test_path("contained", "resolved-ambiguity.test");
+  let call_site = Mutex::new(vec![]);
+  tu::test_body_bundles(&path, |bundle| {
+    let bundle: BodyBundle =
+      serde_json::from_value(serde_json::to_value(&bundle).unwrap()).unwrap();
+    let printer = Printer::new(&bundle.body.tys, PrintOptions::default());
+    call_site.lock().unwrap().extend(
+      bundle
+        .body
+        .obligations
+        .iter()
+        .filter(|obl| obl.range.start.line == 8)
+        .map(|obl| (printer.predicate(&obl.obligation.predicate), obl.result)),
+    );
+  });
+
+  let call_site = call_site.into_inner().unwrap();
+  let ambiguous = ("T: Marker".to_owned(), EvaluationResult::MaybeAmbiguity);
+  let failed = ("Elem: Marker".to_owned(), EvaluationResult::No);
+  assert!(!call_site.contains(&ambiguous), "{call_site:#?}");
+  assert!(call_site.contains(&failed), "{call_site:#?}");
+}
+
+/// Every test in `macros` has failing obligations expanded from a macro.
+#[test_log::test]
+fn macro_backtraces() {
+  tu::run_in_dir("macros", |path| {
+    let mut frames = vec![];
+    tu::test_obligations_no_crash(path, |_, obligations| {
+      frames.extend(
+        obligations
+          .obligations
+          .into_iter()
+          .filter(|obl| obl.result.is_err())
+          .flat_map(|obl| obl.expansion),
+      );
+    });
+
+    assert!(
+      frames.iter().any(|f| f.name == "#[derive(Clone)]"),
+      "missing derive frame {frames:#?}"
+    );
+    assert!(
+      frames
+        .iter()
+        .any(|f| f.name == "check_copy!" && f.def_site.is_some()),
+      "missing macro by example frame {frames:#?}"
+    );
+  });
+}
+
+/// Bodies generated by a derive are analyzed like any other, without them
+/// the `#[derive(Clone)]` frame above would never be reported.
+#[test_log::test]
+fn derived_bodies() {
+  let path = tu::test_path("macros", "expanded-bounds.test");
+  let mut bodies = 0;
+  let mut derived = 0;
+  tu::test_obligations_no_crash(&path, |_, obligations| {
+    bodies += 1;
+    if obligations
+      .obligations
+      .iter()
+      .any(|obl| obl.expansion.iter().any(|f| f.name == "#[derive(Clone)]"))
+    {
+      derived += 1;
+    }
+  });
+
+  // `needs_copy`, `main` and the derived `clone`.
+  assert_eq!(bodies, 3);
+  assert_eq!(derived, 1);
+}
+
+/// A printed method lookup: its receiver steps with their autoderefs, its
+/// candidates with the step they match and their unmet bounds, and its
+/// extension traits.
+#[derive(Debug, PartialEq)]
+struct PrintedLookup {
+  name: String,
+  steps: Vec<(String, usize)>,
+  candidates: Vec<PrintedCandidate>,
+  extension_candidates: Vec<String>,
+}
+
+type PrintedCandidate = (
+  MethodCandidateKind,
+  String,
+  Option<(usize, Autoref)>,
+  Vec<String>,
+);
+
+/// Method lookups of every body in `tests/methods/<name>`, printed.
+fn method_lookups(name: &str) -> Vec<PrintedLookup> {
+  let mut lookups = vec![];
+  tu::test_body_bundles(&tu::test_path("methods", name), |bundle| {
+    let bundle: BodyBundle = tu::mirror(&bundle);
+    let printer = Printer::new(&bundle.body.tys, PrintOptions {
+      trim_paths: false,
+      ..PrintOptions::default()
+    });
+    for lookup in &bundle.body.method_lookups {
+      lookups.push(PrintedLookup {
+        name: lookup.name.clone(),
+        steps: lookup
+          .steps
+          .iter()
+          .map(|step| (printer.ty(step.ty), step.autoderefs))
+          .collect(),
+        candidates: lookup
+          .candidates
+          .iter()
+          .map(|c| {
+            let receiver = c.receiver.map(|m| (m.step, m.autoref));
+            let unmet = c.unmet_bounds.iter().map(|p| printer.predicate(p));
+            (c.kind, printer.path(&c.method), receiver, unmet.collect())
+          })
+          .collect(),
+        extension_candidates: lookup
+          .extension_candidates
+          .data
+          .iter()
+          .map(|path| printer.path(path))
+          .collect(),
+      });
+    }
+  });
+  lookups
+}
+
+#[test_log::test]
+fn method_lookup_unmet_bound() {
+  // `build` exists, but only for states that are `Ready`.
+  assert_eq!(method_lookups("typestate.test"), [PrintedLookup {
+    name: "build".to_owned(),
+    steps: vec![("Builder<Empty>".to_owned(), 0)],
+    candidates: vec![(
+      MethodCandidateKind::Inherent,
+      "Builder<S>::build".to_owned(),
+      Some((0, Autoref::ByValue)),
+      vec!["Empty: Ready".to_owned()],
+    )],
+    extension_candidates: vec![],
+  }]);
+}
+
+#[test_log::test]
+fn method_lookup_extension_trait() {
+  // `str` is reached by autoderef, `shout::private::Loud` can't be named
+  // from `main` and isn't suggested.
+  assert_eq!(method_lookups("trait-not-in-scope.test"), [PrintedLookup {
+    name: "shout".to_owned(),
+    steps: vec![
+      ("alloc::string::String".to_owned(), 0),
+      ("str".to_owned(), 1),
+    ],
+    candidates: vec![],
+    extension_candidates: vec!["shout::Shout".to_owned()],
+  }]);
+}
+
+/// Every test in `closures` passes a closure, or async block, with a
+/// failing obligation in its body to a callee whose bound fails.
+#[test_log::test]
+fn closure_links() {
+  tu::run_in_dir("closures", |path| {
+    let mut links = 0;
+    tu::test_obligations_no_crash(path, |_, obligations| {
+      for link in &obligations.closures {
+        let closure = &obligations.exprs[link.closure];
+        assert!(matches!(closure.kind, ExprKind::Closure));
+        assert!(
+          !link.requirements.is_empty() && !link.failures.is_empty(),
+          "unlinked closure {link:#?}"
+        );
+        assert!(
+          link
+            .failures
+            .iter()
+            .all(|i| closure.obligations.contains(i)),
+          "failures outside of the closure {link:#?}"
+        );
+        links += 1;
+      }
+    });
+
+    assert_eq!(links, 1, "closure bodies should be nested in their parent");
+  });
+}
+type Range = ((usize, usize), (usize, usize));
+
+/// Kind and range of the expressions in `exprs` holding a necessary failing
+/// obligation.
+fn failing_exprs<'a>(
+  exprs: impl IntoIterator<Item = &'a Expr>,
+  obligations: &IndexVec<ObligationIdx, Obligation>,
+) -> Vec<(ExprKind, Range)> {
+  exprs
+    .into_iter()
+    .filter(|expr| {
+      expr.obligations.iter().any(|&idx| {
+        let obl = &obligations[idx];
+        obl.result.is_err() && obl.is_necessary
+      })
+    })
+    .map(|expr| {
+      let CharRange { start, end, .. } = expr.range;
+      (
+        expr.kind,
+        ((start.line, start.column), (end.line, end.column)),
+      )
+    })
+    .collect()
+}
+
+/// Obligations that point into a signature are binned under the part of
+/// it they point to, each bin kind becomes the expression kind.
+#[test_log::test]
+fn signature_bins() {
+  // `[u8]: Sized` for the return type of `bytes`.
+  let mut exprs = vec![];
+  tu::test_obligations_no_crash(
+    &tu::test_path("contained", "unsized-return.test"),
+    |_, body| exprs.extend(failing_exprs(&body.exprs, &body.obligations)),
+  );
+  assert!(
+    exprs.contains(&(ExprKind::FnSigTy, ((0, 14), (0, 18)))),
+    "{exprs:#?}"
+  );
+
+  let items = [
+    ("field-type.test", ExprKind::FieldTy, ((7, 8), (7, 20))),
+    (
+      "where-clause.test",
+      ExprKind::WhereClause,
+      ((8, 2), (8, 17)),
+    ),
+    ("impl-header.test", ExprKind::ImplHeader, ((6, 5), (6, 17))),
+    (
+      "supertrait-bound.test",
+      ExprKind::ImplHeader,
+      ((19, 20), (19, 30)),
+    ),
+    (
+      "assoc-type-bound.test",
+      ExprKind::AssocBinding,
+      ((11, 14), (11, 22)),
+    ),
+  ];
+  for (name, kind, range) in items {
+    let mut exprs = vec![];
+    tu::test_item_bundles(&tu::test_path("items", name), |bundle| {
+      exprs.extend(failing_exprs(&bundle.item.exprs, &bundle.item.obligations));
+    });
+    assert_eq!(exprs, [(kind, range)], "{name}");
+  }
+}
+
+/// Obligations of `{integer}: Add<{integer}>` in `forced-on-error.test`
+/// with their necessity, and whether the bundle and the tree output have a
+/// tree for them.
+fn lang_item_ambiguities(
+  force_on_error: bool,
+) -> Vec<(ObligationNecessity, bool, bool, bool)> {
+  let path = tu::test_path("contained", "forced-on-error.test");
+  let mut found = vec![];
+  tu::test_body_outputs(&path, force_on_error, |bundle, obligations, trees| {
+    let mirrored: ObligationsInBody = tu::mirror(&obligations);
+    let printer = Printer::new(&mirrored.tys, PrintOptions::default());
+    for (obl, hash) in mirrored
+      .obligations
+      .iter()
+      .zip(obligations.obligations.iter().map(|obl| obl.hash))
+    {
+      if printer.predicate(&obl.obligation.predicate)
+        == "{integer}: Add<{integer}>"
+      {
+        found.push((
+          obl.necessity,
+          obl.is_necessary,
+          bundle.trees.contains_key(&hash),
+          trees.contains_key(&hash),
+        ));
+      }
+    }
+  });
+  found
+}
+
+/// Ambiguous predicates on lang-item traits are `OnError`, they're only
+/// shown and given a tree once forced.
+#[test_log::test]
+fn force_on_error() {
+  let hidden = lang_item_ambiguities(false);
+  assert!(!hidden.is_empty());
+  for found in hidden {
+    assert_eq!(found, (ObligationNecessity::OnError, false, false, false));
+  }
+
+  let forced = lang_item_ambiguities(true);
+  assert!(!forced.is_empty());
+  for found in forced {
+    assert_eq!(found, (ObligationNecessity::Yes, true, true, true));
+  }
+}
@@ -0,0 +1,361 @@
+use std::sync::Mutex;
+
+use argus_lib::{
+  analysis::{PathStyle, SerializeOptions},
+  test_utils as tu,
+};
+use argus_schema::{
+  print::{PrintOptions, Printer},
+  proof_tree::{CandidateData, Node, SerializedTree},
+  ty::TyVal,
+  types::{BodyBundle, SharedBundle},
+};
+
+/// Closure spans and the `_` of unnamed inference variables aren't
+/// serialized, Argus prints the closure path and the variable's origin
+/// instead.
+fn is_comparable(rendered: &str) -> bool {
+  !rendered.contains('@')
+    && !rendered
+      .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+      .any(|word| word == "_")
+}
+
+/// Goals printed from their serialized form must match rustc's rendering.
+fn assert_goals_match_rustc(dir: &str) {
+  tu::run_in_dir(dir, |path| {
+    tu::test_body_bundles(path, |bundle| {
+      for tree in bundle.trees.values() {
+        let mirror: SerializedTree = tu::mirror(tree);
+        let printer = Printer::new(&mirror.tys, PrintOptions::default());
+        for idx in tree.analysis.ranked_goals() {
+          let Node::Goal(goal) = mirror.nodes[idx.index()] else {
+            panic!("ranked node {idx:?} isn't a goal");
+          };
+          let expected = tree.analysis.rendered(idx).unwrap();
+          if !is_comparable(expected) {
+            continue;
+          }
+          let predicate = &mirror.goals[goal].value.predicate;
+          assert_eq!(printer.predicate(predicate), expected);
+        }
+      }
+    });
+  });
+}
+
+macro_rules! print_goals {
+  ($($name:ident),*) => {$(
+    #[test_log::test]
+    fn $name() {
+      assert_goals_match_rustc(stringify!($name));
+    }
+  )*}
+}
+
+print_goals! { contained, macros, methods, closures }
+
+/// Projections in a where clause are printed on their trait bound.
+#[test_log::test]
+fn impl_headers_sugar_projections() {
+  let headers = Mutex::new(vec![]);
+  tu::run_in_dir("contained", |path| {
+    tu::test_body_bundles(path, |bundle| {
+      for tree in bundle.trees.values() {
+        let mirror: SerializedTree = tu::mirror(tree);
+        let printer = Printer::new(&mirror.tys, PrintOptions::default());
+        for candidate in &mirror.candidates {
+          if let CandidateData::Impl { hd, .. } = candidate {
+            let hd = printer.resolve_impl_header(hd).unwrap();
+            headers.lock().unwrap().push(printer.impl_header(hd));
+          }
+        }
+      }
+    });
+  });
+
+  let headers = headers.into_inner().unwrap();
+  let expected = [
+    "I: Iterator<Item = u32>",
+    "F: Fn(u32) -> Fut",
+    "Fut: Future<Output = String>",
+    "F: AsyncFnOnce(u32) -> String",
+  ];
+  for bound in expected {
+    assert!(
+      headers.iter().any(|hd| hd.contains(bound)),
+      "no impl header with `{bound}` in {headers:#?}"
+    );
+  }
+}
+
+/// Anonymous consts from other crates are printed by their value, local
+/// ones as written.
+#[test_log::test]
+fn anon_const_in_impl_header() {
+  let mut headers = vec![];
+  for bundle in tu::contained_bundles("array-default.test") {
+    for tree in bundle.trees.values() {
+      let printer = Printer::new(&tree.tys, PrintOptions::default());
+      for candidate in &tree.candidates {
+        if let CandidateData::Impl { hd, .. } = candidate {
+          let hd = printer.resolve_impl_header(hd).unwrap();
+          headers.push(printer.impl_header(hd));
+        }
+      }
+    }
+  }
+
+  for expected in [
+    "impl<T> Default for [T; 0]",
+    "impl<T> Default for [T; 32] where T: Default",
+    "impl<T> Fill for [T; 1 + 2]",
+  ] {
+    assert!(
+      headers.iter().any(|hd| hd == expected),
+      "no `{expected}` in {headers:#?}"
+    );
+  }
+  assert!(
+    !headers.iter().any(|hd| hd.contains("constant")),
+    "{headers:#?}"
+  );
+}
+
+/// The generic arguments of an opaque type's traits are printed once, after
+/// the trait's name.
+#[test_log::test]
+fn opaque_trait_args() {
+  let mut goals = vec![];
+  for bundle in tu::contained_bundles("opaque-args.test") {
+    for tree in bundle.trees.values() {
+      let printer = Printer::new(&tree.tys, PrintOptions::default());
+      for goal in &tree.goals {
+        goals.push(printer.predicate(&goal.value.predicate));
+      }
+    }
+  }
+
+  let expected = "impl Into<String>: Copy";
+  assert!(
+    goals.iter().any(|goal| goal == expected),
+    "no `{expected}` in {goals:#?}"
+  );
+}
+
+/// Obligations of the body, then the goals and impl headers of its trees.
+fn print_body<'a>(
+  bundle: &'a BodyBundle,
+  printer: impl Fn(&'a [TyVal]) -> Printer<'a>,
+) -> Vec<String> {
+  let mut printed = vec![];
+  let body = &bundle.body;
+  let body_printer = printer(&body.tys);
+  for obligation in &body.obligations {
+    printed.push(body_printer.predicate(&obligation.obligation.predicate));
+    let Some(tree) = bundle.trees.get(&obligation.hash) else {
+      continue;
+    };
+    let tree_printer = printer(&tree.tys);
+    for goal in &tree.goals {
+      printed.push(tree_printer.predicate(&goal.value.predicate));
+    }
+    for candidate in &tree.candidates {
+      if let CandidateData::Impl { hd, .. } = candidate {
+        let hd = tree_printer.resolve_impl_header(hd).unwrap();
+        printed.push(tree_printer.impl_header(hd));
+      }
+    }
+  }
+  printed
+}
+
+/// Bundles with shared tables print the same as those of each body.
+#[test_log::test]
+fn shared_tables() {
+  let options = PrintOptions::default();
+  tu::run_in_dir("contained", |path| {
+    tu::test_shared_bundle(path, |bundles, shared| {
+      let shared: SharedBundle = tu::mirror(&shared);
+      assert_eq!(bundles.len(), shared.bodies.len());
+      for (bundle, shared_bundle) in bundles.iter().zip(&shared.bodies) {
+        let bundle: BodyBundle = tu::mirror(bundle);
+        let expected = print_body(&bundle, |tys| Printer::new(tys, options));
+        let actual =
+          print_body(shared_bundle, |_| Printer::shared(&shared, options));
+        assert_eq!(expected, actual);
+      }
+    });
+  });
+}
+
+/// Obligations of every body in "contained", with paths serialized
+/// following `options` and printed as they were serialized.
+fn print_obligations(options: SerializeOptions) -> Vec<String> {
+  let printed = Mutex::new(vec![]);
+  let print_options = PrintOptions {
+    trim_paths: false,
+    ..PrintOptions::default()
+  };
+  tu::run_in_dir("contained", |path| {
+    tu::test_body_bundles_with(path, options, |bundle| {
+      let bundle: BodyBundle = tu::mirror(&bundle);
+      let printer = Printer::new(&bundle.body.tys, print_options);
+      for obligation in &bundle.body.obligations {
+        let predicate = printer.predicate(&obligation.obligation.predicate);
+        printed.lock().unwrap().push(predicate);
+      }
+    });
+  });
+  printed.into_inner().unwrap()
+}
+
+#[test_log::test]
+fn path_styles() {
+  let with_paths = |paths| SerializeOptions {
+    paths,
+    ..SerializeOptions::default()
+  };
+  let expected = [
+    (
+      PathStyle::Qualified,
+      "alloc::vec::Vec<u8>: core::fmt::Display",
+    ),
+    (PathStyle::Visible, "std::vec::Vec<u8>: std::fmt::Display"),
+    (PathStyle::Trimmed, "Vec<u8>: Display"),
+  ];
+  for (paths, predicate) in expected {
+    let printed = print_obligations(with_paths(paths));
+    assert!(
+      printed.iter().any(|p| p == predicate),
+      "no `{predicate}` with {paths:?} paths in {printed:#?}"
+    );
+  }
+
+  let prefixed = print_obligations(SerializeOptions {
+    crate_prefix: true,
+    ..SerializeOptions::default()
+  });
+  assert!(prefixed
+    .iter()
+    .any(|p| p == "crate::Local: core::fmt::Display"));
+}
+
+/// Past the depth and width limits types are abbreviated, each placeholder
+/// pointing to a type of the same table, and trait goals list where they
+/// differ from their impl candidates.
+#[test_log::test]
+fn abbreviations_and_impl_diffs() {
+  let options = SerializeOptions {
+    max_ty_depth: Some(1),
+    max_ty_width: Some(1),
+    ..SerializeOptions::default()
+  };
+  let abbreviated = Mutex::new(vec![]);
+  let num_diffs = Mutex::new(0);
+  tu::run_in_dir("contained", |path| {
+    tu::test_body_bundles_with(path, options, |bundle| {
+      let bundle: BodyBundle = tu::mirror(&bundle);
+      for tree in bundle.trees.values() {
+        let printer = Printer::new(&tree.tys, PrintOptions::default());
+        for (&ty, placeholders) in &tree.ty_abbreviations {
+          assert!(placeholders.iter().all(|&p| p < tree.tys.len()));
+          assert!(!placeholders.contains(&ty));
+          abbreviated
+            .lock()
+            .unwrap()
+            .push(printer.abbreviated_ty(ty, placeholders));
+        }
+
+        for (goal, diffs) in &tree.impl_diffs {
+          assert!(matches!(tree.nodes[*goal], Node::Goal(_)));
+          for diff in diffs {
+            assert!(tree.all_impl_candidates[goal].contains(&diff.candidate));
+            assert!(!diff.args.is_empty());
+            assert!(diff.args.iter().all(|arg| arg.goal < tree.tys.len()
+              && arg.impl_ty < tree.tys.len()
+              && arg.goal != arg.impl_ty));
+          }
+          *num_diffs.lock().unwrap() += diffs.len();
+        }
+      }
+    });
+  });
+
+  let abbreviated = abbreviated.into_inner().unwrap();
+  assert!(!abbreviated.is_empty());
+  assert!(
+    abbreviated.iter().all(|ty| ty.contains("$1")),
+    "{abbreviated:#?}"
+  );
+  assert!(num_diffs.into_inner().unwrap() > 0);
+}
+
+/// A goal, the header of one of its impl candidates and the subterms where
+/// the two fail to unify.
+type ImplDiff = (String, String, Vec<(String, String)>);
+
+/// Goals of `impl-diffs.test` and their impl candidates.
+fn impl_diffs() -> Vec<ImplDiff> {
+  let mut diffs = vec![];
+  for bundle in tu::contained_bundles("impl-diffs.test") {
+    for tree in bundle.trees.values() {
+      let printer = Printer::new(&tree.tys, PrintOptions::default());
+      for (goal, candidates) in &tree.all_impl_candidates {
+        let Node::Goal(goal_idx) = tree.nodes[*goal] else {
+          panic!("impl candidates of {goal:?} aren't of a goal");
+        };
+        let predicate =
+          printer.predicate(&tree.goals[goal_idx].value.predicate);
+        for &candidate in candidates {
+          let CandidateData::Impl { hd, .. } = &tree.candidates[candidate]
+          else {
+            continue;
+          };
+          let hd =
+            printer.impl_header(printer.resolve_impl_header(hd).unwrap());
+          let args = tree
+            .impl_diffs
+            .get(goal)
+            .into_iter()
+            .flatten()
+            .filter(|diff| diff.candidate == candidate)
+            .flat_map(|diff| &diff.args)
+            .map(|arg| (printer.ty(arg.goal), printer.ty(arg.impl_ty)))
+            .collect();
+          diffs.push((predicate.clone(), hd, args));
+        }
+      }
+    }
+  }
+  diffs
+}
+
+/// Generic parameters of an impl unify with any subterm of the goal, only
+/// the subterms that can't are listed.
+#[test_log::test]
+fn impl_diffs_unify_params() {
+  let diffs = impl_diffs();
+  let diffs_of = |goal: &str, hd: &str| {
+    let matching = diffs
+      .iter()
+      .filter(|(g, h, _)| g == goal && h == hd)
+      .map(|(_, _, args)| args.clone())
+      .collect::<Vec<_>>();
+    assert!(!matching.is_empty(), "no `{hd}` for `{goal}` in {diffs:#?}");
+    matching
+  };
+
+  for args in diffs_of(
+    "Vec<NotCopy>: Show",
+    "impl<T> Show for Vec<T> where T: Copy",
+  ) {
+    assert!(args.is_empty(), "{args:?}");
+  }
+  for args in diffs_of("(u8, u16): Show", "impl Show for (u8, u8)") {
+    assert_eq!(args, [("u16".to_owned(), "u8".to_owned())]);
+  }
+  for args in diffs_of("(u8, u16): Pair", "impl<T> Pair for (T, T)") {
+    assert_eq!(args, [("u16".to_owned(), "T".to_owned())]);
+  }
+}
@@ -0,0 +1,70 @@
+use argus_lib::test_utils as tu;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+/// Sort the children of every proof tree node, these are serialized from
+/// a `HashSet` and so their order isn't stable.
+fn normalize(value: &mut Value) {
+  match value {
+    Value::Object(map) => {
+      if let Some(Value::Object(children)) = map
+        .get_mut("topology")
+        .and_then(|topology| topology.get_mut("children"))
+      {
+        for set in children.values_mut() {
+          if let Value::Array(set) = set {
+            set.sort_by_key(Value::as_u64);
+          }
+        }
+      }
+      map.values_mut().for_each(normalize);
+    }
+    Value::Array(values) => values.iter_mut().for_each(normalize),
+    _ => {}
+  }
+}
+
+/// Output of `argus-lib` must deserialize into the `argus-schema` mirror
+/// and serialize back to the same JSON.
+fn assert_round_trip<T: DeserializeOwned + Serialize>(value: &impl Serialize) {
+  let mut expected = serde_json::to_value(value).unwrap();
+  let mirror: T = serde_path_to_error::deserialize(expected.clone())
+    .unwrap_or_else(|e| panic!("failed to deserialize mirror: {e}"));
+  let mut actual = serde_json::to_value(mirror).unwrap();
+  normalize(&mut expected);
+  normalize(&mut actual);
+  assert!(expected == actual, "round trip changed the output");
+}
+
+macro_rules! round_trip_bodies {
+  ($($name:ident),*) => {$(
+    #[test_log::test]
+    fn $name() {
+      tu::run_in_dir(stringify!($name), |path| {
+        tu::test_body_bundles(path, |bundle| {
+          assert_round_trip::<argus_schema::types::BodyBundle>(&bundle);
+        });
+      });
+    }
+  )*}
+}
+
+round_trip_bodies! { contained, macros, methods, closures }
+
+#[test_log::test]
+fn items() {
+  tu::run_in_dir("items", |path| {
+    tu::test_item_bundles(path, |bundle| {
+      assert_round_trip::<argus_schema::types::ItemBundle>(&bundle);
+    });
+  });
+}
+
+#[test_log::test]
+fn shared_bundle() {
+  tu::run_in_dir("contained", |path| {
+    tu::test_shared_bundle(path, |_, shared| {
+      assert_round_trip::<argus_schema::types::SharedBundle>(&shared);
+    });
+  });
+}
@@ -23,12 +23,12 @@ fn stability() {
     for _ in 0 .. iterations {
       let mut hashes = vec![];
       tu::test_obligations_no_crash(path, |_, obligations| {
-        let body_hashes = obligations
+        let obligation_hashes = obligations
           .obligations
           .into_iter()
           .map(|o| o.hash)
           .collect::<Vec<_>>();
-        hashes.extend(body_hashes);
+        hashes.push((obligations.hash, obligation_hashes));
       });
       output_for_path.push(hashes);
     }
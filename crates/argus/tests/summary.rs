@@ -0,0 +1,30 @@
+use std::sync::Mutex;
+
+use argus_lib::{summary::Summary, test_utils as tu};
+
+#[test_log::test]
+fn trait_error_summary() {
+  let sites = Mutex::new(vec![]);
+  tu::run_in_dir("contained", |path| {
+    let file_sites = tu::test_trait_error_sites(path);
+    sites.lock().unwrap().extend(file_sites);
+  });
+
+  let sites = sites.into_inner().unwrap();
+  let summary = Summary::from(sites.clone());
+  log::info!("{summary:#?}");
+
+  assert!(summary.errors > 0, "no trait errors in `contained`");
+  assert!(
+    sites
+      .iter()
+      .all(|s| s.failing_trait.is_some() == s.self_ty.is_some()),
+    "trait and self type keyed separately {sites:#?}"
+  );
+  assert!(
+    !summary.by_trait.is_empty() && !summary.by_root_cause.is_empty(),
+    "errors weren't grouped {summary:#?}"
+  );
+  let grouped = summary.by_trait.iter().map(|g| g.count).sum::<usize>();
+  assert!(grouped <= summary.errors);
+}
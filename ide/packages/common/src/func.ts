@@ -8,7 +8,6 @@ import type {
   EvaluationResult,
   GenericArg,
   ObligationHash,
-  ObligationNecessity,
   Predicate,
   Region,
   Ty,
@@ -68,7 +67,7 @@ export function makeHighlightPosters(
 }
 
 export const isVisibleObligation = (
-  o: { necessity: ObligationNecessity; result: EvaluationResult },
+  o: { isNecessary: boolean; result: EvaluationResult },
   filterAmbiguities = false
 ) =>
   // Short-circuit ambiguities if we're filtering them
@@ -76,10 +75,8 @@ export const isVisibleObligation = (
     (o.result === "maybe-ambiguity" || o.result === "maybe-overflow") &&
     filterAmbiguities
   ) &&
-  // If the obligation is listed as necessary, it's visible
-  (o.necessity === "Yes" ||
-    // If the obligation is listed as necessary on error, and it failed, it's visible
-    (o.necessity === "OnError" && o.result === "no"));
+  // Necessity is decided by the backend, see `ObligationNecessity::is_necessary`
+  o.isNecessary;
 
 export function searchObject(obj: any, target: any) {
   for (let key in obj) {
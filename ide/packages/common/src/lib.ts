@@ -186,8 +186,10 @@ export type ArgusReturn<T extends ArgusCliOptions> = T extends "preload"
       ? ObligationsInBody[]
       : never;
 
-// serde-compatible type
-export type Result<T> = { Ok: T } | { Err: ArgusError };
+// serde-compatible type, outputs are tagged with their schema version
+export type Result<T> = ({ Ok: T } | { Err: ArgusError }) & {
+  schemaVersion: number;
+};
 
 export type ArgusError =
   | { type: "analysis-error"; error: string }
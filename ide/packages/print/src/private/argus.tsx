@@ -26,6 +26,7 @@ import {
   PrintGenericArg,
   PrintPolarity,
   PrintRegion,
+  PrintTrait,
   PrintTy,
   PrintTyKind
 } from "./ty";
@@ -173,7 +174,7 @@ const PrintClauseWithBounds = ({ o }: { o: ClauseWithBounds }) => {
   const sortedBounds = _.sortBy(o.bounds, bound =>
     "FnTrait" in bound
       ? 0
-      : "Trait" in bound
+      : "Trait" in bound || "AssocTrait" in bound
         ? 1
         : "Region" in bound
           ? 2
@@ -219,6 +220,8 @@ const PrintClauseBound = ({ o }: { o: ClauseBound }) => {
         <PrintDefinitionPath o={path} />
       </>
     );
+  } else if ("AssocTrait" in o) {
+    return <PrintTrait o={o.AssocTrait} />;
   } else if ("Region" in o) {
     return <PrintRegion o={o.Region} />;
   }
@@ -596,6 +596,37 @@ export const PrintPolarity = ({ o }: { o: Polarity }) => {
   return o === "Negative" ? "!" : o === "Maybe" ? "?" : null;
 };
 
+export const PrintAssocItem = ({ o }: { o: AssocItem }) => {
+  console.debug("Printing AssocItem", o);
+  return (
+    <>
+      {o.name} = <PrintTerm o={o.term} />
+    </>
+  );
+};
+
+export const PrintTrait = ({ o }: { o: Trait }) => {
+  console.debug("Printing Trait", o);
+  const prefix = <PrintPolarity o={o.polarity} />;
+  const name = <PrintDefinitionPath o={o.traitName} />;
+  const ownArgs = _.map(o.ownArgs, arg => <PrintGenericArg o={arg} />);
+  const assocArgs = _.map(o.assocArgs, arg => <PrintAssocItem o={arg} />);
+  const argComponents = [...ownArgs, ...assocArgs];
+  const list =
+    argComponents.length === 0 ? null : (
+      <Angled>
+        <CommaSeparated components={argComponents} />
+      </Angled>
+    );
+  return (
+    <>
+      {prefix}
+      {name}
+      {list}
+    </>
+  );
+};
+
 export const PrintOpaqueImplType = ({ o }: { o: OpaqueImpl }) => {
   console.debug("Printing OpaqueImplType", o);
 
@@ -619,37 +650,6 @@ export const PrintOpaqueImplType = ({ o }: { o: OpaqueImpl }) => {
     );
   };
 
-  const PrintAssocItem = ({ o }: { o: AssocItem }) => {
-    console.debug("Printing AssocItem", o);
-    return (
-      <>
-        {o.name} = <PrintTerm o={o.term} />
-      </>
-    );
-  };
-
-  const PrintTrait = ({ o }: { o: Trait }) => {
-    console.debug("Printing Trait", o);
-    const prefix = <PrintPolarity o={o.polarity} />;
-    const name = <PrintDefinitionPath o={o.traitName} />;
-    const ownArgs = _.map(o.ownArgs, arg => <PrintGenericArg o={arg} />);
-    const assocArgs = _.map(o.assocArgs, arg => <PrintAssocItem o={arg} />);
-    const argComponents = [...ownArgs, ...assocArgs];
-    const list =
-      argComponents.length === 0 ? null : (
-        <Angled>
-          <CommaSeparated components={argComponents} />
-        </Angled>
-      );
-    return (
-      <>
-        {prefix}
-        {name}
-        {list}
-      </>
-    );
-  };
-
   const fnTraits = _.map(o.fnTraits, trait => <PrintFnTrait o={trait} />);
   const traits = _.map(o.traits, trait => <PrintTrait o={trait} />);
   const lifetimes = _.map(o.lifetimes, lifetime => (
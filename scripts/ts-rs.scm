@@ -20,7 +20,7 @@
                 (scandir "crates"))))
 
 ;; Include files here if hand-written TS bindings are required.
-(define ext-dirs '())
+(define ext-dirs '("crates/argus-ser/src/hir"))
 
 (define dest-dir "ide/packages/common/src")
 